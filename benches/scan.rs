@@ -0,0 +1,40 @@
+//! The byte-class scanning skipping the bytes which can't begin or end a match, `src/byteset.rs`
+//!
+//! Compare a change against the previous commit by criterion baselines:
+//! `cargo bench --bench scan -- --save-baseline before` on it, then `cargo bench --bench scan -- --baseline before`.
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use find_digits::{extract_line, Generator};
+
+/// The generated calibration lines, mostly short ones with the digits near the ends
+fn calibration_lines() -> Vec<String> {
+    let mut generator = Generator::new(0);
+    (0..10_000).map(|_| generator.next_calibration().0).collect()
+}
+
+/// Long lines of bytes no match begins or ends with, the digits are in their middle
+fn sparse_lines() -> Vec<String> {
+    let filler = "abcdhijklmpqbcdhijklm".repeat(10);
+    (0..1_000).map(|n| format!("{filler}{}{filler}", n % 10)).collect()
+}
+
+fn scan(c: &mut Criterion) {
+    let mut group = c.benchmark_group("scan");
+    for (name, lines) in [("calibration", calibration_lines()), ("sparse", sparse_lines())] {
+        group.throughput(Throughput::Bytes(lines.iter().map(|line| line.len() as u64).sum()));
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                lines
+                    .iter()
+                    .filter_map(|line| extract_line(black_box(line)).ok())
+                    .count()
+            });
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, scan);
+criterion_main!(benches);
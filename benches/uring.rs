@@ -0,0 +1,73 @@
+//! The reads of `--io-backend uring`, `src/uring.rs`, against the buffered reads of the default backend
+//!
+//! Every configuration runs the binary over the same generated file. The file stays in the page cache, so the
+//! differences are the overhead of the ring and of the copies, not the latency of a device, drop the caches
+//! (`echo 3 > /proc/sys/vm/drop_caches`) between the runs of the configurations to measure one.
+//!
+//! The defaults of `--uring-depth 4` and `--uring-block-size 256K` keep 1 MiB in flight, enough to cover the
+//! latency of a NVMe read while the workers parse the previous blocks. On a cached file every configuration runs
+//! as fast as the default reader, the parsing is the bottleneck: a deeper ring or larger blocks only pay off on a
+//! device with a higher latency, tune them with this benchmark there.
+
+use std::{fs, hint::black_box, path::Path, process::Command};
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use find_digits::Generator;
+
+/// Size of the generated input
+const INPUT_SIZE: usize = 64 * 1024 * 1024;
+
+fn generate(path: &Path) {
+    let mut generator = Generator::new(0);
+    let mut content = String::with_capacity(INPUT_SIZE + 1024);
+    while content.len() < INPUT_SIZE {
+        content.push_str(&generator.next_calibration().0);
+        content.push('\n');
+    }
+    fs::write(path, content).unwrap();
+}
+
+fn run(path: &Path, backend: &[&str]) {
+    let status = Command::new(env!("CARGO_BIN_EXE_find_digits"))
+        .args(backend)
+        .arg("--file")
+        .arg(path)
+        .stdout(std::process::Stdio::null())
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
+
+fn uring(c: &mut Criterion) {
+    let path = tempfile::Builder::new()
+        .prefix("find_digits_")
+        .suffix(".txt")
+        .tempfile()
+        .unwrap()
+        .into_temp_path();
+    generate(&path);
+
+    let mut group = c.benchmark_group("uring");
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(INPUT_SIZE as u64));
+    group.bench_function("std", |b| b.iter(|| run(black_box(&path), &[])));
+    for depth in ["1", "4", "16"] {
+        for block_size in ["64K", "256K", "1M"] {
+            let backend = [
+                "--io-backend",
+                "uring",
+                "--uring-depth",
+                depth,
+                "--uring-block-size",
+                block_size,
+            ];
+            group.bench_function(format!("depth {depth} block {block_size}"), |b| {
+                b.iter(|| run(black_box(&path), &backend));
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, uring);
+criterion_main!(benches);
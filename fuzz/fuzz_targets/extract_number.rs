@@ -0,0 +1,19 @@
+#![no_main]
+
+use find_digits::{cross_check, extract_line_with, extract_number_with};
+use libfuzzer_sys::fuzz_target;
+
+mod options;
+
+fuzz_target!(|data: &[u8]| {
+    let Some((options, line)) = options::split(data) else {
+        return;
+    };
+
+    let value = extract_number_with(&line, &options);
+    assert!(value.map_or(true, |value| value < 100));
+    assert_eq!(value, extract_line_with(&line, &options).ok().map(|value| value.value));
+    if options.fuzzy == 0 {
+        assert_eq!(cross_check(&line, &options), Ok(()), "{line:?}");
+    }
+});
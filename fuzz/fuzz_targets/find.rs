@@ -0,0 +1,16 @@
+#![no_main]
+
+use find_digits::find_with;
+use libfuzzer_sys::fuzz_target;
+
+mod options;
+
+fuzz_target!(|data: &[u8]| {
+    let Some((options, line)) = options::split(data) else {
+        return;
+    };
+
+    let result = find_with(&line, &options);
+    assert!(result.last_parsed_position <= line.len());
+    assert!(result.number.map_or(true, |digit| digit.is_ascii_digit()));
+});
@@ -0,0 +1,18 @@
+use find_digits::{Overlap, ScanOptions};
+
+/// The options of the bits of the first byte of the input, the rest is the line
+pub fn split(data: &[u8]) -> Option<(ScanOptions, String)> {
+    let (&flags, line) = data.split_first()?;
+    let options = ScanOptions {
+        unicode_digits: flags & 1 != 0,
+        fullwidth_digits: flags & 2 != 0,
+        keycap_digits: flags & 4 != 0,
+        isolated_digits_only: flags & 8 != 0,
+        fuzzy: usize::from(flags & 16 != 0),
+        word_boundaries: flags & 64 != 0,
+        dictionary: None,
+        overlap: if flags & 32 == 0 { Overlap::Allow } else { Overlap::Consume },
+    };
+
+    Some((options, String::from_utf8_lossy(line).into_owned()))
+}
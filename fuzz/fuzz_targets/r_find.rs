@@ -0,0 +1,21 @@
+#![no_main]
+
+use find_digits::r_find_with;
+use libfuzzer_sys::fuzz_target;
+
+mod options;
+
+fuzz_target!(|data: &[u8]| {
+    // any position, e.g. inside a char or beyond the end of the line
+    let Some((position, data)) = data.split_first() else {
+        return;
+    };
+    let Some((options, line)) = options::split(data) else {
+        return;
+    };
+
+    let found_pos = usize::from(*position);
+    let result = r_find_with(&line, found_pos, &options);
+    assert!(result.last_parsed_position <= line.len());
+    assert!(result.number.map_or(true, |digit| digit.is_ascii_digit()));
+});
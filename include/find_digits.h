@@ -0,0 +1,140 @@
+/*
+ * C interface of find_digits, the libraries are built by
+ * `cargo rustc --release --lib --features capi --crate-type cdylib,staticlib`.
+ *
+ * The lines are UTF-8 byte strings with their lengths, they don't need a terminating zero.
+ * The functions return one of the statuses below, the outputs are written on FIND_DIGITS_OK only.
+ */
+
+#ifndef FIND_DIGITS_H
+#define FIND_DIGITS_H
+
+/* Generated by cbindgen from src/capi.rs, don't edit */
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+/**
+ * The line has a value
+ */
+#define FIND_DIGITS_OK 0
+
+/**
+ * The line has no digits
+ */
+#define FIND_DIGITS_NO_DIGITS 1
+
+/**
+ * The line is empty
+ */
+#define FIND_DIGITS_EMPTY 2
+
+/**
+ * The line is a null pointer or isn't UTF-8
+ */
+#define FIND_DIGITS_INVALID -1
+
+/**
+ * A configured [`DigitFinder`], opaque to C
+ */
+typedef struct FindDigitsFinder FindDigitsFinder;
+
+/**
+ * A digit of a line, `start..end` is its byte span
+ */
+typedef struct FindDigitsMatch {
+  uint8_t digit;
+  /**
+   * spelled with letters
+   */
+  bool is_word;
+  size_t start;
+  size_t end;
+} FindDigitsMatch;
+
+/**
+ * The value of a line with its first and last digits
+ */
+typedef struct FindDigitsLine {
+  uint32_t value;
+  struct FindDigitsMatch first;
+  struct FindDigitsMatch last;
+} FindDigitsLine;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Write the calibration value of the line to `out` (when it isn't null), return the status
+ *
+ * # Safety
+ *
+ * `line` points to `len` readable bytes, `out` is null or writable
+ */
+int32_t find_digits_extract(const char *line, size_t len, uint32_t *out);
+
+/**
+ * Write the value of the line with its first and last digits to `out` (when it isn't null), return the status
+ *
+ * # Safety
+ *
+ * `line` points to `len` readable bytes, `out` is null or writable
+ */
+int32_t find_digits_extract_line(const char *line,
+                                 size_t len,
+                                 struct FindDigitsLine *out);
+
+/**
+ * A finder of the dictionary (`<word> <digit>` per line, the English words when it's null), null when the
+ * dictionary is incorrect, it's released by [`find_digits_finder_free`]
+ *
+ * # Safety
+ *
+ * `dictionary` is null or points to `len` readable bytes
+ */
+struct FindDigitsFinder *find_digits_finder_new(const char *dictionary,
+                                                size_t len,
+                                                bool case_sensitive);
+
+/**
+ * [`find_digits_extract`] of the finder, the built-in options when it's null
+ *
+ * # Safety
+ *
+ * `finder` is null or made by [`find_digits_finder_new`], `line` points to `len` readable bytes,
+ * `out` is null or writable
+ */
+int32_t find_digits_finder_extract(const struct FindDigitsFinder *finder,
+                                   const char *line,
+                                   size_t len,
+                                   uint32_t *out);
+
+/**
+ * [`find_digits_extract_line`] of the finder, the built-in options when it's null
+ *
+ * # Safety
+ *
+ * `finder` is null or made by [`find_digits_finder_new`], `line` points to `len` readable bytes,
+ * `out` is null or writable
+ */
+int32_t find_digits_finder_extract_line(const struct FindDigitsFinder *finder,
+                                        const char *line,
+                                        size_t len,
+                                        struct FindDigitsLine *out);
+
+/**
+ * Release the finder, nothing happens when it's null
+ *
+ * # Safety
+ *
+ * `finder` is null or made by [`find_digits_finder_new`] and not released yet
+ */
+void find_digits_finder_free(struct FindDigitsFinder *finder);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* FIND_DIGITS_H */
@@ -0,0 +1,288 @@
+use std::{
+    fs::File,
+    io::{self, BufReader, Read},
+    panic,
+    path::Path,
+    process::exit,
+    sync::mpsc::{self, Receiver},
+    thread,
+    time::Instant,
+};
+
+use find_digits::{
+    counting::{ByteCounter, CountingReader},
+    Summary,
+};
+use regex::Regex;
+
+use crate::{
+    decompress::Compression,
+    process::{sum_read, Processing},
+};
+
+/// Bytes of the entry data sent to the reader thread at once
+const CHUNK_SIZE: usize = 64 << 10;
+/// Chunks in flight
+const CHUNKS: usize = 4;
+
+/// Layout of an archive of several files
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Archive {
+    /// possibly compressed: `.tar.gz`, `.tgz`, `.tar.zst`, ...
+    Tar,
+    Zip,
+}
+
+impl Archive {
+    /// The layout by the file name, `None` of a single file
+    pub fn of_file(file: &str) -> Option<Self> {
+        let name = Path::new(file).file_name()?.to_str()?.to_ascii_lowercase();
+        let (stem, extension) = name.rsplit_once('.')?;
+
+        match extension {
+            "zip" => Some(Self::Zip),
+            "tar" | "tgz" | "tbz2" | "txz" => Some(Self::Tar),
+            _ if Path::new(stem).extension().is_some_and(|extension| extension == "tar") => Some(Self::Tar),
+            _ => None,
+        }
+    }
+}
+
+/// Process the files of the archive one after another, their total is returned
+/// and every one of them is reported on its own
+///
+/// Only the files with the names matching the filter are processed, the lines are numbered from zero in every file.
+pub fn sum_archive(
+    file: &str,
+    archive: Archive,
+    processing: &Processing,
+    entries: Option<&Regex>,
+    physical_bytes: &ByteCounter,
+) -> Summary {
+    let f = match File::open(file) {
+        Ok(f) => f,
+        Err(e) => {
+            println!("Fail to open file {file}: {e:?}");
+            exit(1);
+        },
+    };
+
+    let mut total = Summary::default();
+    let mut add_entry = |name: &str, input: &mut dyn Read| {
+        if entries.is_some_and(|entries| !entries.is_match(name)) {
+            return;
+        }
+
+        let entry = format!("{file}:{name}");
+        let start = Instant::now();
+        let summary = sum_entry(&entry, processing, input);
+        println!("Entry {entry}: {summary}");
+        processing.add_file(&entry, &summary, start.elapsed());
+        total.merge(&summary);
+    };
+
+    let result = match archive {
+        Archive::Tar => sum_tar(file, CountingReader::with_counter(f, physical_bytes.clone()), &mut add_entry),
+        Archive::Zip => sum_zip(f, &mut add_entry),
+    };
+    if let Err(e) = result {
+        println!("Fail to read archive {file}: {e}");
+        exit(1);
+    }
+
+    // the logical sizes are of the entries, the physical one is of the whole archive
+    total.input_bytes.physical = match archive {
+        Archive::Tar => physical_bytes.get(),
+        Archive::Zip => std::fs::metadata(file).map_or(0, |metadata| metadata.len()),
+    };
+    total
+}
+
+/// Process the lines of an entry, the entry is read on this thread and its data is sent to the reader thread
+///
+/// The entries of the archives borrow their archives, they can't be moved to the reader thread themselves.
+fn sum_entry(entry: &str, processing: &Processing, input: &mut dyn Read) -> Summary {
+    thread::scope(|scope| {
+        let (sender, receiver) = mpsc::sync_channel(CHUNKS);
+        let reading = scope.spawn(move || {
+            let input = ChannelReader {
+                chunks: receiver,
+                chunk: Vec::new(),
+                position: 0,
+            };
+            sum_read(entry, processing, input, &ByteCounter::default())
+        });
+
+        loop {
+            let mut chunk = vec![0; CHUNK_SIZE];
+            match input.read(&mut chunk) {
+                Ok(0) => break,
+                Ok(length) => {
+                    chunk.truncate(length);
+                    if sender.send(Ok(chunk)).is_err() {
+                        break;
+                    }
+                },
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {},
+                Err(e) => {
+                    let _ = sender.send(Err(e));
+                    break;
+                },
+            }
+        }
+        drop(sender);
+
+        reading.join().unwrap_or_else(|e| panic::resume_unwind(e))
+    })
+}
+
+/// Reads the chunks sent by another thread
+struct ChannelReader {
+    chunks: Receiver<io::Result<Vec<u8>>>,
+    chunk: Vec<u8>,
+    /// of the first unread byte of the chunk
+    position: usize,
+}
+
+impl Read for ChannelReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.chunk.len() {
+            match self.chunks.recv() {
+                Ok(chunk) => {
+                    self.chunk = chunk?;
+                    self.position = 0;
+                },
+                // the end of the entry
+                Err(_) => return Ok(0),
+            }
+        }
+
+        let length = buf.len().min(self.chunk.len() - self.position);
+        buf[..length].copy_from_slice(&self.chunk[self.position..self.position + length]);
+        self.position += length;
+
+        Ok(length)
+    }
+}
+
+fn sum_tar(file: &str, input: impl Read + Send, add_entry: &mut dyn FnMut(&str, &mut dyn Read)) -> Result<(), String> {
+    let mut input = BufReader::new(input);
+    let compression = Compression::detect(file, io::BufRead::fill_buf(&mut input).unwrap_or_default());
+    let mut archive = tar::Archive::new(compression.decoder(input).map_err(|e| e.to_string())?);
+
+    for entry in archive.entries().map_err(|e| e.to_string())? {
+        let mut entry = entry.map_err(|e| e.to_string())?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+
+        let name = entry.path().map_err(|e| e.to_string())?.to_string_lossy().into_owned();
+        add_entry(&name, &mut entry);
+    }
+
+    Ok(())
+}
+
+fn sum_zip(input: File, add_entry: &mut dyn FnMut(&str, &mut dyn Read)) -> Result<(), String> {
+    let mut archive = zip::ZipArchive::new(input).map_err(|e| e.to_string())?;
+
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index).map_err(|e| e.to_string())?;
+        if !entry.is_file() {
+            continue;
+        }
+
+        let name = entry.name().to_owned();
+        add_entry(&name, &mut entry);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::{fs, io::Write};
+
+    use clap::Parser;
+    use flate2::{write::GzEncoder, Compression as Level};
+
+    use super::*;
+    use crate::testing::temp_dir;
+    use crate::Args;
+
+    #[test]
+    fn test_of_file() {
+        let files = [
+            "in.tar",
+            "IN.TAR.GZ",
+            "in.tgz",
+            "data/in.tar.zst",
+            "in.zip",
+            "in.txt",
+            "in.gz",
+            "tar",
+        ];
+        let expected = [
+            Some(Archive::Tar),
+            Some(Archive::Tar),
+            Some(Archive::Tar),
+            Some(Archive::Tar),
+            Some(Archive::Zip),
+            None,
+            None,
+            None,
+        ];
+
+        for (pos, file) in files.into_iter().enumerate() {
+            assert_eq!(Archive::of_file(file), expected[pos], "{file}");
+        }
+    }
+
+    #[test]
+    fn test_sum_archive() {
+        let files = [
+            ("a.txt", "two1nine\nabcdefg\n".to_owned()),
+            ("docs/b.txt", "7pqrstsixteen\n".repeat(10_000)),
+            ("skipped.log", "5\n".to_owned()),
+        ];
+        let directory = temp_dir();
+
+        let tar = directory.path().join("archive.tar.gz");
+        let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Level::default()));
+        builder.append_dir("docs", directory.path()).unwrap();
+        for (name, text) in &files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(text.len() as u64);
+            header.set_mode(0o644);
+            builder.append_data(&mut header, name, text.as_bytes()).unwrap();
+        }
+        fs::write(&tar, builder.into_inner().unwrap().finish().unwrap()).unwrap();
+
+        let zip = directory.path().join("archive.zip");
+        let mut writer = zip::ZipWriter::new(File::create(&zip).unwrap());
+        writer
+            .add_directory("docs", zip::write::SimpleFileOptions::default())
+            .unwrap();
+        for (name, text) in &files {
+            writer
+                .start_file(*name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(text.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap();
+
+        let entries = Regex::new(r"\.txt$").unwrap();
+        for path in [tar, zip] {
+            let file = path.to_str().unwrap();
+            let processing = Processing::new(&Args::parse_from(["find_digits", "--file", file])).unwrap();
+            let archive = Archive::of_file(file).unwrap();
+
+            let all = sum_archive(file, archive, &processing, None, &ByteCounter::default());
+            assert_eq!((all.parsed_lines, all.incorrect_lines, all.total_sum), (10_003, 1, 29 + 760_000 + 55));
+            assert_eq!(all.input_bytes.physical, fs::metadata(&path).unwrap().len());
+
+            let text = sum_archive(file, archive, &processing, Some(&entries), &ByteCounter::default());
+            assert_eq!((text.parsed_lines, text.incorrect_lines, text.total_sum), (10_002, 1, 29 + 760_000));
+        }
+    }
+}
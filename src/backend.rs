@@ -0,0 +1,151 @@
+use std::{
+    io,
+    pin::Pin,
+    process::exit,
+    task::{Context, Poll},
+};
+
+use clap::ValueEnum;
+use find_digits::{counting::ByteCounter, ByteCount, Summary};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncRead, BufReader, ReadBuf};
+
+use crate::process::{pipeline, sum_file, sum_read, Batcher, Processing};
+use crate::tee::message;
+
+/// How the input file is read
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IoBackend {
+    /// blocking reads of the standard library
+    #[default]
+    Std,
+    /// async reads on a tokio runtime, the reads of a local file run on its blocking pool
+    Tokio,
+    /// several reads ahead in flight on `io_uring`, Linux only
+    Uring,
+}
+
+/// The same as [`crate::process::sum_file`], the file is read by a tokio runtime on the reader thread
+///
+/// The file is local and not compressed, see `check_input`. `tokio::fs` reads it on the blocking pool of the
+/// runtime, the other async inputs are fed to the workers through [`read_batches`] the same way.
+pub fn sum_tokio(file: &str, processing: &Processing, physical_bytes: &ByteCounter) -> Summary {
+    let runtime = match tokio::runtime::Builder::new_current_thread().build() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            message!("Fail to start the tokio runtime: {e:?}");
+            exit(1);
+        },
+    };
+
+    let f = match runtime.block_on(tokio::fs::File::open(file)) {
+        Ok(f) => f,
+        Err(e) => {
+            message!("Fail to open file {file}: {e:?}");
+            exit(1);
+        },
+    };
+
+    let reader = BufReader::with_capacity(
+        processing.buffer_size,
+        CountingReader {
+            inner: f,
+            counter: physical_bytes.clone(),
+        },
+    );
+
+    let mut summary = pipeline(file, processing, move |collector| runtime.block_on(read_batches(reader, collector)));
+
+    let size = physical_bytes.get();
+    summary.input_bytes = ByteCount {
+        logical: size,
+        physical: size,
+    };
+
+    summary
+}
+
+/// The same as [`crate::process::sum_file`], the file is read ahead on `io_uring`
+///
+/// Falls back to the std reader when `io_uring` is not available.
+pub fn sum_uring(file: &str, processing: &Processing, physical_bytes: &ByteCounter) -> Summary {
+    #[cfg(target_os = "linux")]
+    {
+        let f = match std::fs::File::open(file) {
+            Ok(f) => f,
+            Err(e) => {
+                message!("Fail to open file {file}: {e:?}");
+                exit(1);
+            },
+        };
+
+        match crate::uring::UringReader::new(f, processing.uring_depth, processing.uring_block_size) {
+            Ok(reader) => return sum_read(file, processing, reader, physical_bytes),
+            Err(e) => message!("io_uring is not available ({e}), the std reader is used"),
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    message!("io_uring is available on Linux only, the std reader is used");
+
+    sum_file(file, processing, physical_bytes)
+}
+
+/// Read the numbered lines of any async input until its end or until the workers are gone
+///
+/// A full queue of the workers blocks the sending, it's backpressure of the runtime of this reader only.
+pub async fn read_batches<R>(mut reader: R, mut collector: Batcher<'_>)
+where
+    R: AsyncBufRead + Unpin,
+{
+    loop {
+        let buffer = collector.buffer();
+        let start = buffer.len();
+        let read_result = reader.read_line(buffer).await;
+
+        if !collector.push(start, read_result) {
+            break;
+        }
+    }
+
+    collector.finish();
+}
+
+/// Counts the bytes read from the inner async reader
+struct CountingReader<R> {
+    inner: R,
+    counter: ByteCounter,
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for CountingReader<R> {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        let before = buf.filled().len();
+        let poll = Pin::new(&mut self.inner).poll_read(cx, buf);
+
+        if let Poll::Ready(Ok(())) = poll {
+            self.counter.add((buf.filled().len() - before) as u64);
+        }
+
+        poll
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use clap::Parser;
+
+    use super::*;
+    use crate::testing::temp_file;
+    use crate::Args;
+
+    #[test]
+    fn test_backends() {
+        let path = temp_file(".txt", "two1nine\neightwothree\n\nabcdefg\nabc\u{FF}one2\nxtwone3four\n4nineeightseven2");
+        let file = path.to_str().unwrap();
+
+        let processing = Processing::new(&Args::parse_from(["find_digits", "--file", file])).unwrap();
+        let expected = sum_file(file, &processing, &ByteCounter::default());
+
+        assert_eq!(sum_tokio(file, &processing, &ByteCounter::default()), expected);
+        assert_eq!(sum_uring(file, &processing, &ByteCounter::default()), expected);
+    }
+}
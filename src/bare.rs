@@ -0,0 +1,205 @@
+//! The scanner without `std` and without allocations, for embedded targets and kernels
+//!
+//! The lines are byte strings, the digits are the ASCII ones and the spelled digits are the words of a table,
+//! the longest word at a position is matched. The results are the ones of [`crate::extract_number`] with
+//! the default options once the table is [`ENGLISH`], the words of the default dictionary of the `std` scanner.
+//! The tests check the matches of both scanners against each other.
+
+/// The spelled digits with their values `0..=9`
+pub type Words<'a> = &'a [(&'a [u8], u8)];
+
+/// `one`, `two`, ... `nine`
+pub const ENGLISH: Words<'static> = &[
+    (b"one", 1),
+    (b"two", 2),
+    (b"three", 3),
+    (b"four", 4),
+    (b"five", 5),
+    (b"six", 6),
+    (b"seven", 7),
+    (b"eight", 8),
+    (b"nine", 9),
+];
+
+/// A digit of a line, `start..end` is its byte span
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Digit {
+    pub value: u8,
+    pub start: usize,
+    pub end: usize,
+    /// spelled with letters
+    pub is_word: bool,
+}
+
+/// The first digit of the line
+#[must_use]
+pub fn first_digit(line: &[u8], words: Words<'_>) -> Option<Digit> {
+    (0..line.len()).find_map(|start| digit_at(line, start, words))
+}
+
+/// The last digit of the line ending after `from`, e.g. the beginning of the first digit
+#[must_use]
+pub fn last_digit(line: &[u8], from: usize, words: Words<'_>) -> Option<Digit> {
+    (from.saturating_add(1)..=line.len())
+        .rev()
+        .find_map(|end| digit_before(line, end, words))
+}
+
+/// The calibration value of the line: the first digit times ten plus the last digit
+#[must_use]
+pub fn extract_number(line: &[u8], words: Words<'_>) -> Option<u32> {
+    let first = first_digit(line, words)?;
+    let last = last_digit(line, first.start, words)?;
+
+    Some(u32::from(first.value) * 10 + u32::from(last.value))
+}
+
+/// The digit or the longest word beginning at `start`
+fn digit_at(line: &[u8], start: usize, words: Words<'_>) -> Option<Digit> {
+    let rest = line.get(start..)?;
+    if let Some(value) = rest.first().and_then(|&b| ascii_digit(b)) {
+        return Some(Digit {
+            value,
+            start,
+            end: start + 1,
+            is_word: false,
+        });
+    }
+
+    longest(
+        words
+            .iter()
+            .filter(|(word, _)| !word.is_empty() && rest.starts_with(word)),
+    )
+    .map(|(length, value)| Digit {
+        value,
+        start,
+        end: start + length,
+        is_word: true,
+    })
+}
+
+/// The digit or the longest word ending at `end`
+fn digit_before(line: &[u8], end: usize, words: Words<'_>) -> Option<Digit> {
+    let before = line.get(..end)?;
+    if let Some(value) = before.last().and_then(|&b| ascii_digit(b)) {
+        return Some(Digit {
+            value,
+            start: end - 1,
+            end,
+            is_word: false,
+        });
+    }
+
+    longest(
+        words
+            .iter()
+            .filter(|(word, _)| !word.is_empty() && before.ends_with(word)),
+    )
+    .map(|(length, value)| Digit {
+        value,
+        start: end - length,
+        end,
+        is_word: true,
+    })
+}
+
+/// The length and the value of the longest word, the last one of the table among the words of the same length
+fn longest<'a>(words: impl Iterator<Item = &'a (&'a [u8], u8)>) -> Option<(usize, u8)> {
+    words.fold(None, |longest, &(word, value)| match longest {
+        Some((length, _)) if length > word.len() => longest,
+        _ => Some((word.len(), value)),
+    })
+}
+
+fn ascii_digit(b: u8) -> Option<u8> {
+    b.is_ascii_digit().then(|| b - b'0')
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_extract_number() {
+        let strings: [&[u8]; 8] = [
+            b"two1nine",
+            b"eightwothree",
+            b"xtwone3four",
+            b"treb7uchet",
+            b"oneight",
+            b"\xFFseven\xC3",
+            b"abcdefg",
+            b"",
+        ];
+        let expected = [Some(29), Some(83), Some(24), Some(77), Some(18), Some(77), None, None];
+
+        for (pos, line) in strings.into_iter().enumerate() {
+            assert_eq!(extract_number(line, ENGLISH), expected[pos], "{line:?}");
+        }
+
+        let words: Words<'_> = &[(b"un", 1), (b"une", 8), (b"", 5)];
+        assert_eq!(extract_number(b"xune2un", words), Some(81));
+        assert_eq!(last_digit(b"one", 3, ENGLISH), None);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_same_as_scanner() {
+        let mut generator = crate::Generator::new(5);
+        for line in (0..5000).map(|_| generator.next_line()) {
+            assert_eq!(extract_number(line.as_bytes(), ENGLISH), crate::extract_number(&line), "{line}");
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_same_as_extract_line() {
+        use std::sync::Arc;
+
+        use crate::{extract_line_with, Dictionary, Match, ScanOptions};
+
+        let same = |digit: Option<Digit>, expected: &Match| {
+            digit.is_some_and(|digit| {
+                (char::from(b'0' + digit.value), digit.start, digit.end, digit.is_word)
+                    == (expected.digit, expected.start, expected.end, expected.is_word())
+            })
+        };
+        let lines = include_str!("../examples/example.txt").lines().chain([
+            "oneight",
+            "twone",
+            "sevenine",
+            "xune2un",
+            "caf\u{e9}one\u{1F600}two",
+            "un\u{e9}n\u{e9}",
+            "",
+        ]);
+        let words: Words<'_> = &[(b"un", 1), (b"une", 8), ("n\u{e9}".as_bytes(), 2)];
+        let tables = [
+            (ENGLISH, ScanOptions::default()),
+            (
+                words,
+                ScanOptions {
+                    dictionary: Some(Arc::new(Dictionary::parse("un 1\nune 8\nn\u{e9} 2\n").unwrap())),
+                    ..ScanOptions::default()
+                },
+            ),
+        ];
+
+        for line in lines {
+            for (words, options) in &tables {
+                let bytes = line.as_bytes();
+                let first = first_digit(bytes, words);
+                match extract_line_with(line, options) {
+                    Ok(expected) => {
+                        assert!(same(first, &expected.first), "{line}");
+                        let last = first.and_then(|first| last_digit(bytes, first.start, words));
+                        assert!(same(last, &expected.last), "{line}");
+                        assert_eq!(extract_number(bytes, words), Some(expected.value), "{line}");
+                    },
+                    Err(_) => assert_eq!(extract_number(bytes, words), None, "{line}"),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use std::thread;
+
+use crate::{extract_line_with, LineError, LineValue, ScanOptions};
+
+/// Below this amount of lines the batch is processed on the calling thread
+const PARALLEL_THRESHOLD: usize = 1024;
+
+/// Calibration values of all the lines, the result has the same order as the input
+///
+/// Big batches are split between the available CPUs,
+/// it's the entry point for the language bindings: one call per batch instead of one per line.
+#[must_use]
+pub fn extract_batch(lines: &[&str]) -> Vec<Result<LineValue, LineError>> {
+    extract_batch_with(lines, &ScanOptions::default())
+}
+
+/// [`extract_batch`] with custom scan options
+#[must_use]
+pub fn extract_batch_with(lines: &[&str], options: &ScanOptions) -> Vec<Result<LineValue, LineError>> {
+    let workers = thread::available_parallelism().map_or(1, std::num::NonZeroUsize::get);
+
+    if workers == 1 || lines.len() < PARALLEL_THRESHOLD {
+        return lines.iter().map(|line| extract_line_with(line, options)).collect();
+    }
+
+    let chunk_size = lines.len().div_ceil(workers);
+
+    thread::scope(|scope| {
+        let handles: Vec<_> = lines
+            .chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .map(|line| extract_line_with(line, options))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap_or_else(|e| std::panic::resume_unwind(e)))
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::extract_line;
+
+    #[test]
+    fn test_extract_batch_keeps_order() {
+        let samples = ["eightwothree", "", "treb7uchet", "abcdefg", "4nineeightseven2"];
+        let lines: Vec<&str> = samples
+            .iter()
+            .copied()
+            .cycle()
+            .take(PARALLEL_THRESHOLD * 3 + 7)
+            .collect();
+
+        let expected: Vec<_> = lines.iter().map(|line| extract_line(line)).collect();
+
+        assert_eq!(extract_batch(&lines), expected);
+        assert_eq!(extract_batch(&lines[..samples.len()]), expected[..samples.len()]);
+    }
+}
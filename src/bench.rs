@@ -0,0 +1,169 @@
+use std::{
+    fs,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use clap::{Args, ValueEnum};
+use find_digits::{
+    counting::ByteCounter, extract_batch_with, extract_line_with, extract_number_with, Generator, ScanOptions,
+};
+
+use crate::process::{sum_read, Processing};
+
+#[derive(Args, Debug, Clone)]
+pub struct BenchArgs {
+    /// The lines to extract, generated calibration lines without it
+    #[arg(long)]
+    corpus: Option<PathBuf>,
+    /// Amount of the generated lines
+    #[arg(long, default_value_t = 100_000)]
+    lines: usize,
+    /// Seed of the generated lines
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+    /// Runs of every engine, the median one is reported
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..), default_value_t = 5)]
+    iterations: u32,
+    /// The engines to run, e.g. `number,pipeline`, all of them without it
+    #[arg(long, value_enum, value_delimiter = ',')]
+    engine: Vec<Engine>,
+}
+
+/// How the values of the lines are extracted
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Engine {
+    /// `extract_number`, the value only
+    Number,
+    /// `extract_line`, the value and its first and last matches
+    Line,
+    /// `extract_batch`, the lines are split between the CPUs
+    Batch,
+    /// the reading and the worker threads of the files, with the options of the command line
+    Pipeline,
+}
+
+/// The median run of an engine
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Measure {
+    elapsed: Duration,
+    total_sum: u64,
+}
+
+pub fn run(args: &BenchArgs, options: &ScanOptions, processing: &Processing) -> Result<(), String> {
+    let (text, origin) = match &args.corpus {
+        Some(path) => {
+            let text = fs::read(path).map_err(|e| format!("Fail to read corpus {}: {e:?}", path.display()))?;
+            let text = String::from_utf8(text).map_err(|_| format!("Corpus {} isn't UTF-8", path.display()))?;
+            (text, path.display().to_string())
+        },
+        None => (generate(args.lines, args.seed), format!("generated (seed {})", args.seed)),
+    };
+    let lines: Vec<&str> = text.lines().collect();
+    if lines.is_empty() {
+        return Err("The corpus has no lines".to_owned());
+    }
+
+    println!(
+        "Corpus: {origin}, {} lines, {:.1} MB, {} runs per engine",
+        lines.len(),
+        text.len() as f64 / 1e6,
+        args.iterations
+    );
+
+    let engines = if args.engine.is_empty() {
+        Engine::value_variants().to_vec()
+    } else {
+        args.engine.clone()
+    };
+    for engine in engines {
+        let measure = measure(engine, &text, &lines, args.iterations, options, processing);
+        let seconds = measure.elapsed.as_secs_f64().max(f64::EPSILON);
+        println!(
+            "{:<8} {:>12.0} lines/s {:>9.1} MB/s  Total amount: {}, Elapsed {:?}",
+            format!("{engine:?}").to_lowercase(),
+            lines.len() as f64 / seconds,
+            text.len() as f64 / 1e6 / seconds,
+            measure.total_sum,
+            measure.elapsed
+        );
+    }
+
+    Ok(())
+}
+
+/// The lines of the built-in corpus, the same seed gives the same lines
+fn generate(lines: usize, seed: u64) -> String {
+    let mut generator = Generator::new(seed);
+    let mut text = String::new();
+    for _ in 0..lines {
+        text.push_str(&generator.next_line());
+        text.push('\n');
+    }
+
+    text
+}
+
+/// Run the engine over all the lines `iterations` times
+fn measure(
+    engine: Engine,
+    text: &str,
+    lines: &[&str],
+    iterations: u32,
+    options: &ScanOptions,
+    processing: &Processing,
+) -> Measure {
+    let mut runs: Vec<Measure> = (0..iterations)
+        .map(|_| {
+            let start_timestamp = Instant::now();
+            let total_sum = match engine {
+                Engine::Number => lines
+                    .iter()
+                    .filter_map(|line| extract_number_with(line, options))
+                    .map(u64::from)
+                    .sum(),
+                Engine::Line => lines
+                    .iter()
+                    .filter_map(|line| extract_line_with(line, options).ok())
+                    .map(|value| u64::from(value.value))
+                    .sum(),
+                Engine::Batch => extract_batch_with(lines, options)
+                    .into_iter()
+                    .filter_map(Result::ok)
+                    .map(|value| u64::from(value.value))
+                    .sum(),
+                Engine::Pipeline => sum_read("bench", processing, text.as_bytes(), &ByteCounter::default()).total_sum,
+            };
+
+            Measure {
+                elapsed: start_timestamp.elapsed(),
+                total_sum,
+            }
+        })
+        .collect();
+
+    runs.sort_by_key(|run| run.elapsed);
+    runs[runs.len() / 2]
+}
+
+#[cfg(test)]
+mod test {
+    use clap::Parser;
+
+    use super::*;
+
+    #[test]
+    fn test_measure() {
+        let text = generate(2000, 7);
+        let lines: Vec<&str> = text.lines().collect();
+        let processing = Processing::new(&crate::Args::parse_from(["find_digits", "bench"])).unwrap();
+
+        let expected = sum_read("bench", &processing, text.as_bytes(), &ByteCounter::default());
+        assert_eq!(expected.parsed_lines, 2000);
+
+        for engine in Engine::value_variants() {
+            let measure = measure(*engine, &text, &lines, 3, &ScanOptions::default(), &processing);
+            assert_eq!(measure.total_sum, expected.total_sum, "{engine:?}");
+        }
+    }
+}
@@ -0,0 +1,198 @@
+/// The lowest and the highest bits of every byte of a word
+const LOW: u64 = 0x0101_0101_0101_0101;
+const HIGH: u64 = LOW * 0x80;
+
+/// The most ranges of consecutive bytes a set is searched by words with, the bytes are tested one by one beyond it
+const MAX_RANGES: usize = 8;
+
+/// Set of byte values, the class of bytes a match can begin or end with
+///
+/// The ranges of consecutive bytes of the set are kept to test eight bytes at once (SWAR): every range takes
+/// a few arithmetic operations per word, the digits and the letters beginning the English digits are four ranges.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct ByteSet {
+    bits: [u64; 4],
+    /// `from..=to` within one half of the byte values, ASCII or not
+    ranges: [(u8, u8); MAX_RANGES],
+    /// the amount of the ranges, `None` beyond [`MAX_RANGES`]
+    len: Option<usize>,
+}
+
+impl ByteSet {
+    pub(crate) fn range(from: u8, to: u8) -> Self {
+        let mut bits = [0; 4];
+        for byte in from..=to {
+            bits[usize::from(byte >> 6)] |= 1 << (byte & 63);
+        }
+
+        Self::from_bits(bits)
+    }
+
+    fn from_bits(bits: [u64; 4]) -> Self {
+        let mut set = Self {
+            bits,
+            ranges: [(0, 0); MAX_RANGES],
+            len: Some(0),
+        };
+
+        let halves = [
+            u128::from(bits[0]) | u128::from(bits[1]) << 64,
+            u128::from(bits[2]) | u128::from(bits[3]) << 64,
+        ];
+        for (base, half) in [0u8, 0x80].into_iter().zip(halves) {
+            let mut rest = half;
+            while rest != 0 {
+                let start = rest.trailing_zeros();
+                let width = (!(rest >> start)).trailing_zeros();
+                rest &= !(u128::MAX.checked_shl(width).map_or(u128::MAX, |above| !above) << start);
+
+                set.len = set.len.filter(|&len| len < MAX_RANGES).map(|len| {
+                    // the bounds are within the half, below 0x80
+                    #[allow(clippy::cast_possible_truncation)]
+                    let range = (base + start as u8, base + (start + width - 1) as u8);
+                    set.ranges[len] = range;
+                    len + 1
+                });
+            }
+        }
+
+        set
+    }
+
+    pub(crate) fn insert(&mut self, byte: u8) {
+        let mut bits = self.bits;
+        bits[usize::from(byte >> 6)] |= 1 << (byte & 63);
+        *self = Self::from_bits(bits);
+    }
+
+    #[inline]
+    pub(crate) fn contains(&self, byte: u8) -> bool {
+        self.bits[usize::from(byte >> 6)] & (1 << (byte & 63)) != 0
+    }
+
+    #[must_use]
+    pub(crate) fn union(self, other: Self) -> Self {
+        Self::from_bits([
+            self.bits[0] | other.bits[0],
+            self.bits[1] | other.bits[1],
+            self.bits[2] | other.bits[2],
+            self.bits[3] | other.bits[3],
+        ])
+    }
+
+    /// The highest bit of every byte of the word (little endian) which is in the set
+    #[inline]
+    fn lanes(ranges: &[(u8, u8)], word: u64) -> u64 {
+        ranges.iter().fold(0, |lanes, &(from, to)| {
+            // the bytes of the other half are excluded, the rest is compared without its highest bit,
+            // so the subtractions never borrow from the next byte
+            let (half, candidates) = if from >= 0x80 {
+                (word ^ HIGH, word & HIGH)
+            } else {
+                (word, !word & HIGH)
+            };
+            let low = half & !HIGH;
+            let above = (low | HIGH) - LOW * u64::from(from & 0x7F);
+            let below = LOW * u64::from(to | 0x80) - low;
+
+            lanes | above & below & candidates
+        })
+    }
+
+    /// Position of the first byte of the set, eight bytes are tested at a time while the set has few ranges
+    pub(crate) fn find(&self, bytes: &[u8]) -> Option<usize> {
+        let Some(len) = self.len else {
+            return bytes.iter().position(|&byte| self.contains(byte));
+        };
+
+        let mut chunks = bytes.chunks_exact(8);
+        let mut offset = 0;
+        for chunk in chunks.by_ref() {
+            let lanes = Self::lanes(&self.ranges[..len], chunk.try_into().map_or(0, u64::from_le_bytes));
+            if lanes != 0 {
+                return Some(offset + lanes.trailing_zeros() as usize / 8);
+            }
+            offset += 8;
+        }
+
+        chunks
+            .remainder()
+            .iter()
+            .position(|&byte| self.contains(byte))
+            .map(|pos| offset + pos)
+    }
+
+    /// Position of the last byte of the set
+    pub(crate) fn rfind(&self, bytes: &[u8]) -> Option<usize> {
+        let Some(len) = self.len else {
+            return bytes.iter().rposition(|&byte| self.contains(byte));
+        };
+
+        let mut end = bytes.len();
+        for chunk in bytes.rchunks_exact(8) {
+            let lanes = Self::lanes(&self.ranges[..len], chunk.try_into().map_or(0, u64::from_le_bytes));
+            if lanes != 0 {
+                return Some(end - 1 - lanes.leading_zeros() as usize / 8);
+            }
+            end -= 8;
+        }
+
+        bytes[..end].iter().rposition(|&byte| self.contains(byte))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_find() {
+        let digits = ByteSet::range(b'0', b'9');
+        let line = b"abcdefghijklmnop7qrstuvwxyz8abcdefghijklmnop";
+
+        assert_eq!(digits.find(line), Some(16));
+        assert_eq!(digits.rfind(line), Some(27));
+        assert_eq!(digits.find(b"abcdefghijklmnopqrstuvwxyz"), None);
+        assert_eq!(digits.rfind(b"abcdefghijklmnopqrstuvwxyz"), None);
+        assert_eq!(digits.find(b""), None);
+        assert_eq!(digits.rfind(b"1"), Some(0));
+
+        let mut letters = ByteSet::default();
+        letters.insert(b'z');
+        assert_eq!(digits.union(letters).find(line), Some(16));
+        assert_eq!(digits.union(letters).rfind(line), Some(27));
+        assert!(!digits.contains(b'a'));
+    }
+
+    #[test]
+    fn test_find_every_byte() {
+        let mut sparse = ByteSet::default();
+        for byte in (0..=255).step_by(3) {
+            sparse.insert(byte);
+        }
+        let sets = [
+            ByteSet::range(0, 0),
+            ByteSet::range(b'0', b'9').union(ByteSet::range(0x80, 0xFF)),
+            ByteSet::range(0x7F, 0x80),
+            ByteSet::range(0xFF, 0xFF),
+            ByteSet::range(0, 0xFF),
+            sparse,
+        ];
+        assert_eq!(sets.map(|set| set.len), [Some(1), Some(2), Some(2), Some(1), Some(2), None]);
+
+        // every byte at every position of a word and of the remainder, among bytes of the other sets
+        for set in sets {
+            for byte in 0..=255 {
+                for pos in 0..19 {
+                    let mut bytes: Vec<u8> = (0..19).map(|n| [b'a', 0x81, 1, 0xFE][n % 4]).collect();
+                    bytes[pos] = byte;
+
+                    let expected = bytes.iter().position(|&byte| set.contains(byte));
+                    assert_eq!(set.find(&bytes), expected, "{set:?} {bytes:?}");
+                    let expected = bytes.iter().rposition(|&byte| set.contains(byte));
+                    assert_eq!(set.rfind(&bytes), expected, "{set:?} {bytes:?}");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,276 @@
+//! C interface of the extractor, declared by `include/find_digits.h` as cbindgen generates it (see `cbindgen.toml`)
+//!
+//! The shared and the static libraries are built on demand,
+//! `cargo rustc --release --lib --features capi --crate-type cdylib,staticlib`.
+//! The lines are UTF-8 byte strings with their lengths, they don't need a terminating zero.
+
+use std::{ffi::c_char, slice, str};
+
+use crate::{extract_line, Dictionary, DigitFinder, LineError, LineValue, Match};
+
+/// The line has a value
+pub const FIND_DIGITS_OK: i32 = 0;
+/// The line has no digits
+pub const FIND_DIGITS_NO_DIGITS: i32 = 1;
+/// The line is empty
+pub const FIND_DIGITS_EMPTY: i32 = 2;
+/// The line is a null pointer or isn't UTF-8
+pub const FIND_DIGITS_INVALID: i32 = -1;
+
+/// A digit of a line, `start..end` is its byte span
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FindDigitsMatch {
+    pub digit: u8,
+    /// spelled with letters
+    pub is_word: bool,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// The value of a line with its first and last digits
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FindDigitsLine {
+    pub value: u32,
+    pub first: FindDigitsMatch,
+    pub last: FindDigitsMatch,
+}
+
+/// A configured [`DigitFinder`], opaque to C
+#[derive(Debug)]
+pub struct FindDigitsFinder(DigitFinder);
+
+impl From<&Match> for FindDigitsMatch {
+    fn from(m: &Match) -> Self {
+        Self {
+            digit: m.value(),
+            is_word: m.is_word(),
+            start: m.start,
+            end: m.end,
+        }
+    }
+}
+
+/// The line of the finder, of the built-in options without it
+///
+/// # Safety
+///
+/// `line` points to `len` readable bytes, `finder` is null or made by [`find_digits_finder_new`]
+unsafe fn extract(finder: *const FindDigitsFinder, line: *const c_char, len: usize) -> Result<LineValue, i32> {
+    if line.is_null() {
+        return Err(FIND_DIGITS_INVALID);
+    }
+    let Ok(line) = str::from_utf8(slice::from_raw_parts(line.cast::<u8>(), len)) else {
+        return Err(FIND_DIGITS_INVALID);
+    };
+
+    let result = match finder.as_ref() {
+        Some(FindDigitsFinder(finder)) => finder.extract_line(line),
+        None => extract_line(line),
+    };
+    result.map_err(|e| match e {
+        LineError::Empty => FIND_DIGITS_EMPTY,
+        LineError::NoDigits => FIND_DIGITS_NO_DIGITS,
+    })
+}
+
+/// Write the calibration value of the line to `out` (when it isn't null), return the status
+///
+/// # Safety
+///
+/// `line` points to `len` readable bytes, `out` is null or writable
+#[no_mangle]
+pub unsafe extern "C" fn find_digits_extract(line: *const c_char, len: usize, out: *mut u32) -> i32 {
+    find_digits_finder_extract(std::ptr::null(), line, len, out)
+}
+
+/// Write the value of the line with its first and last digits to `out` (when it isn't null), return the status
+///
+/// # Safety
+///
+/// `line` points to `len` readable bytes, `out` is null or writable
+#[no_mangle]
+pub unsafe extern "C" fn find_digits_extract_line(line: *const c_char, len: usize, out: *mut FindDigitsLine) -> i32 {
+    find_digits_finder_extract_line(std::ptr::null(), line, len, out)
+}
+
+/// A finder of the dictionary (`<word> <digit>` per line, the English words when it's null), null when the
+/// dictionary is incorrect, it's released by [`find_digits_finder_free`]
+///
+/// # Safety
+///
+/// `dictionary` is null or points to `len` readable bytes
+#[no_mangle]
+pub unsafe extern "C" fn find_digits_finder_new(
+    dictionary: *const c_char,
+    len: usize,
+    case_sensitive: bool,
+) -> *mut FindDigitsFinder {
+    let mut builder = DigitFinder::builder().case_sensitive(case_sensitive);
+    if !dictionary.is_null() {
+        let table = str::from_utf8(slice::from_raw_parts(dictionary.cast::<u8>(), len));
+        match table.map(Dictionary::parse) {
+            Ok(Ok(dictionary)) => builder = builder.dictionary(dictionary),
+            _ => return std::ptr::null_mut(),
+        }
+    }
+
+    Box::into_raw(Box::new(FindDigitsFinder(builder.build())))
+}
+
+/// [`find_digits_extract`] of the finder, the built-in options when it's null
+///
+/// # Safety
+///
+/// `finder` is null or made by [`find_digits_finder_new`], `line` points to `len` readable bytes,
+/// `out` is null or writable
+#[no_mangle]
+pub unsafe extern "C" fn find_digits_finder_extract(
+    finder: *const FindDigitsFinder,
+    line: *const c_char,
+    len: usize,
+    out: *mut u32,
+) -> i32 {
+    match extract(finder, line, len) {
+        Ok(value) => {
+            if let Some(out) = out.as_mut() {
+                *out = value.value;
+            }
+            FIND_DIGITS_OK
+        },
+        Err(status) => status,
+    }
+}
+
+/// [`find_digits_extract_line`] of the finder, the built-in options when it's null
+///
+/// # Safety
+///
+/// `finder` is null or made by [`find_digits_finder_new`], `line` points to `len` readable bytes,
+/// `out` is null or writable
+#[no_mangle]
+pub unsafe extern "C" fn find_digits_finder_extract_line(
+    finder: *const FindDigitsFinder,
+    line: *const c_char,
+    len: usize,
+    out: *mut FindDigitsLine,
+) -> i32 {
+    match extract(finder, line, len) {
+        Ok(value) => {
+            if let Some(out) = out.as_mut() {
+                *out = FindDigitsLine {
+                    value: value.value,
+                    first: FindDigitsMatch::from(&value.first),
+                    last: FindDigitsMatch::from(&value.last),
+                };
+            }
+            FIND_DIGITS_OK
+        },
+        Err(status) => status,
+    }
+}
+
+/// Release the finder, nothing happens when it's null
+///
+/// # Safety
+///
+/// `finder` is null or made by [`find_digits_finder_new`] and not released yet
+#[no_mangle]
+pub unsafe extern "C" fn find_digits_finder_free(finder: *mut FindDigitsFinder) {
+    if !finder.is_null() {
+        drop(Box::from_raw(finder));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const HEADER: &str = include_str!("../include/find_digits.h");
+
+    fn call(finder: *const FindDigitsFinder, line: &[u8]) -> (i32, u32) {
+        let mut value = 0;
+        let status = unsafe { find_digits_finder_extract(finder, line.as_ptr().cast(), line.len(), &mut value) };
+        (status, value)
+    }
+
+    #[test]
+    fn test_extract() {
+        let strings: [&[u8]; 5] = [b"two1nine", b"xTWOne3four", b"abcdefg", b"", b"\xFF7"];
+        let expected = [
+            [
+                (FIND_DIGITS_OK, 29),
+                (FIND_DIGITS_OK, 34),
+                (FIND_DIGITS_NO_DIGITS, 0),
+                (FIND_DIGITS_EMPTY, 0),
+            ],
+            [
+                (FIND_DIGITS_OK, 11),
+                (FIND_DIGITS_OK, 13),
+                (FIND_DIGITS_NO_DIGITS, 0),
+                (FIND_DIGITS_EMPTY, 0),
+            ],
+        ];
+
+        let table = "one 1\nzwei 2\n";
+        let finder = unsafe { find_digits_finder_new(table.as_ptr().cast(), table.len(), false) };
+        assert!(!finder.is_null());
+
+        for (finder, expected) in [std::ptr::null(), finder.cast_const()].into_iter().zip(expected) {
+            for (pos, line) in strings.into_iter().take(expected.len()).enumerate() {
+                assert_eq!(call(finder, line), expected[pos], "{line:?}");
+            }
+            assert_eq!(call(finder, strings[4]), (FIND_DIGITS_INVALID, 0));
+        }
+        unsafe { find_digits_finder_free(finder) };
+
+        let mut value = FindDigitsLine::default();
+        let status = unsafe { find_digits_extract_line("xtwone3four".as_ptr().cast(), 11, &mut value) };
+        assert_eq!(status, FIND_DIGITS_OK);
+        assert_eq!(
+            value,
+            FindDigitsLine {
+                value: 24,
+                first: FindDigitsMatch {
+                    digit: 2,
+                    is_word: true,
+                    start: 1,
+                    end: 4
+                },
+                last: FindDigitsMatch {
+                    digit: 4,
+                    is_word: true,
+                    start: 7,
+                    end: 11
+                },
+            }
+        );
+
+        assert_eq!(unsafe { find_digits_extract(std::ptr::null(), 0, &mut 0) }, FIND_DIGITS_INVALID);
+        assert!(unsafe { find_digits_finder_new("one".as_ptr().cast(), 3, true) }.is_null());
+    }
+
+    #[test]
+    fn test_header() {
+        let functions: Vec<&str> = include_str!("capi.rs")
+            .lines()
+            .filter_map(|line| line.strip_prefix("pub unsafe extern \"C\" fn "))
+            .filter_map(|declaration| declaration.split('(').next())
+            .collect();
+        assert_eq!(functions.len(), 6);
+        for function in functions {
+            assert!(HEADER.contains(&format!("{function}(")), "{function}");
+        }
+
+        let constants = [
+            ("FIND_DIGITS_OK", FIND_DIGITS_OK),
+            ("FIND_DIGITS_NO_DIGITS", FIND_DIGITS_NO_DIGITS),
+            ("FIND_DIGITS_EMPTY", FIND_DIGITS_EMPTY),
+            ("FIND_DIGITS_INVALID", FIND_DIGITS_INVALID),
+        ];
+        for (name, value) in constants {
+            assert!(HEADER.contains(&format!("#define {name} {value}\n")), "{name}");
+        }
+    }
+}
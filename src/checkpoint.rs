@@ -0,0 +1,230 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use find_digits::Summary;
+
+/// The first line of a checkpoint file, the format may change with its version
+const HEADER: &str = "find_digits checkpoint 2";
+
+/// Where the processed lines end in the decoded input
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Position {
+    /// of the first byte after the processed lines
+    pub offset: u64,
+    /// the number of the first line after them
+    pub line: usize,
+}
+
+/// The totals of the lines before the position, enough to continue the processing after it
+///
+/// Only the totals and the line values are kept, the reports of the matched tokens and the top lines
+/// cover the lines processed after the last resume.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Checkpoint {
+    pub file: String,
+    pub position: Position,
+    pub summary: Summary,
+}
+
+impl Checkpoint {
+    /// `None` when there's no checkpoint yet
+    pub fn load(path: &Path) -> Result<Option<Self>, String> {
+        match fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text)
+                .map(Some)
+                .ok_or_else(|| format!("Checkpoint {} is corrupted", path.display())),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(format!("Fail to read checkpoint {}: {e:?}", path.display())),
+        }
+    }
+
+    /// Replace the checkpoint file by a new one at once, a crash leaves either the old or the new one
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let mut temporary = path.as_os_str().to_owned();
+        temporary.push(".tmp");
+        let temporary = PathBuf::from(temporary);
+
+        fs::write(&temporary, self.render())
+            .and_then(|()| fs::rename(&temporary, path))
+            .map_err(|e| format!("Fail to save checkpoint {}: {e:?}", path.display()))
+    }
+
+    fn render(&self) -> String {
+        let values: Vec<String> = self
+            .summary
+            .stats
+            .counts()
+            .map(|(value, count)| format!("{value}:{count}"))
+            .collect();
+
+        [
+            HEADER.to_owned(),
+            format!("file\t{}", self.file),
+            format!("offset\t{}", self.position.offset),
+            format!("line\t{}", self.position.line),
+            format!("parsed_lines\t{}", self.summary.parsed_lines),
+            format!("incorrect_lines\t{}", self.summary.incorrect_lines),
+            format!("empty_lines\t{}", self.summary.empty_lines),
+            format!("excluded_lines\t{}", self.summary.excluded_lines),
+            format!("unsampled_lines\t{}", self.summary.unsampled_lines),
+            format!("total_sum\t{}", self.summary.total_sum),
+            format!("overflowed\t{}", self.summary.overflowed),
+            format!("values\t{}", values.join(" ")),
+        ]
+        .join("\n")
+            + "\n"
+    }
+
+    fn parse(text: &str) -> Option<Self> {
+        let mut lines = text.lines();
+        if lines.next()? != HEADER {
+            return None;
+        }
+
+        let mut field = |name: &str| {
+            let (key, value) = lines.next()?.split_once('\t')?;
+            (key == name).then_some(value)
+        };
+
+        let mut checkpoint = Self {
+            file: field("file")?.to_owned(),
+            position: Position {
+                offset: field("offset")?.parse().ok()?,
+                line: field("line")?.parse().ok()?,
+            },
+            summary: Summary {
+                parsed_lines: field("parsed_lines")?.parse().ok()?,
+                incorrect_lines: field("incorrect_lines")?.parse().ok()?,
+                empty_lines: field("empty_lines")?.parse().ok()?,
+                excluded_lines: field("excluded_lines")?.parse().ok()?,
+                unsampled_lines: field("unsampled_lines")?.parse().ok()?,
+                total_sum: field("total_sum")?.parse().ok()?,
+                overflowed: field("overflowed")?.parse().ok()?,
+                ..Summary::default()
+            },
+        };
+
+        for value in field("values")?.split_whitespace() {
+            let (value, count) = value.split_once(':')?;
+            checkpoint
+                .summary
+                .stats
+                .add_count(value.parse().ok()?, count.parse().ok()?);
+        }
+
+        lines.next().is_none().then_some(checkpoint)
+    }
+}
+
+/// The totals of the lines before a position, the batches processed out of order wait for the preceding ones
+pub struct Progress {
+    /// the sequence number of the next batch to commit
+    next: u64,
+    pending: BTreeMap<u64, (Position, Summary)>,
+    position: Position,
+    summary: Summary,
+}
+
+impl Progress {
+    pub fn new(resumed: Option<&Checkpoint>) -> Self {
+        Self {
+            next: 0,
+            pending: BTreeMap::new(),
+            position: resumed.map_or_else(Position::default, |checkpoint| checkpoint.position),
+            summary: resumed.map_or_else(Summary::default, |checkpoint| checkpoint.summary.clone()),
+        }
+    }
+
+    /// Add the totals of the batch with the sequence number, it ends at the position
+    pub fn add(&mut self, sequence: u64, end: Position, part: Summary) {
+        self.pending.insert(sequence, (end, part));
+
+        while let Some((end, part)) = self.pending.remove(&self.next) {
+            self.summary.merge(&part);
+            self.position = end;
+            self.next += 1;
+        }
+    }
+
+    pub fn checkpoint(&self, file: &str) -> Checkpoint {
+        Checkpoint {
+            file: file.to_owned(),
+            position: self.position,
+            summary: self.summary.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::temp_dir;
+
+    #[test]
+    fn test_checkpoint() {
+        let mut summary = Summary {
+            parsed_lines: 5,
+            incorrect_lines: 1,
+            empty_lines: 1,
+            excluded_lines: 2,
+            total_sum: 29 + 83 + 83,
+            ..Summary::default()
+        };
+        for value in [29, 83, 83] {
+            summary.stats.add(value);
+        }
+        let checkpoint = Checkpoint {
+            file: "some where/input.txt".to_owned(),
+            position: Position { offset: 1234, line: 5 },
+            summary,
+        };
+
+        let directory = temp_dir();
+        let path = directory.path().join("checkpoint");
+        assert_eq!(Checkpoint::load(&path), Ok(None));
+
+        checkpoint.save(&path).unwrap();
+        assert_eq!(Checkpoint::load(&path), Ok(Some(checkpoint.clone())));
+
+        let text = fs::read_to_string(&path).unwrap();
+        assert!(text.contains("\nvalues\t29:1 83:2\n"));
+        for corrupted in [
+            text.replace("offset\t1234", "offset\tmany"),
+            text.replace("line\t5\n", ""),
+            text + "more\n",
+        ] {
+            fs::write(&path, corrupted).unwrap();
+            assert!(Checkpoint::load(&path).is_err());
+        }
+    }
+
+    #[test]
+    fn test_progress() {
+        let part = |parsed_lines| Summary {
+            parsed_lines,
+            ..Summary::default()
+        };
+        let end = |line| Position {
+            offset: line as u64 * 10,
+            line,
+        };
+
+        let mut progress = Progress::new(None);
+        progress.add(1, end(20), part(10));
+        assert_eq!(progress.checkpoint("in.txt").position, Position::default());
+
+        progress.add(0, end(10), part(10));
+        progress.add(3, end(40), part(10));
+        let checkpoint = progress.checkpoint("in.txt");
+        assert_eq!((checkpoint.position, checkpoint.summary.parsed_lines), (end(20), 20));
+
+        let mut resumed = Progress::new(Some(&checkpoint));
+        resumed.add(0, end(25), part(5));
+        let checkpoint = resumed.checkpoint("in.txt");
+        assert_eq!((checkpoint.position, checkpoint.summary.parsed_lines), (end(25), 25));
+    }
+}
@@ -0,0 +1,71 @@
+use std::{
+    io::{self, Cursor},
+    process::{exit, Command},
+};
+
+use find_digits::{counting::ByteCounter, Summary};
+
+use crate::{
+    process::{sum_read, Processing},
+    tee::message,
+};
+
+/// The name of the clipboard input in the messages and the reports
+pub const NAME: &str = "clipboard";
+
+/// The tools printing the text of the clipboard with their arguments, the first one which works is used
+#[cfg(target_os = "macos")]
+const TOOLS: &[(&str, &[&str])] = &[("pbpaste", &[])];
+#[cfg(windows)]
+const TOOLS: &[(&str, &[&str])] = &[("powershell", &["-NoProfile", "-Command", "Get-Clipboard -Raw"])];
+#[cfg(not(any(target_os = "macos", windows)))]
+const TOOLS: &[(&str, &[&str])] = &[
+    ("wl-paste", &["--no-newline"]),
+    ("xclip", &["-selection", "clipboard", "-out"]),
+    ("xsel", &["--clipboard", "--output"]),
+];
+
+/// The same as [`crate::process::sum_file`] of the text of the system clipboard
+pub fn sum_clipboard(processing: &Processing, physical_bytes: &ByteCounter) -> Summary {
+    match read(TOOLS) {
+        Ok(text) => sum_read(NAME, processing, Cursor::new(text), physical_bytes),
+        Err(e) => {
+            message!("{e}");
+            exit(1);
+        },
+    }
+}
+
+/// The output of the first tool which succeeds
+fn read(tools: &[(&str, &[&str])]) -> Result<Vec<u8>, String> {
+    let mut failures = Vec::new();
+    for (program, arguments) in tools {
+        match Command::new(program).args(*arguments).output() {
+            Ok(output) if output.status.success() => return Ok(output.stdout),
+            Ok(output) => failures.push(format!("{program}: {}", String::from_utf8_lossy(&output.stderr).trim())),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => failures.push(format!("{program} isn't installed")),
+            Err(e) => failures.push(format!("{program}: {e}")),
+        }
+    }
+
+    Err(format!("Fail to read the clipboard: {}", failures.join(", ")))
+}
+
+#[cfg(all(test, unix))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_read() {
+        let tools: &[(&str, &[&str])] = &[
+            ("find_digits_missing_tool", &[]),
+            ("sh", &["-c", "echo no clipboard >&2; exit 1"]),
+            ("printf", &["two1nine\\nabcdefg"]),
+        ];
+        assert_eq!(read(tools).unwrap(), b"two1nine\nabcdefg");
+        assert_eq!(
+            read(&tools[..2]).unwrap_err(),
+            "Fail to read the clipboard: find_digits_missing_tool isn't installed, sh: no clipboard"
+        );
+    }
+}
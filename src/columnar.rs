@@ -0,0 +1,321 @@
+use std::{fs::File, mem, path::Path, process::exit, sync::Arc};
+
+use find_digits::{counting::ByteCounter, ByteCount, Summary};
+use parquet::{
+    basic::Type as PhysicalType,
+    data_type::{ByteArray, ByteArrayType, DataType, Int64Type},
+    errors::{ParquetError, Result as ParquetResult},
+    file::{
+        metadata::KeyValue,
+        properties::WriterProperties,
+        reader::{FileReader, SerializedFileReader},
+        writer::{SerializedFileWriter, SerializedRowGroupWriter},
+    },
+    record::Field,
+    schema::{parser::parse_message_type, types::Type},
+};
+
+use crate::{
+    output::AtomicFile,
+    process::{pipeline, Processing},
+};
+
+/// Schema of the written line results, the columns are only ever added to keep the readers working
+const LINES_SCHEMA: &str = "
+    message find_digits_lines {
+        REQUIRED BYTE_ARRAY file (UTF8);
+        REQUIRED INT64 line;
+        OPTIONAL INT64 value;
+        REQUIRED GROUP tokens (LIST) {
+            REPEATED GROUP list {
+                REQUIRED BYTE_ARRAY element (UTF8);
+            }
+        }
+    }
+";
+/// Rows buffered before they're written as a row group
+const ROW_GROUP_ROWS: usize = 1 << 16;
+
+/// Process every cell of the string column of a Parquet file as a line, a null cell is an empty line
+pub fn sum_parquet(file: &str, column: &str, processing: &Processing, physical_bytes: &ByteCounter) -> Summary {
+    let (reader, projection) = match open(file, column) {
+        Ok(opened) => opened,
+        Err(e) => {
+            println!("Fail to read Parquet file {file}: {e}");
+            exit(1);
+        },
+    };
+    let size = std::fs::metadata(file).map_or(0, |metadata| metadata.len());
+
+    let progress = physical_bytes.clone();
+    let mut summary = pipeline(file, processing, move |mut collector| {
+        let rows = match reader.get_row_iter(Some(projection)) {
+            Ok(rows) => rows,
+            Err(e) => {
+                println!("Fail to read Parquet file {file}: {e}");
+                collector.finish();
+                return;
+            },
+        };
+
+        for row in rows {
+            let row = match row {
+                Ok(row) => row,
+                Err(e) => {
+                    println!("Fail to read Parquet file {file}: {e}");
+                    break;
+                },
+            };
+
+            let text = match row.get_column_iter().next() {
+                Some((_, Field::Str(text))) => text.as_str(),
+                _ => "",
+            };
+            // the cells are decoded in memory, their sizes are the progress of the reading
+            progress.add(text.len() as u64);
+
+            let buffer = collector.buffer();
+            let start = buffer.len();
+            buffer.push_str(text);
+            // the cell is read like a line with its newline, the length of zero is the end of the input
+            if !collector.push(start, Ok(text.len() + 1)) {
+                break;
+            }
+        }
+
+        collector.finish();
+    });
+
+    summary.input_bytes = ByteCount {
+        logical: physical_bytes.get(),
+        physical: size,
+    };
+    summary
+}
+
+/// The reader of the file and the projection of the file schema to the column
+fn open(file: &str, column: &str) -> Result<(SerializedFileReader<File>, Type), String> {
+    let f = File::open(file).map_err(|e| format!("{e:?}"))?;
+    let reader = SerializedFileReader::new(f).map_err(|e| e.to_string())?;
+
+    let schema = reader.metadata().file_metadata().schema();
+    let Some(field) = schema.get_fields().iter().find(|field| field.name() == column) else {
+        return Err(format!("no column {column}"));
+    };
+    if !field.is_primitive() || field.get_physical_type() != PhysicalType::BYTE_ARRAY {
+        return Err(format!("column {column} isn't a string one"));
+    }
+
+    let projection = Type::group_type_builder(schema.name())
+        .with_fields(vec![Arc::clone(field)])
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    Ok((reader, projection))
+}
+
+/// Parquet file of the results of the lines: the file, the line number, the value (null without one)
+/// and the list of the matched tokens, the totals are in the key-value metadata of the file
+///
+/// The file is written under a temporary name and renamed once finished.
+pub struct LinesWriter {
+    writer: SerializedFileWriter<AtomicFile>,
+    rows: Vec<LineRow>,
+}
+
+struct LineRow {
+    file: String,
+    line: i64,
+    value: Option<i64>,
+    tokens: Vec<String>,
+}
+
+impl LinesWriter {
+    pub fn create(path: &Path) -> Result<Self, String> {
+        let schema = parse_message_type(LINES_SCHEMA).map_err(|e| e.to_string())?;
+        let writer = SerializedFileWriter::new(
+            AtomicFile::create(path)?,
+            Arc::new(schema),
+            Arc::new(WriterProperties::builder().build()),
+        )
+        .map_err(|e| format!("Fail to write Parquet file {}: {e}", path.display()))?;
+
+        Ok(Self {
+            writer,
+            rows: Vec::with_capacity(ROW_GROUP_ROWS),
+        })
+    }
+
+    pub fn add(&mut self, file: &str, number: usize, value: Option<u32>, tokens: Vec<String>) -> Result<(), String> {
+        self.rows.push(LineRow {
+            file: file.to_owned(),
+            line: i64::try_from(number).unwrap_or(i64::MAX),
+            value: value.map(i64::from),
+            tokens,
+        });
+
+        if self.rows.len() >= ROW_GROUP_ROWS {
+            self.write_group()
+                .map_err(|e| format!("Fail to write Parquet row group: {e}"))?;
+        }
+        Ok(())
+    }
+
+    /// Write the buffered rows and the metadata, then replace the destination by the file
+    pub fn finish(mut self, metadata: Vec<(&str, String)>) -> Result<(), String> {
+        let error = |e: ParquetError| format!("Fail to finish Parquet file: {e}");
+
+        if !self.rows.is_empty() {
+            self.write_group().map_err(error)?;
+        }
+        for (key, value) in metadata {
+            self.writer
+                .append_key_value_metadata(KeyValue::new(key.to_owned(), value));
+        }
+
+        self.writer.into_inner().map_err(error)?.commit()
+    }
+
+    fn write_group(&mut self) -> ParquetResult<()> {
+        let rows = mem::take(&mut self.rows);
+        let mut group = self.writer.next_row_group()?;
+
+        let files: Vec<ByteArray> = rows.iter().map(|row| row.file.as_str().into()).collect();
+        write_column::<ByteArrayType>(&mut group, &files, None, None)?;
+
+        let lines: Vec<i64> = rows.iter().map(|row| row.line).collect();
+        write_column::<Int64Type>(&mut group, &lines, None, None)?;
+
+        let values: Vec<i64> = rows.iter().filter_map(|row| row.value).collect();
+        let defined: Vec<i16> = rows.iter().map(|row| i16::from(row.value.is_some())).collect();
+        write_column::<Int64Type>(&mut group, &values, Some(&defined), None)?;
+
+        // an empty list is a single undefined level, every token of a list but the first one repeats it
+        let (mut tokens, mut defined, mut repeated) = (Vec::new(), Vec::new(), Vec::new());
+        for row in &rows {
+            if row.tokens.is_empty() {
+                defined.push(0);
+                repeated.push(0);
+            }
+            for (pos, token) in row.tokens.iter().enumerate() {
+                tokens.push(ByteArray::from(token.as_str()));
+                defined.push(1);
+                repeated.push(i16::from(pos != 0));
+            }
+        }
+        write_column::<ByteArrayType>(&mut group, &tokens, Some(&defined), Some(&repeated))?;
+
+        group.close()?;
+        self.rows = rows;
+        self.rows.clear();
+        Ok(())
+    }
+}
+
+fn write_column<T: DataType>(
+    group: &mut SerializedRowGroupWriter<'_, AtomicFile>,
+    values: &[T::T],
+    defined: Option<&[i16]>,
+    repeated: Option<&[i16]>,
+) -> ParquetResult<()> {
+    let Some(mut column) = group.next_column()? else {
+        return Err(ParquetError::General("no column left in the schema".to_owned()));
+    };
+    column.typed::<T>().write_batch(values, defined, repeated)?;
+    column.close()
+}
+
+#[cfg(test)]
+mod test {
+    use clap::Parser;
+    use parquet::{
+        data_type::{ByteArray, ByteArrayType, Int32Type},
+        file::{properties::WriterProperties, writer::SerializedFileWriter},
+        schema::parser::parse_message_type,
+    };
+
+    use super::*;
+    use crate::testing::{temp_dir, temp_file};
+    use crate::Args;
+
+    #[test]
+    fn test_sum_parquet() {
+        let path = temp_file(".parquet", "");
+        let schema =
+            parse_message_type("message lines { REQUIRED INT32 id; OPTIONAL BYTE_ARRAY text (UTF8); }").unwrap();
+
+        let mut writer = SerializedFileWriter::new(
+            File::create(&path).unwrap(),
+            Arc::new(schema),
+            Arc::new(WriterProperties::builder().build()),
+        )
+        .unwrap();
+        // two row groups of the same rows
+        for _ in 0..2 {
+            let texts: Vec<ByteArray> = ["two1nine", "abcdefg", "seven\nthree"]
+                .into_iter()
+                .map(ByteArray::from)
+                .collect();
+
+            let mut group = writer.next_row_group().unwrap();
+            let mut ids = group.next_column().unwrap().unwrap();
+            ids.typed::<Int32Type>().write_batch(&[1, 2, 3, 4], None, None).unwrap();
+            ids.close().unwrap();
+            let mut column = group.next_column().unwrap().unwrap();
+            // the second cell is null
+            column
+                .typed::<ByteArrayType>()
+                .write_batch(&texts, Some(&[1, 0, 1, 1]), None)
+                .unwrap();
+            column.close().unwrap();
+            group.close().unwrap();
+        }
+        writer.close().unwrap();
+
+        let file = path.to_str().unwrap();
+        let processing = Processing::new(&Args::parse_from(["find_digits", "--file", file])).unwrap();
+
+        let summary = sum_parquet(file, "text", &processing, &ByteCounter::default());
+        assert_eq!((summary.parsed_lines, summary.incorrect_lines, summary.total_sum), (8, 4, 2 * (29 + 73)));
+        assert_eq!(summary.input_bytes.logical, 2 * (8 + 7 + 11));
+
+        assert_eq!(open(file, "missing").err(), Some("no column missing".to_owned()));
+        assert_eq!(open(file, "id").err(), Some("column id isn't a string one".to_owned()));
+    }
+
+    #[test]
+    fn test_lines_writer() {
+        let directory = temp_dir();
+        let path = directory.path().join("results.parquet");
+
+        let mut writer = LinesWriter::create(&path).unwrap();
+        writer
+            .add("in.txt", 0, Some(29), vec!["two".to_owned(), "1".to_owned(), "nine".to_owned()])
+            .unwrap();
+        writer.add("in.txt", 1, None, Vec::new()).unwrap();
+        writer.add("in.txt", 2, Some(77), vec!["7".to_owned()]).unwrap();
+        // nothing is written until the file is finished
+        assert!(!path.exists());
+        writer
+            .finish(vec![("find_digits.total_sum", "106".to_owned())])
+            .unwrap();
+
+        let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+        let rows: Vec<String> = reader
+            .get_row_iter(None)
+            .unwrap()
+            .map(|row| row.unwrap().to_string())
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                "{file: \"in.txt\", line: 0, value: 29, tokens: [\"two\", \"1\", \"nine\"]}",
+                "{file: \"in.txt\", line: 1, value: null, tokens: []}",
+                "{file: \"in.txt\", line: 2, value: 77, tokens: [\"7\"]}",
+            ]
+        );
+
+        let metadata = reader.metadata().file_metadata().key_value_metadata().unwrap();
+        assert_eq!((metadata[0].key.as_str(), metadata[0].value.as_deref()), ("find_digits.total_sum", Some("106")));
+    }
+}
@@ -0,0 +1,39 @@
+use std::io::{self, Write};
+
+use clap::Args;
+use clap_complete::Shell;
+
+#[derive(Args, Debug, Clone)]
+pub struct CompletionsArgs {
+    /// The shell of the completion script
+    #[arg(value_enum)]
+    shell: Shell,
+}
+
+/// Print the completion script of the commands and the options, e.g. to source it from `~/.bashrc`
+pub fn run(args: &CompletionsArgs) {
+    generate(args.shell, &mut io::stdout());
+}
+
+fn generate(shell: Shell, output: &mut impl Write) {
+    let mut command = crate::config::command();
+    let name = command.get_name().to_owned();
+    clap_complete::generate(shell, &mut command, name, output);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_generate() {
+        for shell in [Shell::Bash, Shell::Zsh, Shell::Fish, Shell::PowerShell] {
+            let mut script = Vec::new();
+            generate(shell, &mut script);
+
+            let script = String::from_utf8(script).unwrap();
+            assert!(script.contains("find_digits"), "{shell}");
+            assert!(script.contains("dictionary") && script.contains("scan"), "{shell}");
+        }
+    }
+}
@@ -0,0 +1,464 @@
+use std::{
+    env,
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+    process::exit,
+};
+
+use clap::{parser::ValueSource, Arg, ArgMatches, Command, CommandFactory, FromArgMatches};
+use toml::{Table, Value};
+
+use crate::{Args, PROCESSING_COMMANDS};
+
+/// Prefix of the environment variables of the options, e.g. `FIND_DIGITS_THREADS=4`
+const ENV_PREFIX: &str = "FIND_DIGITS_";
+/// The config file of the current directory, used unless `--config` is given
+const DEFAULT_FILE: &str = "find_digits.toml";
+
+/// The arguments of the command line completed by the environment variables and then by the config file
+pub fn parse() -> Args {
+    let arguments: Vec<OsString> = env::args_os().collect();
+    // the usage errors and `--help` exit as clap reports them
+    command().get_matches_from(&arguments);
+
+    match complete(arguments) {
+        Ok(args) => args,
+        Err(e) => {
+            println!("{e}");
+            exit(1);
+        },
+    }
+}
+
+/// The arguments completed again, e.g. by the changed config file, an error leaves the process running
+pub fn reload() -> Result<Args, String> {
+    complete(env::args_os().collect())
+}
+
+/// The command line parser: the commands processing lines take the options of the processing after them too
+pub fn command() -> Command {
+    let command = Args::command();
+    let options: Vec<Arg> = options(&command).cloned().collect();
+
+    PROCESSING_COMMANDS.into_iter().fold(command, |command, name| {
+        command.mut_subcommand(name, |subcommand| subcommand.args(options.iter().cloned()))
+    })
+}
+
+/// The options of the processing, the ones before the command
+fn options(command: &Command) -> impl Iterator<Item = &Arg> {
+    command
+        .get_arguments()
+        .filter(|arg| !arg.is_global_set() && !["help", "version"].contains(&arg.get_id().as_str()))
+}
+
+/// The arguments and their matches, the options given after the command are moved before it
+fn parse_from(arguments: Vec<OsString>) -> Result<(Args, ArgMatches), String> {
+    let command = command();
+    let matches = command
+        .clone()
+        .try_get_matches_from(&arguments)
+        .map_err(|e| e.to_string().trim_end().to_owned())?;
+
+    let moved = moved_options(&matches);
+    let matches = if moved.is_empty() {
+        matches
+    } else {
+        let mut arguments = arguments.into_iter();
+        let program = arguments.next().unwrap_or_default();
+        command
+            .try_get_matches_from([program].into_iter().chain(moved).chain(arguments))
+            .map_err(|e| e.to_string().trim_end().to_owned())?
+    };
+
+    let args = Args::from_arg_matches(&matches).map_err(|e| e.to_string().trim_end().to_owned())?;
+    Ok((args, matches))
+}
+
+/// The options of the processing given after the command, as arguments before it, e.g. `--file=in.txt`
+///
+/// They stay after the command too, where they're ignored. An option given both before and after the command
+/// is repeated then, an error of the options which can't be.
+fn moved_options(matches: &ArgMatches) -> Vec<OsString> {
+    let Some((_, subcommand)) = matches
+        .subcommand()
+        .filter(|(name, _)| PROCESSING_COMMANDS.contains(name))
+    else {
+        return Vec::new();
+    };
+
+    let command = Args::command();
+    let mut moved = Vec::new();
+    for arg in options(&command) {
+        let id = arg.get_id().as_str();
+        if subcommand.value_source(id) != Some(ValueSource::CommandLine) {
+            continue;
+        }
+
+        let long = arg.get_long().unwrap_or(id);
+        if arg.get_action().takes_values() {
+            for value in subcommand.get_raw(id).into_iter().flatten() {
+                let mut option = OsString::from(format!("--{long}="));
+                option.push(value);
+                moved.push(option);
+            }
+        } else {
+            moved.push(OsString::from(format!("--{long}")));
+        }
+    }
+
+    moved
+}
+
+fn complete(arguments: Vec<OsString>) -> Result<Args, String> {
+    let (args, matches) = parse_from(arguments.clone())?;
+
+    let path = args
+        .config
+        .clone()
+        .or_else(|| env::var_os(format!("{ENV_PREFIX}CONFIG")).map(PathBuf::from))
+        .or_else(|| Some(PathBuf::from(DEFAULT_FILE)).filter(|path| path.exists()));
+    let mut config = path.as_deref().map(load).transpose()?.unwrap_or_default();
+    let profile = args
+        .profile
+        .clone()
+        .or_else(|| env::var(format!("{ENV_PREFIX}PROFILE")).ok());
+    select_profile(&mut config, profile.as_deref())?;
+
+    // the command of the config file is run when the command line has none
+    let command = match config.remove("command").map(|command| config_values(&command)) {
+        Some(Some(command)) if matches.subcommand().is_none() => command,
+        Some(None) => {
+            return Err(
+                "Invalid command in the config file, it's an array like [\"scan\", \"--show\", \"explain\"]".to_owned()
+            )
+        },
+        _ => Vec::new(),
+    };
+
+    let defaults = defaults(&matches, &config, |name| env::var(name).ok())?;
+    let mut arguments = arguments.into_iter();
+    let program = arguments.next().unwrap_or_default();
+
+    // the options of the command of the config file are given as on the command line
+    let command_matches = match matches.subcommand() {
+        Some((name, subcommand)) => Some((name.to_owned(), subcommand.clone())),
+        None if !command.is_empty() => {
+            let arguments = [program.clone()].into_iter().chain(command.iter().map(OsString::from));
+            let (_, mut matches) = parse_from(arguments.collect())?;
+            matches.remove_subcommand()
+        },
+        None => None,
+    };
+    let command_defaults = match &command_matches {
+        Some((name, subcommand)) => command_defaults(name, subcommand, &config, |name| env::var(name).ok())?,
+        None => Vec::new(),
+    };
+    if defaults.is_empty() && command.is_empty() && command_defaults.is_empty() {
+        return Ok(args);
+    }
+
+    // the options come before the command, the ones of the command line are after them
+    // and the ones of the command after all of them
+    let arguments = [program]
+        .into_iter()
+        .chain(defaults.into_iter().map(OsString::from))
+        .chain(arguments)
+        .chain(command.into_iter().map(OsString::from))
+        .chain(command_defaults.into_iter().map(OsString::from));
+    parse_from(arguments.collect()).map(|(args, _)| args)
+}
+
+/// Replace the options of the config file by the ones of the `[profile.<name>]` table
+fn select_profile(config: &mut Table, name: Option<&str>) -> Result<(), String> {
+    let profiles = match config.remove("profile") {
+        Some(Value::Table(profiles)) => profiles,
+        Some(_) => return Err("The profiles of the config file are [profile.<name>] tables".to_owned()),
+        None => Table::new(),
+    };
+    let Some(name) = name else {
+        return Ok(());
+    };
+
+    let Some(Value::Table(profile)) = profiles.get(name) else {
+        let names: Vec<&str> = profiles.keys().map(String::as_str).collect();
+        return Err(format!("Unknown profile {name}, the config file has: {}", names.join(", ")));
+    };
+    for (key, value) in profile {
+        config.insert(key.clone(), value.clone());
+    }
+
+    Ok(())
+}
+
+fn load(path: &Path) -> Result<Table, String> {
+    let content =
+        fs::read_to_string(path).map_err(|e| format!("Fail to read config file {}: {e:?}", path.display()))?;
+
+    content
+        .parse()
+        .map_err(|e| format!("Fail to parse config file {}: {e}", path.display()))
+}
+
+/// The arguments of the options missing on the command line, e.g. `--threads=4`
+///
+/// The options are named by their identifiers in the config file (`buffer_size = "1M"`)
+/// and in the environment variables (`FIND_DIGITS_BUFFER_SIZE=1M`), the environment variables come first.
+/// The tables named by the commands hold their options, see [`command_defaults`].
+fn defaults(matches: &ArgMatches, config: &Table, var: impl Fn(&str) -> Option<String>) -> Result<Vec<String>, String> {
+    let command = Args::command();
+    let options: Vec<&Arg> = command
+        .get_arguments()
+        .filter(|arg| {
+            arg.get_long().is_some() && !["help", "version", "config", "profile"].contains(&arg.get_id().as_str())
+        })
+        .collect();
+
+    let is_known = |key: &str, value: &Value| {
+        let is_command = command.get_subcommands().any(|subcommand| subcommand.get_name() == key);
+        options.iter().any(|arg| arg.get_id() == key) || is_command && value.is_table()
+    };
+    if let Some((key, _)) = config.iter().find(|(key, value)| !is_known(key, value)) {
+        return Err(format!("Unknown option {key} in the config file"));
+    }
+
+    arguments(&options, matches, config, ENV_PREFIX, &var)
+}
+
+/// The arguments of the options of the command missing on its command line, e.g. `--top=5` of `stats`
+///
+/// They're named by the command too: `top = 5` of the `[stats]` table of the config file
+/// and `FIND_DIGITS_STATS_TOP=5` in the environment.
+fn command_defaults(
+    name: &str,
+    matches: &ArgMatches,
+    config: &Table,
+    var: impl Fn(&str) -> Option<String>,
+) -> Result<Vec<String>, String> {
+    let command = Args::command();
+    let Some(subcommand) = command.find_subcommand(name) else {
+        return Ok(Vec::new());
+    };
+    let options: Vec<&Arg> = subcommand
+        .get_arguments()
+        .filter(|arg| arg.get_long().is_some() && arg.get_id() != "help")
+        .collect();
+
+    let empty = Table::new();
+    let table = match config.get(name) {
+        Some(Value::Table(table)) => table,
+        Some(_) => return Err(format!("The options of command {name} are the [{name}] table of the config file")),
+        None => &empty,
+    };
+    if let Some(key) = table
+        .keys()
+        .find(|key| !options.iter().any(|arg| arg.get_id() == key.as_str()))
+    {
+        return Err(format!("Unknown option {key} of command {name} in the config file"));
+    }
+
+    let prefix = format!("{ENV_PREFIX}{}_", name.to_uppercase().replace('-', "_"));
+    arguments(&options, matches, table, &prefix, &var)
+}
+
+/// The arguments of the options not given on the command line, by the variable `<prefix><ID>` or else the config
+fn arguments(
+    options: &[&Arg],
+    matches: &ArgMatches,
+    config: &Table,
+    prefix: &str,
+    var: &impl Fn(&str) -> Option<String>,
+) -> Result<Vec<String>, String> {
+    let mut defaults = Vec::new();
+    for arg in options {
+        let id = arg.get_id().as_str();
+        if matches.value_source(id) == Some(ValueSource::CommandLine) {
+            continue;
+        }
+
+        let name = format!("{prefix}{}", id.to_uppercase());
+        let values = match (var(&name), config.get(id)) {
+            (Some(value), _) => vec![value],
+            (None, Some(value)) => {
+                config_values(value).ok_or_else(|| format!("Invalid value of {id} in the config file"))?
+            },
+            (None, None) => continue,
+        };
+
+        let long = arg.get_long().unwrap_or(id);
+        if arg.get_action().takes_values() {
+            defaults.extend(values.iter().map(|value| format!("--{long}={value}")));
+        } else {
+            match values.as_slice() {
+                [value] if ["true", "1", "yes"].contains(&value.as_str()) => defaults.push(format!("--{long}")),
+                [value] if ["false", "0", "no", ""].contains(&value.as_str()) => {},
+                _ => return Err(format!("Option {id} (or {name}) is a flag, its value is true or false")),
+            }
+        }
+    }
+
+    Ok(defaults)
+}
+
+/// The values of an option of the config file as given on the command line, an array is a repeated option
+fn config_values(value: &Value) -> Option<Vec<String>> {
+    match value {
+        Value::String(text) => Some(vec![text.clone()]),
+        Value::Integer(number) => Some(vec![number.to_string()]),
+        Value::Float(number) => Some(vec![number.to_string()]),
+        Value::Boolean(flag) => Some(vec![flag.to_string()]),
+        Value::Array(values) => values
+            .iter()
+            .map(config_values)
+            .try_fold(Vec::new(), |mut all, values| {
+                all.extend(values?);
+                Some(all)
+            }),
+        Value::Datetime(_) | Value::Table(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::num::NonZeroUsize;
+
+    use super::*;
+
+    #[test]
+    fn test_defaults() {
+        let config: Table = "threads = 2\nbuffer_size = \"1M\"\ndedup = true\nmmap = false\ndictionary = \"words.txt\""
+            .parse()
+            .unwrap();
+        let env = |name: &str| match name {
+            "FIND_DIGITS_THREADS" => Some("8".to_owned()),
+            "FIND_DIGITS_PARANOID" => Some("1".to_owned()),
+            "FIND_DIGITS_DICTIONARY" => Some("other.txt".to_owned()),
+            _ => None,
+        };
+        let matches = Args::command().get_matches_from(["find_digits", "--file", "in.txt", "--dictionary", "cli.txt"]);
+
+        let mut defaults = defaults(&matches, &config, env).unwrap();
+        defaults.sort();
+        assert_eq!(defaults, ["--buffer-size=1M", "--dedup", "--paranoid", "--threads=8"]);
+
+        let unknown: Table = "threads = 2\nthread = 4".parse().unwrap();
+        assert_eq!(
+            super::defaults(&matches, &unknown, |_| None),
+            Err("Unknown option thread in the config file".to_owned())
+        );
+        let flag = |_: &str| Some("sure".to_owned());
+        assert!(super::defaults(&matches, &Table::new(), flag).is_err());
+    }
+
+    #[test]
+    fn test_command_defaults() {
+        let config: Table = "[stats]\ntop = 2\npercentiles = [50, 90]".parse().unwrap();
+        let env = |name: &str| (name == "FIND_DIGITS_STATS_TOP").then(|| "3".to_owned());
+        let no_env = |_: &str| None;
+        let parse = |arguments: &[&str], var: &dyn Fn(&str) -> Option<String>| {
+            let arguments: Vec<OsString> = ["find_digits", "stats"]
+                .iter()
+                .chain(arguments)
+                .map(OsString::from)
+                .collect();
+            let (_, matches) = parse_from(arguments.clone()).unwrap();
+            let (name, subcommand) = matches.subcommand().unwrap();
+            let defaults = command_defaults(name, subcommand, &config, var).unwrap();
+            let (args, _) = parse_from(
+                arguments
+                    .into_iter()
+                    .chain(defaults.into_iter().map(OsString::from))
+                    .collect(),
+            )
+            .unwrap();
+            args.stats()
+        };
+
+        // the command line, then the environment and then the config file
+        assert_eq!(parse(&["--top", "1"], &env).top, 1);
+        assert_eq!(parse(&[], &env).top, 3);
+        let stats = parse(&[], &no_env);
+        assert_eq!((stats.top, stats.percentiles), (2, vec![50.0, 90.0]));
+
+        let unknown: Table = "[stats]\ntops = 2".parse().unwrap();
+        let (_, matches) = parse_from(["find_digits", "stats"].map(OsString::from).to_vec()).unwrap();
+        let (name, subcommand) = matches.subcommand().unwrap();
+        assert_eq!(
+            command_defaults(name, subcommand, &unknown, no_env),
+            Err("Unknown option tops of command stats in the config file".to_owned())
+        );
+        assert!(defaults(&matches, &unknown, no_env).is_ok());
+        assert!(defaults(&matches, &"stats = 2".parse().unwrap(), no_env).is_err());
+    }
+
+    #[test]
+    fn test_command_options() {
+        let parse = |arguments: &[&str]| {
+            let arguments = [&["find_digits"], arguments].concat();
+            parse_from(arguments.into_iter().map(OsString::from).collect()).map(|(args, _)| args)
+        };
+        let arguments: [&[&str]; 4] = [
+            &["sum", "-f", "x"],
+            &["scan", "--show", "explain", "-f", "x"],
+            &["--dedup", "stats", "--top", "3", "--file=x", "--lines", "1:4"],
+            &["-f", "x", "serve", "--threads", "2"],
+        ];
+
+        for arguments in arguments {
+            assert_eq!(parse(arguments).unwrap().file.as_deref(), Some("x"), "{arguments:?}");
+        }
+
+        let args = parse(arguments[1]).unwrap();
+        assert_eq!(args.scan().map(|scan_args| scan_args.show), Some(crate::scan::Show::Explain));
+        let args = parse(arguments[2]).unwrap();
+        assert_eq!((args.dedup, args.stats().top, args.lines), (true, 3, Some(1..4)));
+        assert_eq!(parse(arguments[3]).unwrap().threads.map(NonZeroUsize::get), Some(2));
+
+        // an option is given once, before or after the command
+        assert!(parse(&["-f", "x", "sum", "-f", "y"]).is_err());
+        // the other commands take their own options only
+        assert!(parse(&["bench", "-f", "x"]).is_err());
+    }
+
+    #[test]
+    fn test_select_profile() {
+        let text = "threads = 2\ndedup = true\n\n[profile.aoc]\ndictionary = \"words.txt\"\ncommand = [\"stats\"]\n\n\
+                    [profile.logs]\nthreads = 8\n";
+        let profiles = ["aoc", "logs"];
+        let expected = [
+            "command = [\"stats\"]\ndedup = true\ndictionary = \"words.txt\"\nthreads = 2\n",
+            "dedup = true\nthreads = 8\n",
+        ];
+
+        for (pos, profile) in profiles.into_iter().enumerate() {
+            let mut config: Table = text.parse().unwrap();
+            select_profile(&mut config, Some(profile)).unwrap();
+            assert_eq!(config.to_string(), expected[pos]);
+        }
+
+        let mut config: Table = text.parse().unwrap();
+        select_profile(&mut config, None).unwrap();
+        assert_eq!(config.to_string(), "dedup = true\nthreads = 2\n");
+        assert_eq!(
+            select_profile(&mut text.parse().unwrap(), Some("ci")),
+            Err("Unknown profile ci, the config file has: aoc, logs".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_config_values() {
+        let config: Table = "a = \"x\"\nb = 3\nc = [1, \"2\"]\nd = { e = 1 }".parse().unwrap();
+        let expected = [Some(vec!["x"]), Some(vec!["3"]), Some(vec!["1", "2"]), None];
+
+        for (pos, key) in ["a", "b", "c", "d"].into_iter().enumerate() {
+            let values = config_values(&config[key]);
+            assert_eq!(
+                values,
+                expected[pos]
+                    .as_ref()
+                    .map(|values| values.iter().map(|&v| v.to_owned()).collect())
+            );
+        }
+    }
+}
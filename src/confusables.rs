@@ -0,0 +1,120 @@
+use std::{borrow::Cow, collections::HashMap};
+
+/// Look-alikes of digits and of the letters of spelled digits
+static BUILTIN: [(char, char); 28] = [
+    ('O', '0'),
+    ('l', '1'),
+    ('I', '1'),
+    ('|', '1'),
+    // Cyrillic
+    ('\u{417}', '3'),
+    ('\u{437}', '3'),
+    ('\u{431}', '6'),
+    ('\u{430}', 'a'),
+    ('\u{435}', 'e'),
+    ('\u{456}', 'i'),
+    ('\u{43E}', 'o'),
+    ('\u{440}', 'p'),
+    ('\u{441}', 'c'),
+    ('\u{443}', 'y'),
+    ('\u{445}', 'x'),
+    ('\u{455}', 's'),
+    ('\u{4BB}', 'h'),
+    ('\u{501}', 'd'),
+    ('\u{51D}', 'w'),
+    ('\u{475}', 'v'),
+    // Greek
+    ('\u{3BF}', 'o'),
+    ('\u{3BD}', 'v'),
+    ('\u{3B9}', 'i'),
+    ('\u{3C5}', 'u'),
+    ('\u{3C4}', 't'),
+    ('\u{3B7}', 'n'),
+    // Latin
+    ('\u{131}', 'i'),
+    ('\u{261}', 'g'),
+];
+
+/// Mapping of confusable characters applied to a line before the matching
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Confusables {
+    map: HashMap<char, char>,
+}
+
+impl Confusables {
+    /// The built-in table: `O` → `0`, `l`/`I` → `1` and Cyrillic/Greek look-alikes of the Latin letters
+    #[must_use]
+    pub fn builtin() -> Self {
+        Self {
+            map: BUILTIN.into_iter().collect(),
+        }
+    }
+
+    /// Mapping table with one `<from> <to>` pair of characters per line, `#` starts a comment
+    ///
+    /// # Errors
+    ///
+    /// a line is not a pair of single characters
+    pub fn parse(table: &str) -> Result<Self, String> {
+        let mut map = HashMap::new();
+
+        for (number, line) in table.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut fields = line.split_whitespace().map(|field| {
+                let mut chars = field.chars();
+                chars.next().filter(|_| chars.next().is_none())
+            });
+
+            match (fields.next(), fields.next(), fields.next()) {
+                (Some(Some(from)), Some(Some(to)), None) => {
+                    map.insert(from, to);
+                },
+                _ => return Err(format!("incorrect mapping (line number {number}): {line:?}")),
+            }
+        }
+
+        Ok(Self { map })
+    }
+
+    /// The line with every confusable replaced, it isn't copied when nothing is replaced
+    #[must_use]
+    pub fn normalize<'a>(&self, line: &'a str) -> Cow<'a, str> {
+        if !line.chars().any(|c| self.map.contains_key(&c)) {
+            return Cow::Borrowed(line);
+        }
+
+        Cow::Owned(line.chars().map(|c| self.map.get(&c).copied().unwrap_or(c)).collect())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_builtin() {
+        let confusables = Confusables::builtin();
+        let strings = ["abc", "lO", "\u{3C4}w\u{43E}", "s\u{435}v\u{435}n"];
+        let expected = ["abc", "10", "two", "seven"];
+
+        for (pos, line) in strings.into_iter().enumerate() {
+            assert_eq!(confusables.normalize(line), expected[pos]);
+        }
+
+        assert!(matches!(confusables.normalize("abc"), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_parse() {
+        let confusables = Confusables::parse("# comment\nS 5\n\nZ 2\n").unwrap();
+        assert_eq!(confusables.normalize("SZx"), "52x");
+
+        assert!(Confusables::parse("S").is_err());
+        assert!(Confusables::parse("SS 5").is_err());
+        assert!(Confusables::parse("S 5 6").is_err());
+    }
+}
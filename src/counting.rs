@@ -0,0 +1,113 @@
+use std::{
+    io::{self, Read, Write},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+/// Shared amount of bytes passed through a [`CountingReader`] or a [`CountingWriter`]
+#[derive(Debug, Clone, Default)]
+pub struct ByteCounter(Arc<AtomicU64>);
+
+impl ByteCounter {
+    #[must_use]
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Count the bytes passed through a reader of another kind, e.g. an async one
+    pub fn add(&self, bytes: u64) {
+        self.0.fetch_add(bytes, Ordering::Relaxed);
+    }
+}
+
+/// Reader counting the bytes read from the inner reader
+///
+/// Wrapping the raw file gives the physical size, wrapping a decoder gives the logical one.
+#[derive(Debug)]
+pub struct CountingReader<R> {
+    inner: R,
+    counter: ByteCounter,
+}
+
+impl<R> CountingReader<R> {
+    pub fn new(inner: R) -> (Self, ByteCounter) {
+        let counter = ByteCounter::default();
+
+        (Self::with_counter(inner, counter.clone()), counter)
+    }
+
+    /// The reader adding to an existing counter, e.g. one watched by another thread
+    pub fn with_counter(inner: R, counter: ByteCounter) -> Self {
+        Self { inner, counter }
+    }
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.counter.add(read as u64);
+
+        Ok(read)
+    }
+}
+
+/// Writer counting the bytes written to the inner writer, e.g. the size of an output file
+#[derive(Debug)]
+pub struct CountingWriter<W> {
+    inner: W,
+    counter: ByteCounter,
+}
+
+impl<W> CountingWriter<W> {
+    pub fn new(inner: W) -> (Self, ByteCounter) {
+        let counter = ByteCounter::default();
+
+        (
+            Self {
+                inner,
+                counter: counter.clone(),
+            },
+            counter,
+        )
+    }
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.counter.add(written as u64);
+
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_counting_reader() {
+        let (mut reader, counter) = CountingReader::new(&b"two1nine\neightwothree\n"[..]);
+
+        let mut content = String::new();
+        reader.read_to_string(&mut content).unwrap();
+
+        assert_eq!(counter.get(), 22);
+    }
+
+    #[test]
+    fn test_counting_writer() {
+        let (mut writer, counter) = CountingWriter::new(Vec::new());
+
+        writer.write_all(b"1\tno_digits\tabc\n").unwrap();
+        writeln!(writer, "{}\tempty\t", 2).unwrap();
+
+        assert_eq!(counter.get(), 25);
+    }
+}
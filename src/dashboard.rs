@@ -0,0 +1,292 @@
+//! The `--tui` dashboard of a running job, with the `tui` feature
+
+use std::{
+    collections::VecDeque,
+    io::{self, IsTerminal},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use find_digits::{counting::ByteCounter, LineStats, Summary};
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    layout::{Constraint, Layout},
+    style::{Color, Style},
+    text::Line,
+    widgets::{Bar, BarChart, BarGroup, Block, List, Paragraph},
+    DefaultTerminal, Frame,
+};
+
+use crate::{signals, tee};
+
+/// Rejected lines shown, the latest ones
+const REJECTED_LINES: u16 = 10;
+/// Characters of a shown rejected line
+const LINE_WIDTH: usize = 200;
+/// Period of the throughput and of the rolling histogram
+const WINDOW: Duration = Duration::from_secs(10);
+/// Delay between the frames, the keys are read meanwhile
+const FRAME_DELAY: Duration = Duration::from_millis(250);
+/// Width of the buckets of the histogram
+const BUCKET_WIDTH: u32 = 10;
+
+/// The live totals of a job, updated by the pipeline and the workers and drawn by [`show`]
+#[derive(Debug, Default)]
+pub struct Dashboard {
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    summary: Summary,
+    /// the numbers and the beginnings of the latest rejected lines, the oldest first
+    rejected: VecDeque<(usize, String)>,
+}
+
+impl Dashboard {
+    /// The totals so far, e.g. merged by the pipeline
+    pub fn update(&self, summary: &Summary) {
+        self.state().summary.clone_from(summary);
+    }
+
+    /// Show the line without a value among the latest rejected ones
+    pub fn reject(&self, number: usize, line: &str) {
+        let line = line.chars().take(LINE_WIDTH).collect();
+        let mut state = self.state();
+        if state.rejected.len() == usize::from(REJECTED_LINES) {
+            state.rejected.pop_front();
+        }
+        state.rejected.push_back((number, line));
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// The totals at a moment of the job
+#[derive(Debug, Clone)]
+struct Sample {
+    at: Instant,
+    lines: u64,
+    bytes: u64,
+    stats: LineStats,
+}
+
+/// The samples of the last [`WINDOW`], the rates and the histogram are of the oldest and the latest ones
+#[derive(Debug, Default)]
+struct Window {
+    samples: VecDeque<Sample>,
+}
+
+impl Window {
+    fn push(&mut self, sample: Sample) {
+        while self
+            .samples
+            .get(1)
+            .is_some_and(|next| sample.at.duration_since(next.at) >= WINDOW)
+        {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    /// The oldest and the latest samples, `None` before the second one
+    fn ends(&self) -> Option<(&Sample, &Sample)> {
+        match (self.samples.front(), self.samples.back()) {
+            (Some(oldest), Some(latest)) if self.samples.len() > 1 => Some((oldest, latest)),
+            _ => None,
+        }
+    }
+
+    /// Lines and bytes per second
+    fn throughput(&self) -> (f64, f64) {
+        let Some((oldest, latest)) = self.ends() else {
+            return (0.0, 0.0);
+        };
+        let seconds = latest.at.duration_since(oldest.at).as_secs_f64().max(f64::EPSILON);
+
+        ((latest.lines - oldest.lines) as f64 / seconds, latest.bytes.saturating_sub(oldest.bytes) as f64 / seconds)
+    }
+
+    /// The buckets of the values of the lines processed within the window
+    fn histogram(&self) -> Vec<(u32, u64)> {
+        let Some((oldest, latest)) = self.ends() else {
+            return LineStats::default().buckets(BUCKET_WIDTH);
+        };
+
+        latest
+            .stats
+            .buckets(BUCKET_WIDTH)
+            .into_iter()
+            .zip(oldest.stats.buckets(BUCKET_WIDTH))
+            .map(|((start, count), (_, before))| (start, count - before))
+            .collect()
+    }
+}
+
+/// The drawn dashboard, the messages are held until it's closed
+pub struct Screen {
+    stop: Arc<AtomicBool>,
+    drawer: JoinHandle<io::Result<()>>,
+}
+
+/// Draw the dashboard on the terminal until [`Screen::close`], `q` or Ctrl-C interrupts the job
+pub fn show(dashboard: Arc<Dashboard>, file: &str, progress: ByteCounter) -> Result<Screen, String> {
+    if !io::stdout().is_terminal() {
+        return Err("The dashboard is drawn on a terminal, stdout isn't one".to_owned());
+    }
+    let terminal = ratatui::try_init().map_err(|e| format!("Fail to draw the dashboard: {e}"))?;
+    tee::hold_messages();
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let drawer = {
+        let (stop, file) = (stop.clone(), file.to_owned());
+        thread::spawn(move || draw(terminal, &dashboard, &file, &progress, &stop))
+    };
+
+    Ok(Screen { stop, drawer })
+}
+
+impl Screen {
+    /// Restore the terminal and print the held messages
+    pub fn close(self) -> Result<(), String> {
+        self.stop.store(true, Ordering::Relaxed);
+        let drawn = self.drawer.join().unwrap_or_else(|e| std::panic::resume_unwind(e));
+        let restored = ratatui::try_restore();
+        tee::release_messages();
+
+        drawn
+            .and(restored)
+            .map_err(|e| format!("Fail to draw the dashboard: {e}"))
+    }
+}
+
+fn draw(
+    mut terminal: DefaultTerminal,
+    dashboard: &Dashboard,
+    file: &str,
+    progress: &ByteCounter,
+    stop: &AtomicBool,
+) -> io::Result<()> {
+    let start = Instant::now();
+    let mut window = Window::default();
+
+    while !stop.load(Ordering::Relaxed) {
+        let (summary, rejected) = {
+            let state = dashboard.state();
+            (state.summary.clone(), state.rejected.clone())
+        };
+        window.push(Sample {
+            at: Instant::now(),
+            lines: summary.parsed_lines,
+            bytes: progress.get(),
+            stats: summary.stats.clone(),
+        });
+        terminal.draw(|frame| render(frame, file, start.elapsed(), &summary, &window, &rejected))?;
+
+        if event::poll(FRAME_DELAY)? {
+            if let Event::Key(key) = event::read()? {
+                let ctrl_c = key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+                if key.kind == KeyEventKind::Press && (key.code == KeyCode::Char('q') || ctrl_c) {
+                    // the raw terminal doesn't send `SIGINT`, the job stops the same way
+                    signals::interrupt_flag().store(true, Ordering::Relaxed);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn render(
+    frame: &mut Frame,
+    file: &str,
+    elapsed: Duration,
+    summary: &Summary,
+    window: &Window,
+    rejected: &VecDeque<(usize, String)>,
+) {
+    let [totals, histogram, lines] = Layout::vertical([
+        Constraint::Length(5),
+        Constraint::Min(8),
+        Constraint::Length(REJECTED_LINES + 2),
+    ])
+    .areas(frame.area());
+
+    let (lines_per_second, bytes_per_second) = window.throughput();
+    let error_rate = if summary.parsed_lines == 0 {
+        0.0
+    } else {
+        summary.incorrect_lines as f64 / summary.parsed_lines as f64 * 100.0
+    };
+    let text = vec![
+        Line::from(format!("Throughput: {lines_per_second:.0} lines/s, {:.2} MB/s", bytes_per_second / 1e6)),
+        Line::from(format!("Parsed lines: {}, Total amount: {}", summary.parsed_lines, summary.total_sum)),
+        Line::from(format!("Incorrect lines: {} ({error_rate:.2}%)", summary.incorrect_lines)),
+    ];
+    let title = format!(" {file}, elapsed {}s, q to stop ", elapsed.as_secs());
+    frame.render_widget(Paragraph::new(text).block(Block::bordered().title(title)), totals);
+
+    let bars: Vec<Bar> = window
+        .histogram()
+        .into_iter()
+        .map(|(start, count)| {
+            Bar::default()
+                .value(count)
+                .label(Line::from(format!("{start}-{}", start + BUCKET_WIDTH - 1)))
+        })
+        .collect();
+    let chart = BarChart::default()
+        .block(Block::bordered().title(format!(" Values of the last {}s ", WINDOW.as_secs())))
+        .data(BarGroup::default().bars(&bars))
+        .bar_width(5)
+        .bar_gap(1)
+        .bar_style(Style::default().fg(Color::Cyan));
+    frame.render_widget(chart, histogram);
+
+    let items = rejected.iter().rev().map(|(number, line)| format!("{number}: {line}"));
+    frame.render_widget(List::new(items).block(Block::bordered().title(" Latest rejected lines ")), lines);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_window() {
+        let start = Instant::now();
+        let mut window = Window::default();
+        assert_eq!(window.throughput(), (0.0, 0.0));
+
+        let mut stats = LineStats::default();
+        for second in 0..=20 {
+            if second > 5 {
+                stats.add(second);
+            }
+            window.push(Sample {
+                at: start + Duration::from_secs(u64::from(second)),
+                lines: u64::from(second) * 100,
+                bytes: u64::from(second) * 1000,
+                stats: stats.clone(),
+            });
+        }
+
+        assert_eq!(window.throughput(), (100.0, 1000.0));
+        let histogram = window.histogram();
+        assert_eq!(histogram.len(), 10);
+        assert_eq!(&histogram[..3], [(0, 0), (10, 9), (20, 1)]);
+
+        let dashboard = Dashboard::default();
+        for number in 0..15 {
+            dashboard.reject(number, &"x".repeat(300));
+        }
+        let rejected = &dashboard.state().rejected;
+        assert_eq!(rejected.len(), usize::from(REJECTED_LINES));
+        assert_eq!(rejected.front().map(|(number, line)| (*number, line.len())), Some((5, LINE_WIDTH)));
+    }
+}
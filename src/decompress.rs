@@ -0,0 +1,201 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, Read},
+    path::Path,
+};
+
+use flate2::bufread::MultiGzDecoder;
+
+/// Compression format of an input file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    /// `.gz`, the concatenated members are read one after another like `zcat` does
+    Gzip,
+    /// `.zst`, with the `zstd` feature
+    Zstd,
+    /// `.bz2`, with the `bzip2` feature
+    Bzip2,
+    /// `.xz`, with the `xz` feature
+    Xz,
+}
+
+/// The magic bytes and the file extensions of the formats
+const FORMATS: [(Compression, &[u8], &str); 4] = [
+    (Compression::Gzip, &[0x1f, 0x8b], "gz"),
+    (Compression::Zstd, &[0x28, 0xb5, 0x2f, 0xfd], "zst"),
+    (Compression::Bzip2, b"BZh", "bz2"),
+    (Compression::Xz, &[0xfd, b'7', b'z', b'X', b'Z', 0], "xz"),
+];
+
+impl Compression {
+    /// The format by the magic bytes at the beginning of the data or by the file extension
+    pub fn detect(file: &str, magic: &[u8]) -> Self {
+        let extension = Path::new(file).extension().and_then(|extension| extension.to_str());
+
+        FORMATS
+            .iter()
+            .find(|(_, prefix, _)| magic.starts_with(prefix))
+            .or_else(|| FORMATS.iter().find(|(.., known)| extension == Some(known)))
+            .map_or(Self::None, |&(compression, ..)| compression)
+    }
+
+    /// The format of the file by its first bytes
+    pub fn of_file(file: &str) -> io::Result<Self> {
+        let mut magic = Vec::with_capacity(8);
+        File::open(file)?.take(8).read_to_end(&mut magic)?;
+
+        Ok(Self::detect(file, &magic))
+    }
+
+    /// The decoded data of the input
+    ///
+    /// # Errors
+    ///
+    /// the format isn't supported by this build or the decoder can't be started
+    pub fn decoder<'a>(self, input: impl BufRead + Send + 'a) -> io::Result<Box<dyn Read + Send + 'a>> {
+        let decoder: Box<dyn Read + Send + 'a> = match self {
+            Self::None => Box::new(input),
+            Self::Gzip => Box::new(MultiGzDecoder::new(input)),
+            #[cfg(feature = "zstd")]
+            Self::Zstd => Box::new(zstd::stream::read::Decoder::with_buffer(input)?),
+            #[cfg(not(feature = "zstd"))]
+            Self::Zstd => return Err(unsupported(self, "zstd")),
+            #[cfg(feature = "bzip2")]
+            Self::Bzip2 => Box::new(bzip2::bufread::MultiBzDecoder::new(input)),
+            #[cfg(not(feature = "bzip2"))]
+            Self::Bzip2 => return Err(unsupported(self, "bzip2")),
+            #[cfg(feature = "xz")]
+            Self::Xz => Box::new(xz2::bufread::XzDecoder::new_multi_decoder(input)),
+            #[cfg(not(feature = "xz"))]
+            Self::Xz => return Err(unsupported(self, "xz")),
+        };
+
+        Ok(decoder)
+    }
+}
+
+#[cfg(not(all(feature = "zstd", feature = "bzip2", feature = "xz")))]
+fn unsupported(compression: Compression, feature: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!("{compression:?} input isn't supported by this build, rebuild it with `--features {feature}`"),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use std::{fs, io::Write};
+
+    use clap::Parser;
+    use find_digits::{counting::ByteCounter, Summary};
+    use flate2::{write::GzEncoder, Compression as Level};
+
+    use super::*;
+    use crate::testing::temp_dir;
+    use crate::{
+        process::{sum_file, Processing},
+        Args,
+    };
+
+    #[test]
+    fn test_detect() {
+        let files = ["in.txt", "in.txt.gz", "in.txt", "in"];
+        let magics: [&[u8]; 4] = [b"two1nine", b"", &[0x1f, 0x8b, 8, 0], &[0x1f]];
+        let expected = [
+            Compression::None,
+            Compression::Gzip,
+            Compression::Gzip,
+            Compression::None,
+        ];
+
+        for (pos, file) in files.into_iter().enumerate() {
+            assert_eq!(Compression::detect(file, magics[pos]), expected[pos]);
+        }
+    }
+
+    #[test]
+    fn test_sum_gzip() {
+        let text = "two1nine\nabcdefg\n7pqrstsixteen\n".repeat(1000);
+        let directory = temp_dir();
+        let plain = directory.path().join("plain.txt");
+        // no extension, the format is detected by the magic bytes
+        let compressed = directory.path().join("compressed");
+
+        // two members like `cat a.gz b.gz` gives
+        let (first, second) = text.split_at(text.len() / 2);
+        let mut data = Vec::new();
+        for member in [first, second] {
+            let mut encoder = GzEncoder::new(Vec::new(), Level::default());
+            encoder.write_all(member.as_bytes()).unwrap();
+            data.extend(encoder.finish().unwrap());
+        }
+        fs::write(&plain, &text).unwrap();
+        fs::write(&compressed, &data).unwrap();
+
+        let sum = |path: &Path| {
+            let file = path.to_str().unwrap();
+            let processing = Processing::new(&Args::parse_from(["find_digits", "--file", file])).unwrap();
+            sum_file(file, &processing, &ByteCounter::default())
+        };
+
+        let expected = sum(&plain);
+        let summary = sum(&compressed);
+        assert_eq!(summary.input_bytes.logical, text.len() as u64);
+        assert_eq!(summary.input_bytes.physical, data.len() as u64);
+        assert_eq!(
+            Summary {
+                input_bytes: expected.input_bytes,
+                ..summary
+            },
+            expected
+        );
+
+        // a truncated file stops the reading instead of breaking every following line
+        fs::write(&compressed, &data[..data.len() / 3]).unwrap();
+        assert!(sum(&compressed).parsed_lines < expected.parsed_lines);
+    }
+
+    #[test]
+    fn test_decoders() {
+        let text = "two1nine\nabcdefg\n7pqrstsixteen\n".repeat(100);
+        let mut gzip = GzEncoder::new(Vec::new(), Level::default());
+        gzip.write_all(text.as_bytes()).unwrap();
+
+        // more formats with the features
+        #[allow(unused_mut)]
+        let mut data = vec![
+            (Compression::None, text.clone().into_bytes()),
+            (Compression::Gzip, gzip.finish().unwrap()),
+        ];
+        #[cfg(feature = "zstd")]
+        data.push((Compression::Zstd, zstd::encode_all(text.as_bytes(), 0).unwrap()));
+        #[cfg(feature = "bzip2")]
+        {
+            let mut encoder = bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+            encoder.write_all(text.as_bytes()).unwrap();
+            data.push((Compression::Bzip2, encoder.finish().unwrap()));
+        }
+        #[cfg(feature = "xz")]
+        {
+            let mut encoder = xz2::write::XzEncoder::new(Vec::new(), 6);
+            encoder.write_all(text.as_bytes()).unwrap();
+            data.push((Compression::Xz, encoder.finish().unwrap()));
+        }
+
+        for (compression, data) in data {
+            assert_eq!(Compression::detect("in", &data), compression);
+
+            let mut decoded = String::new();
+            compression
+                .decoder(&data[..])
+                .unwrap()
+                .read_to_string(&mut decoded)
+                .unwrap();
+            assert_eq!(decoded, text);
+        }
+
+        #[cfg(not(feature = "zstd"))]
+        assert!(Compression::Zstd.decoder(&b""[..]).is_err());
+    }
+}
@@ -0,0 +1,115 @@
+use std::{collections::HashMap, fmt};
+
+use find_digits::ScanOptions;
+
+/// Shorter words match inside ordinary words too often, e.g. `un` of `under`
+const LETTERS_DIGIT_MIN_LEN: usize = 3;
+
+/// A broken invariant of the matcher or a likely mistake of a dictionary
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Issue {
+    /// the lines are extracted differently than the dictionary says
+    Error(String),
+    /// the lines are extracted as the dictionary says, but probably not as meant
+    Warning(String),
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Error(message) => write!(f, "Error: {message}"),
+            Self::Warning(message) => write!(f, "Warning: {message}"),
+        }
+    }
+}
+
+/// Print the issues of the dictionary of the options, an error fails the check
+pub fn run(options: &ScanOptions, name: &str) -> Result<(), String> {
+    let words = options.dictionary().words();
+    let issues = check(words);
+    for issue in &issues {
+        println!("{issue}");
+    }
+
+    let errors = issues.iter().filter(|issue| matches!(issue, Issue::Error(_))).count();
+    println!("Dictionary {name}: {} words, {errors} errors, {} warnings", words.len(), issues.len() - errors);
+
+    if errors > 0 {
+        return Err(format!("Dictionary {name} is incorrect"));
+    }
+    Ok(())
+}
+
+/// The tries match the longest word at a position, the words may share beginnings and endings,
+/// these are the cases they can't resolve as the dictionary says
+fn check(words: &[(String, char)]) -> Vec<Issue> {
+    let mut issues = Vec::new();
+
+    let mut digits: HashMap<&str, char> = HashMap::new();
+    for (word, digit) in words {
+        match digits.insert(word, *digit) {
+            Some(previous) if previous != *digit => issues.push(Issue::Error(format!(
+                "{word:?} is spelled for both {previous} and {digit}, the last one is matched"
+            ))),
+            Some(_) => issues.push(Issue::Warning(format!("{word:?} is repeated"))),
+            None => {},
+        }
+    }
+
+    for (word, digit) in words {
+        // the digit is matched before the word at its position
+        for (end, c) in [("begins", word.chars().next()), ("ends", word.chars().next_back())] {
+            if let Some(c) = c.filter(char::is_ascii_digit) {
+                issues.push(Issue::Error(format!("{word:?} {end} with the digit {c}, the digit is matched instead")));
+            }
+        }
+
+        if word.chars().count() < LETTERS_DIGIT_MIN_LEN {
+            issues.push(Issue::Warning(format!(
+                "{word:?} is shorter than {LETTERS_DIGIT_MIN_LEN} letters, it's matched inside other words"
+            )));
+        }
+
+        // the reverse scan takes the word ending last, the inner word beginning later is skipped
+        for (inner, inner_digit) in words {
+            if inner != word && inner_digit != digit && word.match_indices(inner.as_str()).any(|(start, _)| start > 0) {
+                issues.push(Issue::Error(format!(
+                    "{inner:?} ({inner_digit}) is inside {word:?} ({digit}), the last digit of a line ending with {word:?} \
+                     is {digit}, not {inner_digit}"
+                )));
+            }
+        }
+    }
+
+    issues.dedup();
+    issues
+}
+
+#[cfg(test)]
+mod test {
+    use find_digits::Dictionary;
+
+    use super::*;
+
+    #[test]
+    fn test_check() {
+        assert_eq!(check(Dictionary::english().words()), []);
+        assert_eq!(check(Dictionary::parse("ein 1\neins 1\nzwei 2\ndrei 3\n").unwrap().words()), []);
+
+        let dictionary = Dictionary::parse("uno 1\nuno 1\ndue 2\ndue 3\n4x 4\nun 1\nsun 5\n").unwrap();
+        assert_eq!(
+            check(dictionary.words()),
+            [
+                Issue::Warning("\"uno\" is repeated".to_owned()),
+                Issue::Error("\"due\" is spelled for both 2 and 3, the last one is matched".to_owned()),
+                Issue::Error("\"4x\" begins with the digit 4, the digit is matched instead".to_owned()),
+                Issue::Warning("\"4x\" is shorter than 3 letters, it's matched inside other words".to_owned()),
+                Issue::Warning("\"un\" is shorter than 3 letters, it's matched inside other words".to_owned()),
+                Issue::Error(
+                    "\"un\" (1) is inside \"sun\" (5), the last digit of a line ending with \"sun\" is 5, not 1"
+                        .to_owned()
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,232 @@
+use std::sync::OnceLock;
+
+use crate::{bare, byteset::ByteSet};
+
+/// Spelled digits recognized by the scanners
+///
+/// The words are kept in two tries, of the words and of the reversed words, so the forward scan finds
+/// the words beginning at a position and the reverse scan the words ending at a position.
+/// Any word may be a prefix or a suffix of another one, the longest is matched.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dictionary {
+    words: Vec<(String, char)>,
+    forward: Trie,
+    backward: Trie,
+    /// the first bytes of the words and of the digits: ASCII ones, any non-ASCII byte too
+    starts: [ByteSet; 2],
+    /// the last bytes of the words and of the digits
+    ends: [ByteSet; 2],
+}
+
+impl Dictionary {
+    /// `one`, `two`, ... `nine`
+    #[must_use]
+    pub fn english() -> &'static Self {
+        static DICTIONARY: OnceLock<Dictionary> = OnceLock::new();
+
+        DICTIONARY.get_or_init(|| {
+            // the words of the `no_std` scanner, both scanners match the same ones
+            let words = bare::ENGLISH
+                .iter()
+                .map(|&(word, value)| (String::from_utf8_lossy(word).into_owned(), char::from(b'0' + value)));
+            Self::new(words).unwrap_or_else(|e| {
+                unreachable!("the built-in dictionary is correct: {e}");
+            })
+        })
+    }
+
+    /// # Errors
+    ///
+    /// a word is empty or a digit is not ASCII `0-9`
+    pub fn new(words: impl IntoIterator<Item = (String, char)>) -> Result<Self, String> {
+        let mut dictionary = Self {
+            words: Vec::new(),
+            forward: Trie::default(),
+            backward: Trie::default(),
+            starts: [ByteSet::default(); 2],
+            ends: [ByteSet::default(); 2],
+        };
+        let (mut starts, mut ends) = (ByteSet::default(), ByteSet::default());
+
+        for (word, digit) in words {
+            if word.is_empty() {
+                return Err(format!("empty word for the digit {digit}"));
+            }
+
+            if !digit.is_ascii_digit() {
+                return Err(format!("{digit:?} of the word {word:?} is not a digit"));
+            }
+
+            starts.insert(word.as_bytes()[0]);
+            ends.insert(word.as_bytes()[word.len() - 1]);
+            dictionary.forward.insert(word.bytes(), digit);
+            dictionary.backward.insert(word.bytes().rev(), digit);
+            dictionary.words.push((word, digit));
+        }
+
+        // the classes are built once, the scanners search them in every line
+        let ascii_digits = ByteSet::range(b'0', b'9');
+        let digits = [ascii_digits, ascii_digits.union(ByteSet::range(0x80, 0xFF))];
+        dictionary.starts = digits.map(|digits| digits.union(starts));
+        dictionary.ends = digits.map(|digits| digits.union(ends));
+
+        Ok(dictionary)
+    }
+
+    /// Dictionary with one `<word> <digit>` pair per line, `#` starts a comment
+    ///
+    /// # Errors
+    ///
+    /// a line is not a pair of a word and a digit
+    pub fn parse(table: &str) -> Result<Self, String> {
+        let mut words = Vec::new();
+
+        for (number, line) in table.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut fields = line.split_whitespace();
+            let word = fields.next();
+            let digit = fields.next().and_then(|digit| {
+                let mut chars = digit.chars();
+                chars.next().filter(|_| chars.next().is_none())
+            });
+
+            match (word, digit, fields.next()) {
+                (Some(word), Some(digit), None) => words.push((word.to_owned(), digit)),
+                _ => return Err(format!("incorrect dictionary entry (line number {number}): {line:?}")),
+            }
+        }
+
+        Self::new(words)
+    }
+
+    /// The words with their digits in the order of the definition
+    #[must_use]
+    pub fn words(&self) -> &[(String, char)] {
+        &self.words
+    }
+
+    /// The bytes a word or a digit begins with, any non-ASCII byte too when the digits aren't only ASCII ones
+    pub(crate) fn starts(&self, non_ascii_digits: bool) -> &ByteSet {
+        &self.starts[usize::from(non_ascii_digits)]
+    }
+
+    /// The bytes a word or a digit ends with
+    pub(crate) fn ends(&self, non_ascii_digits: bool) -> &ByteSet {
+        &self.ends[usize::from(non_ascii_digits)]
+    }
+
+    /// The longest word beginning at `start`: its digit and length in bytes, none beyond the end of the line
+    #[must_use]
+    pub fn word_at(&self, line: &str, start: usize) -> Option<(char, usize)> {
+        self.forward.longest(line.as_bytes().get(start..)?.iter().copied())
+    }
+
+    /// The longest word ending at `end`: its digit and length in bytes, none beyond the end of the line
+    #[must_use]
+    pub fn word_before(&self, line: &str, end: usize) -> Option<(char, usize)> {
+        self.backward.longest(line.as_bytes().get(..end)?.iter().rev().copied())
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Trie {
+    nodes: Vec<Node>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Node {
+    children: Vec<(u8, usize)>,
+    digit: Option<char>,
+}
+
+impl Trie {
+    fn insert(&mut self, bytes: impl Iterator<Item = u8>, digit: char) {
+        if self.nodes.is_empty() {
+            self.nodes.push(Node::default());
+        }
+
+        let mut current = 0;
+        for byte in bytes {
+            current = if let Some(next) = self.child(current, byte) {
+                next
+            } else {
+                self.nodes.push(Node::default());
+                let next = self.nodes.len() - 1;
+                self.nodes[current].children.push((byte, next));
+                next
+            };
+        }
+
+        self.nodes[current].digit = Some(digit);
+    }
+
+    fn child(&self, node: usize, byte: u8) -> Option<usize> {
+        self.nodes[node]
+            .children
+            .iter()
+            .find_map(|&(label, next)| (label == byte).then_some(next))
+    }
+
+    fn longest(&self, bytes: impl Iterator<Item = u8>) -> Option<(char, usize)> {
+        if self.nodes.is_empty() {
+            return None;
+        }
+
+        let mut found = None;
+        let mut current = 0;
+
+        for (length, byte) in (1..).zip(bytes) {
+            let Some(next) = self.child(current, byte) else {
+                break;
+            };
+
+            current = next;
+            if let Some(digit) = self.nodes[current].digit {
+                found = Some((digit, length));
+            }
+        }
+
+        found
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_english() {
+        let dictionary = Dictionary::english();
+
+        assert_eq!(dictionary.word_at("eightwo", 0), Some(('8', 5)));
+        assert_eq!(dictionary.word_at("eightwo", 4), Some(('2', 3)));
+        assert_eq!(dictionary.word_at("eigh", 0), None);
+        assert_eq!(dictionary.word_before("eightwo", 7), Some(('2', 3)));
+        assert_eq!(dictionary.word_before("eightwo", 5), Some(('8', 5)));
+        assert_eq!(dictionary.word_before("eightwo", 6), None);
+    }
+
+    #[test]
+    fn test_shared_prefixes() {
+        let dictionary = Dictionary::parse("# German\nein 1\neins 1\nzwei 2\nzwo 2\nsechs 6\nsieben 7\n").unwrap();
+
+        assert_eq!(dictionary.word_at("einsx", 0), Some(('1', 4)));
+        assert_eq!(dictionary.word_at("einx", 0), Some(('1', 3)));
+        assert_eq!(dictionary.word_at("siebenzwo", 0), Some(('7', 6)));
+        assert_eq!(dictionary.word_before("siebenzwo", 9), Some(('2', 3)));
+        assert_eq!(dictionary.word_before("xsechs", 6), Some(('6', 5)));
+        assert_eq!(dictionary.words().len(), 6);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Dictionary::parse("one").is_err());
+        assert!(Dictionary::parse("one 10").is_err());
+        assert!(Dictionary::parse("one x").is_err());
+        assert!(Dictionary::parse("one 1 2").is_err());
+    }
+}
@@ -0,0 +1,81 @@
+use std::fmt::Write as _;
+
+use find_digits::{matches_with, LineError, LineValue, Match, MatchKind, ScanOptions};
+
+/// The line with its tokens, their byte spans and the resulting value
+///
+/// `line` is the line after the confusables replacement, it's shown when it differs from the original one.
+pub fn render(
+    number: usize,
+    original: &str,
+    line: &str,
+    result: &Result<LineValue, LineError>,
+    options: &ScanOptions,
+) -> String {
+    let mut explanation = format!("line number {number} {original:?}");
+    if line != original {
+        let _ = write!(explanation, " (normalized {line:?})");
+    }
+
+    let value = match result {
+        Ok(value) => value,
+        Err(LineError::Empty) => return explanation + ": empty\n",
+        Err(LineError::NoDigits) => return explanation + ": no digits\n",
+    };
+
+    let _ = writeln!(explanation, ": value {}", value.value);
+
+    for m in matches_with(line, options) {
+        let role = match (m == value.first, m == value.last) {
+            (true, true) => " (first, last)",
+            (true, false) => " (first)",
+            (false, true) => " (last)",
+            (false, false) => "",
+        };
+
+        let _ = writeln!(explanation, "  {}{role}", token(line, &m));
+    }
+
+    // the last match found by the reverse scan isn't necessarily among the forward matches, e.g. of `--fuzzy`
+    if !matches_with(line, options).any(|m: Match| m == value.last) {
+        let _ = writeln!(explanation, "  {} (last)", token(line, &value.last));
+    }
+
+    explanation
+}
+
+fn token(line: &str, m: &Match) -> String {
+    let kind = match m.kind {
+        MatchKind::Digit => "digit",
+        MatchKind::Word => "word",
+    };
+
+    format!("{kind} {:?} {}..{} = {}", &line[m.start..m.end], m.start, m.end, m.digit)
+}
+
+#[cfg(test)]
+mod test {
+    use find_digits::extract_line;
+
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let options = ScanOptions::default();
+        let strings = ["eightwothree", "treb7uchet", "abcdefg", ""];
+        let expected = [
+            "line number 0 \"eightwothree\": value 83\n  word \"eight\" 0..5 = 8 (first)\n  word \"two\" 4..7 = 2\n  \
+             word \"three\" 7..12 = 3 (last)\n",
+            "line number 1 \"treb7uchet\": value 77\n  digit \"7\" 4..5 = 7 (first, last)\n",
+            "line number 2 \"abcdefg\": no digits\n",
+            "line number 3 \"\": empty\n",
+        ];
+
+        for (pos, line) in strings.into_iter().enumerate() {
+            assert_eq!(render(pos, line, line, &extract_line(line), &options), expected[pos]);
+        }
+
+        assert!(render(0, "lO", "10", &extract_line("10"), &options)
+            .starts_with("line number 0 \"lO\" (normalized \"10\")"));
+    }
+}
@@ -0,0 +1,58 @@
+use std::fmt;
+
+use crate::{extract_line_with, DigitFinder, LineError, LineValue, ScanOptions};
+
+/// An engine finding the value of a line and the first and the last matches it's made of
+///
+/// The scanners of [`ScanOptions`] and [`DigitFinder`] are the built-in engines, the other ones
+/// (e.g. regular expressions or Aho-Corasick automata) are used through the same interface.
+pub trait Extractor: fmt::Debug + Send + Sync {
+    /// # Errors
+    ///
+    /// the line is empty or has no digits
+    fn extract(&self, line: &str) -> Result<LineValue, LineError>;
+}
+
+impl Extractor for ScanOptions {
+    fn extract(&self, line: &str) -> Result<LineValue, LineError> {
+        extract_line_with(line, self)
+    }
+}
+
+impl Extractor for DigitFinder {
+    fn extract(&self, line: &str) -> Result<LineValue, LineError> {
+        self.extract_line(line)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Combine;
+
+    #[test]
+    fn test_extractors() {
+        let extractors: [Box<dyn Extractor>; 3] = [
+            Box::new(ScanOptions::default()),
+            Box::new(DigitFinder::default()),
+            Box::new(
+                DigitFinder::builder()
+                    .case_sensitive(false)
+                    .combine(Combine::LastFirst)
+                    .build(),
+            ),
+        ];
+        let strings = ["two1NINE", "treb7uchet", "abcdefg", ""];
+        let expected = [
+            [Ok(21), Ok(77), Err(LineError::NoDigits), Err(LineError::Empty)],
+            [Ok(21), Ok(77), Err(LineError::NoDigits), Err(LineError::Empty)],
+            [Ok(92), Ok(77), Err(LineError::NoDigits), Err(LineError::Empty)],
+        ];
+
+        for (extractor, expected) in extractors.iter().zip(expected) {
+            for (pos, line) in strings.into_iter().enumerate() {
+                assert_eq!(extractor.extract(line).map(|value| value.value), expected[pos], "{extractor:?} {line}");
+            }
+        }
+    }
+}
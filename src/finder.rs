@@ -0,0 +1,261 @@
+use std::{borrow::Cow, sync::Arc};
+
+use crate::{
+    scanner::{backward_match, consumed, forward_match, match_at},
+    Dictionary, LineError, LineValue, Match, Overlap, ScanOptions,
+};
+
+/// How the value of a line is made of its first and last digits
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Combine {
+    /// `first * 10 + last`, the calibration value
+    #[default]
+    FirstLast,
+    /// `last * 10 + first`
+    LastFirst,
+    /// `first + last`
+    Sum,
+}
+
+impl Combine {
+    #[must_use]
+    pub fn apply(self, first: &Match, last: &Match) -> u32 {
+        match self {
+            Self::FirstLast => Match::combine(first, last),
+            Self::LastFirst => Match::combine(last, first),
+            Self::Sum => u32::from(first.value()) + u32::from(last.value()),
+        }
+    }
+}
+
+/// Scanner configured once and used for many lines
+///
+/// ```
+/// use find_digits::{Combine, DigitFinder};
+///
+/// let finder = DigitFinder::builder().case_sensitive(false).combine(Combine::Sum).build();
+/// assert_eq!(finder.value_of("TWO1Nine"), Some(11));
+/// assert_eq!(finder.scan("eightwo").map(|m| m.digit).collect::<String>(), "82");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DigitFinder {
+    options: ScanOptions,
+    /// the ASCII letters of the lines are matched in any case
+    fold_case: bool,
+    combine: Combine,
+}
+
+impl DigitFinder {
+    pub fn builder() -> DigitFinderBuilder {
+        DigitFinderBuilder::default()
+    }
+
+    /// The options the lines are scanned with, the words are lowercase when the case is ignored
+    #[must_use]
+    pub fn options(&self) -> &ScanOptions {
+        &self.options
+    }
+
+    /// The value of the first and the last digits of the line, `None` without digits
+    #[must_use]
+    pub fn value_of(&self, line: &str) -> Option<u32> {
+        self.extract_line(line).ok().map(|value| value.value)
+    }
+
+    /// The value of the line with the first and the last matches
+    ///
+    /// # Errors
+    ///
+    /// the line is empty or has no digits
+    pub fn extract_line(&self, line: &str) -> Result<LineValue, LineError> {
+        if line.is_empty() {
+            return Err(LineError::Empty);
+        }
+
+        let line = self.fold(line);
+        let first = forward_match(&line, &self.options).ok_or(LineError::NoDigits)?;
+        let last = backward_match(&line, first.start, &self.options).ok_or(LineError::NoDigits)?;
+
+        Ok(LineValue {
+            value: self.combine.apply(&first, &last),
+            first,
+            last,
+        })
+    }
+
+    /// Every digit of the line in the order of their beginnings, the spans are of the given line
+    pub fn scan<'a>(&'a self, line: &'a str) -> impl Iterator<Item = Match> + 'a {
+        // the lowercase ASCII letters have the same lengths, the positions are the same in both lines
+        let folded = self.fold(line);
+        line.char_indices()
+            .filter_map(move |(pos, c)| match_at(&folded, pos, c, &self.options))
+            .filter(consumed(self.options.overlap))
+    }
+
+    fn fold<'a>(&self, line: &'a str) -> Cow<'a, str> {
+        if self.fold_case && line.bytes().any(|b| b.is_ascii_uppercase()) {
+            Cow::Owned(line.to_ascii_lowercase())
+        } else {
+            Cow::Borrowed(line)
+        }
+    }
+}
+
+/// Configuration of a [`DigitFinder`], the defaults are the ones of [`ScanOptions::default`]
+#[derive(Debug, Clone)]
+#[must_use]
+pub struct DigitFinderBuilder {
+    options: ScanOptions,
+    case_sensitive: bool,
+    combine: Combine,
+}
+
+impl Default for DigitFinderBuilder {
+    fn default() -> Self {
+        Self {
+            options: ScanOptions::default(),
+            case_sensitive: true,
+            combine: Combine::default(),
+        }
+    }
+}
+
+impl DigitFinderBuilder {
+    /// Spelled digits, [`Dictionary::english`] by default
+    pub fn dictionary(mut self, dictionary: Dictionary) -> Self {
+        self.options.dictionary = Some(Arc::new(dictionary));
+        self
+    }
+
+    /// `false` matches the ASCII letters of the words in any case, e.g. `Seven` and `SEVEN`
+    pub fn case_sensitive(mut self, case_sensitive: bool) -> Self {
+        self.case_sensitive = case_sensitive;
+        self
+    }
+
+    /// Any Unicode decimal digit counts, see [`ScanOptions::unicode_digits`]
+    pub fn unicode_digits(mut self, enabled: bool) -> Self {
+        self.options.unicode_digits = enabled;
+        self
+    }
+
+    /// The fullwidth forms `０-９` count, see [`ScanOptions::fullwidth_digits`]
+    pub fn fullwidth_digits(mut self, enabled: bool) -> Self {
+        self.options.fullwidth_digits = enabled;
+        self
+    }
+
+    /// Keycap emoji are matched as a whole, see [`ScanOptions::keycap_digits`]
+    pub fn keycap_digits(mut self, enabled: bool) -> Self {
+        self.options.keycap_digits = enabled;
+        self
+    }
+
+    /// Digits adjacent to other digits are skipped, see [`ScanOptions::isolated_digits_only`]
+    pub fn isolated_digits_only(mut self, enabled: bool) -> Self {
+        self.options.isolated_digits_only = enabled;
+        self
+    }
+
+    /// Typos allowed in the spelled digits, see [`ScanOptions::fuzzy`]
+    pub fn fuzzy(mut self, edits: usize) -> Self {
+        self.options.fuzzy = edits;
+        self
+    }
+
+    /// Spelled digits are matched between non-letters only, see [`ScanOptions::word_boundaries`]
+    pub fn word_boundaries(mut self, enabled: bool) -> Self {
+        self.options.word_boundaries = enabled;
+        self
+    }
+
+    /// Whether the spelled digits may share letters, see [`ScanOptions::overlap`]
+    pub fn overlap(mut self, overlap: Overlap) -> Self {
+        self.options.overlap = overlap;
+        self
+    }
+
+    /// How the first and the last digits make the value, [`Combine::FirstLast`] by default
+    pub fn combine(mut self, combine: Combine) -> Self {
+        self.combine = combine;
+        self
+    }
+
+    #[must_use]
+    pub fn build(self) -> DigitFinder {
+        let mut options = self.options;
+        if !self.case_sensitive {
+            let words = options
+                .dictionary()
+                .words()
+                .iter()
+                .map(|(word, digit)| (word.to_ascii_lowercase(), *digit));
+            let dictionary = Dictionary::new(words).unwrap_or_else(|e| {
+                unreachable!("the lowercase words of a dictionary are correct: {e}");
+            });
+            options.dictionary = Some(Arc::new(dictionary));
+        }
+
+        DigitFinder {
+            options,
+            fold_case: !self.case_sensitive,
+            combine: self.combine,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::extract_number;
+
+    #[test]
+    fn test_value_of() {
+        let finders = [
+            DigitFinder::builder().build(),
+            DigitFinder::builder().case_sensitive(false).build(),
+            DigitFinder::builder().combine(Combine::LastFirst).build(),
+            DigitFinder::builder()
+                .combine(Combine::Sum)
+                .fullwidth_digits(true)
+                .build(),
+            DigitFinder::builder()
+                .dictionary(Dictionary::parse("Eins 1\nzwei 2\n").unwrap())
+                .case_sensitive(false)
+                .build(),
+        ];
+        let strings = ["two1nine", "xTwone3FOUR", "treb7uchet", "a１bEINS", ""];
+        let expected = [
+            [Some(29), Some(13), Some(77), None, None],
+            [Some(29), Some(24), Some(77), None, None],
+            [Some(92), Some(31), Some(77), None, None],
+            [Some(11), Some(4), Some(14), Some(2), None],
+            [Some(11), Some(33), Some(77), Some(11), None],
+        ];
+
+        for (finder, expected) in finders.iter().zip(expected) {
+            for (pos, line) in strings.into_iter().enumerate() {
+                assert_eq!(finder.value_of(line), expected[pos], "{finder:?} {line}");
+            }
+        }
+
+        let finder = DigitFinder::default();
+        for line in ["eightwothree", "abcdefg", "4nineeightseven2"] {
+            assert_eq!(finder.value_of(line), extract_number(line), "{line}");
+        }
+    }
+
+    #[test]
+    fn test_scan() {
+        let finder = DigitFinder::builder().case_sensitive(false).build();
+        let line = "xTWOne3é";
+        let matches: Vec<_> = finder.scan(line).map(|m| (m.digit, &line[m.start..m.end])).collect();
+        assert_eq!(matches, [('2', "TWO"), ('1', "One"), ('3', "3")]);
+
+        assert_eq!(DigitFinder::default().scan("xTWOne3").count(), 1);
+
+        let finder = DigitFinder::builder().overlap(Overlap::Consume).build();
+        assert_eq!(finder.scan("eightwone3").map(|m| m.digit).collect::<String>(), "813");
+        assert_eq!(finder.value_of("eightwo"), Some(88));
+    }
+}
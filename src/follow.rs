@@ -0,0 +1,144 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, Read, Seek, SeekFrom},
+    process::exit,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use find_digits::{counting::ByteCounter, ByteCount, Summary};
+
+use crate::process::{pipeline, Processing};
+use crate::tee::message;
+
+/// How often the end of the followed file is checked for appended data
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Process the lines of the file and then the lines appended to it, like `tail -f`, until the stop is requested
+///
+/// A truncated file (e.g. rotated by copying) is read from its beginning again, the line numbers go on.
+pub fn sum_follow(
+    file: &str,
+    processing: &Processing,
+    physical_bytes: &ByteCounter,
+    stop: &Arc<AtomicBool>,
+) -> Summary {
+    let f = match File::open(file) {
+        Ok(f) => f,
+        Err(e) => {
+            message!("Fail to open file {file}: {e:?}");
+            exit(1);
+        },
+    };
+
+    let input = FollowReader {
+        file,
+        inner: f,
+        position: 0,
+        counter: physical_bytes.clone(),
+        stop: Arc::clone(stop),
+    };
+    let mut reader = BufReader::with_capacity(processing.buffer_size, input);
+
+    let mut summary = pipeline(file, processing, move |mut collector| {
+        loop {
+            // the next read may wait for the appended lines, the collected ones are processed meanwhile
+            if reader.buffer().is_empty() && !collector.flush() {
+                break;
+            }
+
+            let buffer = collector.buffer();
+            let start = buffer.len();
+            let read_result = reader.read_line(buffer);
+
+            if !collector.push(start, read_result) {
+                break;
+            }
+        }
+
+        collector.finish();
+    });
+
+    let size = physical_bytes.get();
+    summary.input_bytes = ByteCount {
+        logical: size,
+        physical: size,
+    };
+
+    summary
+}
+
+/// Waits for the data appended to the file at its end, the end of the input is the requested stop only
+struct FollowReader<'a> {
+    file: &'a str,
+    inner: File,
+    /// of the next read byte
+    position: u64,
+    counter: ByteCounter,
+    stop: Arc<AtomicBool>,
+}
+
+impl Read for FollowReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            let read = self.inner.read(buf)?;
+            if read != 0 {
+                self.position += read as u64;
+                self.counter.add(read as u64);
+                return Ok(read);
+            }
+
+            if self.stop.load(Ordering::Relaxed) {
+                return Ok(0);
+            }
+
+            if self.inner.metadata()?.len() < self.position {
+                message!("File {} is truncated, it's read from its beginning", self.file);
+                self.position = self.inner.seek(SeekFrom::Start(0))?;
+                continue;
+            }
+
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{fs, io::Write};
+
+    use clap::Parser;
+
+    use super::*;
+    use crate::testing::temp_file;
+    use crate::Args;
+
+    #[test]
+    fn test_sum_follow() {
+        let path = temp_file(".txt", "two1nine\nabcdefg\n");
+        let file = path.to_str().unwrap();
+
+        let processing = Processing::new(&Args::parse_from(["find_digits", "--file", file])).unwrap();
+        let stop = Arc::new(AtomicBool::new(false));
+
+        thread::scope(|scope| {
+            let following = scope.spawn(|| sum_follow(file, &processing, &ByteCounter::default(), &stop));
+
+            thread::sleep(POLL_INTERVAL * 2);
+            let mut appended = fs::OpenOptions::new().append(true).open(&path).unwrap();
+            // the line is complete once its newline is appended
+            appended.write_all(b"7pqrst").unwrap();
+            thread::sleep(POLL_INTERVAL * 2);
+            appended.write_all(b"sixteen\n4nine\n").unwrap();
+            thread::sleep(POLL_INTERVAL * 2);
+
+            stop.store(true, Ordering::Relaxed);
+            let summary = following.join().unwrap();
+            assert_eq!((summary.parsed_lines, summary.incorrect_lines, summary.total_sum), (4, 1, 29 + 76 + 49));
+        });
+    }
+}
@@ -0,0 +1,127 @@
+//! Approximate matching of spelled digits for text with typos, e.g. produced by OCR
+
+/// Shortest token accepted as a misspelled digit
+const MIN_TOKEN_LEN: usize = 3;
+
+/// `(distance, difference from the word length, token length)`, the smallest wins
+type Rank = (usize, usize, usize);
+
+/// The spelled digit beginning at `start` within `max_edits` substitutions, insertions or deletions
+///
+/// Returns the digit and the length of the matched token. Ties are resolved deterministically:
+/// the smaller distance, then the length closer to the word, then the shorter token, then the table order.
+pub(crate) fn forward(line: &str, start: usize, max_edits: usize, words: &[(String, char)]) -> Option<(char, usize)> {
+    let rest = &line.as_bytes()[start..];
+
+    best(words, max_edits, rest.len(), |word, len| {
+        let token = &rest[..len];
+        let distance = accept(token, word, max_edits, &token[1..])?;
+
+        line.is_char_boundary(start + len).then_some(distance)
+    })
+}
+
+/// The spelled digit ending at `end`, the mirror of [`forward`]
+pub(crate) fn backward(line: &str, end: usize, max_edits: usize, words: &[(String, char)]) -> Option<(char, usize)> {
+    let before = &line.as_bytes()[..end];
+
+    best(words, max_edits, before.len(), |word, len| {
+        let token = &before[end - len..];
+        let distance = accept(token, word, max_edits, &token[..len - 1])?;
+
+        line.is_char_boundary(end - len).then_some(distance)
+    })
+}
+
+fn best(
+    words: &[(String, char)],
+    max_edits: usize,
+    available: usize,
+    distance_of: impl Fn(&[u8], usize) -> Option<usize>,
+) -> Option<(char, usize)> {
+    let mut best: Option<(Rank, char)> = None;
+
+    for (word, digit) in words {
+        let word = word.as_bytes();
+        let shortest = word.len().saturating_sub(max_edits).max(MIN_TOKEN_LEN);
+        let longest = (word.len() + max_edits).min(available);
+
+        for len in shortest..=longest {
+            let Some(distance) = distance_of(word, len) else {
+                continue;
+            };
+
+            let rank = (distance, len.abs_diff(word.len()), len);
+            if best.map_or(true, |(best_rank, _)| rank < best_rank) {
+                best = Some((rank, *digit));
+            }
+        }
+    }
+
+    best.map(|((_, _, len), digit)| (digit, len))
+}
+
+/// Distance of the token to the word when it's close enough and the `trimmed` token (without the outer char)
+/// isn't closer: a token must not begin (or end in the reverse scan) with an extra char
+fn accept(token: &[u8], word: &[u8], max_edits: usize, trimmed: &[u8]) -> Option<usize> {
+    if !token.iter().all(u8::is_ascii_alphabetic) {
+        return None;
+    }
+
+    let edits = distance(token, word);
+    (edits <= max_edits && distance(trimmed, word) >= edits).then_some(edits)
+}
+
+/// Levenshtein distance
+fn distance(a: &[u8], b: &[u8]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, &x) in a.iter().enumerate() {
+        current[0] = i + 1;
+
+        for (j, &y) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(x != y);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn words() -> Vec<(String, char)> {
+        vec![
+            ("one".to_owned(), '1'),
+            ("three".to_owned(), '3'),
+            ("seven".to_owned(), '7'),
+        ]
+    }
+
+    #[test]
+    fn test_distance() {
+        assert_eq!(distance(b"three", b"three"), 0);
+        assert_eq!(distance(b"thre", b"three"), 1);
+        assert_eq!(distance(b"sevcn", b"seven"), 1);
+        assert_eq!(distance(b"xthree", b"three"), 1);
+        assert_eq!(distance(b"", b"one"), 3);
+    }
+
+    #[test]
+    fn test_forward_backward() {
+        assert_eq!(forward("thre", 0, 1, &words()), Some(('3', 4)));
+        assert_eq!(forward("sevcn", 0, 1, &words()), Some(('7', 5)));
+        assert_eq!(forward("xthree", 0, 1, &words()), None);
+        assert_eq!(forward("on1", 0, 1, &words()), None);
+        assert_eq!(forward("sevcn", 0, 0, &words()), None);
+
+        assert_eq!(backward("abcthre", 7, 1, &words()), Some(('3', 4)));
+        assert_eq!(backward("seven", 5, 1, &words()), Some(('7', 5)));
+        assert_eq!(backward("threex", 6, 1, &words()), None);
+    }
+}
@@ -0,0 +1,93 @@
+use std::{
+    io::{self, BufWriter, Write},
+    path::PathBuf,
+};
+
+use clap::Args;
+use find_digits::Generator;
+
+use crate::output::AtomicFile;
+
+#[derive(Args, Debug, Clone)]
+pub struct GenerateArgs {
+    /// Amount of the generated lines
+    #[arg(long, default_value_t = 1000)]
+    lines: usize,
+    /// The same seed gives the same lines
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+    /// Where to write the lines instead of stdout, the totals are printed to stdout then, to stderr otherwise
+    #[arg(short, long)]
+    out: Option<PathBuf>,
+}
+
+/// The totals the generated lines must give
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Expected {
+    total_sum: u64,
+    /// the lines without digits
+    incorrect_lines: usize,
+}
+
+pub fn run(args: &GenerateArgs) -> Result<(), String> {
+    let mut generator = Generator::new(args.seed);
+
+    if let Some(path) = &args.out {
+        let mut output = AtomicFile::create(path)?;
+        let expected = write_lines(&mut output, &mut generator, args.lines)
+            .map_err(|e| format!("Fail to write output {}: {e:?}", path.display()))?;
+        output.commit()?;
+        println!("{}", render(args.lines, &expected));
+    } else {
+        let expected = write_lines(&mut BufWriter::new(io::stdout().lock()), &mut generator, args.lines)
+            .map_err(|e| format!("Fail to write the lines: {e:?}"))?;
+        eprintln!("{}", render(args.lines, &expected));
+    }
+
+    Ok(())
+}
+
+fn write_lines(output: &mut impl Write, generator: &mut Generator, lines: usize) -> io::Result<Expected> {
+    let mut expected = Expected::default();
+    for _ in 0..lines {
+        let (line, value) = generator.next_calibration();
+        match value {
+            Some(value) => expected.total_sum += u64::from(value),
+            None => expected.incorrect_lines += 1,
+        }
+        writeln!(output, "{line}")?;
+    }
+    output.flush()?;
+
+    Ok(expected)
+}
+
+fn render(lines: usize, expected: &Expected) -> String {
+    format!(
+        "Generated lines: {lines}, Incorrect lines {}, Total amount: {}",
+        expected.incorrect_lines, expected.total_sum
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use clap::Parser;
+    use find_digits::counting::ByteCounter;
+
+    use super::*;
+    use crate::process::{sum_read, Processing};
+
+    #[test]
+    fn test_write_lines() {
+        let mut text = Vec::new();
+        let expected = write_lines(&mut text, &mut Generator::new(7), 5000).unwrap();
+
+        let processing = Processing::new(&crate::Args::parse_from(["find_digits", "generate"])).unwrap();
+        let summary = sum_read("generated", &processing, &text[..], &ByteCounter::default());
+        assert_eq!(
+            (summary.parsed_lines, summary.incorrect_lines, summary.total_sum),
+            (5000, expected.incorrect_lines as u64, expected.total_sum)
+        );
+        assert!(expected.incorrect_lines < 5000);
+    }
+}
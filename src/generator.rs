@@ -0,0 +1,127 @@
+const WORDS: [&str; 9] = ["one", "two", "three", "four", "five", "six", "seven", "eight", "nine"];
+
+/// Spelled digits sharing a letter, with their first and last digits
+const OVERLAPS: [(&str, u8, u8); 8] = [
+    ("oneight", 1, 8),
+    ("twone", 2, 1),
+    ("threeight", 3, 8),
+    ("fiveight", 5, 8),
+    ("sevenine", 7, 9),
+    ("eightwo", 8, 2),
+    ("eighthree", 8, 3),
+    ("nineight", 9, 8),
+];
+
+/// The letters of none of the words, the noise can't spell a digit then
+const NOISE: &[u8] = b"abcdjklmpqyz";
+
+/// Deterministic generator of calibration-like lines: digits, spelled digits, overlapping words and letter noise.
+///
+/// The same seed always produces the same sequence of lines.
+#[derive(Debug, Clone)]
+pub struct Generator {
+    state: u64,
+}
+
+impl Generator {
+    #[must_use]
+    pub fn new(seed: u64) -> Self {
+        // xorshift must not start from zero
+        Self {
+            state: seed ^ 0x9E37_79B9_7F4A_7C15,
+        }
+    }
+
+    /// xorshift64*
+    pub fn next_u64(&mut self) -> u64 {
+        let mut x = self.state;
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.state = x;
+        x.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Random number in `0..bound`
+    ///
+    /// # Panics
+    ///
+    /// if `bound` is zero
+    pub fn below(&mut self, bound: usize) -> usize {
+        assert!(bound > 0, "bound must be positive");
+        usize::try_from(self.next_u64() % bound as u64).unwrap_or_default()
+    }
+
+    pub fn next_line(&mut self) -> String {
+        self.next_calibration().0
+    }
+
+    /// The next line and its calibration value, known from the generated tokens; `None` of a line without digits
+    pub fn next_calibration(&mut self) -> (String, Option<u32>) {
+        let tokens = 1 + self.below(8);
+        let mut line = String::new();
+        let (mut first, mut last) = (None, None);
+
+        for _ in 0..tokens {
+            let (digit_first, digit_last) = match self.below(10) {
+                0..=2 => {
+                    let digit = u8::try_from(self.below(10)).unwrap_or_default();
+                    line.push(char::from(b'0' + digit));
+                    (digit, digit)
+                },
+                3..=4 => {
+                    let word = self.below(WORDS.len());
+                    line.push_str(WORDS[word]);
+                    let digit = u8::try_from(word + 1).unwrap_or_default();
+                    (digit, digit)
+                },
+                5 => {
+                    let (words, digit_first, digit_last) = OVERLAPS[self.below(OVERLAPS.len())];
+                    line.push_str(words);
+                    (digit_first, digit_last)
+                },
+                _ => {
+                    for _ in 0..=self.below(4) {
+                        line.push(char::from(NOISE[self.below(NOISE.len())]));
+                    }
+                    continue;
+                },
+            };
+
+            first.get_or_insert(digit_first);
+            last = Some(digit_last);
+        }
+
+        let value = first
+            .zip(last)
+            .map(|(first, last)| u32::from(first) * 10 + u32::from(last));
+        (line, value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_generator_is_deterministic() {
+        let mut first = Generator::new(7);
+        let mut second = Generator::new(7);
+
+        for _ in 0..100 {
+            let line = first.next_line();
+            assert!(!line.is_empty());
+            assert_eq!(line, second.next_line());
+        }
+    }
+
+    #[test]
+    fn test_next_calibration() {
+        let mut generator = Generator::new(3);
+
+        for _ in 0..10_000 {
+            let (line, value) = generator.next_calibration();
+            assert_eq!(crate::extract_number(&line), value, "{line}");
+        }
+    }
+}
@@ -0,0 +1,61 @@
+//! The keys of `--group-by` partitioning the lines, e.g. a device ID embedded in every line
+
+use regex::Regex;
+
+/// Where the key of a line is
+#[derive(Debug, Clone)]
+pub enum GroupBy {
+    /// the first capture group of the regular expression, the whole match without one
+    Pattern(Regex),
+    /// the comma-separated field, counted from zero
+    Column(usize),
+}
+
+impl GroupBy {
+    /// `/<regex>/` is a regular expression, a number the column of the comma-separated fields counted from one
+    pub fn parse(value: &str) -> Result<Self, String> {
+        if let Some(pattern) = value.strip_prefix('/').and_then(|rest| rest.strip_suffix('/')) {
+            return Regex::new(pattern)
+                .map(Self::Pattern)
+                .map_err(|e| format!("incorrect group key {value}: {e}"));
+        }
+
+        match value.parse::<usize>() {
+            Ok(column) if column > 0 => Ok(Self::Column(column - 1)),
+            _ => Err(format!("group key {value} is neither /<regex>/ nor a column number from 1")),
+        }
+    }
+
+    /// The key of the line, `None` when the pattern doesn't match or the line has fewer columns
+    pub fn key<'a>(&self, line: &'a str) -> Option<&'a str> {
+        match self {
+            Self::Pattern(regex) => {
+                let captures = regex.captures(line)?;
+                captures.get(1).or_else(|| captures.get(0)).map(|key| key.as_str())
+            },
+            Self::Column(column) => line.split(',').nth(*column).map(str::trim),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_key() {
+        let keys = ["/device=(\\w+)/", "/[A-Z]{3}/", "2"];
+        let lines = ["device=ab12 two1nine", "x7 KEY9", "7, dev b ,nine"];
+        let expected = [Some("ab12"), Some("KEY"), Some("dev b")];
+
+        for (pos, key) in keys.into_iter().enumerate() {
+            let group_by = GroupBy::parse(key).unwrap();
+            assert_eq!(group_by.key(lines[pos]), expected[pos], "{key}");
+            assert_eq!(group_by.key("7"), None, "{key}");
+        }
+
+        assert!(GroupBy::parse("0").is_err());
+        assert!(GroupBy::parse("device").is_err());
+        assert!(GroupBy::parse("/(/").is_err());
+    }
+}
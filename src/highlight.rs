@@ -0,0 +1,120 @@
+use clap::ValueEnum;
+use find_digits::{LineValue, Match};
+
+const FIRST: &str = "\x1b[1;32m";
+const LAST: &str = "\x1b[1;36m";
+/// the first and the last matches overlap like in `eightwo` or it's the same match
+const BOTH: &str = "\x1b[1;33m";
+const RESET: &str = "\x1b[0m";
+
+/// When the output is colored
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColorMode {
+    /// only when stdout is a terminal
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorMode {
+    /// `terminal` tells whether the output is a terminal
+    pub fn enabled(self, terminal: bool) -> bool {
+        match self {
+            Self::Auto => terminal,
+            Self::Always => true,
+            Self::Never => false,
+        }
+    }
+}
+
+/// The original line with its first and last matches colored, it's returned as is without a value
+///
+/// The matches are the ones of `line`, the original one after the confusables replacement: a replacement of a char
+/// by a char, their spans are moved to the same chars of the original line.
+pub fn render(original: &str, line: &str, value: Option<&LineValue>) -> String {
+    let Some(value) = value else {
+        return original.to_owned();
+    };
+
+    let span = |m: &Match| original_offset(original, line, m.start)..original_offset(original, line, m.end);
+    let (first, last) = (span(&value.first), span(&value.last));
+    let line = original;
+
+    let mut bounds = vec![0, first.start, first.end, last.start, last.end, line.len()];
+    bounds.sort_unstable();
+    bounds.dedup();
+
+    let mut rendered = String::with_capacity(line.len() + 32);
+    for segment in bounds.windows(2) {
+        let (start, end) = (segment[0], segment[1]);
+        let color = match (first.contains(&start), last.contains(&start)) {
+            (true, true) => Some(BOTH),
+            (true, false) => Some(FIRST),
+            (false, true) => Some(LAST),
+            (false, false) => None,
+        };
+
+        match color {
+            Some(color) => {
+                rendered.push_str(color);
+                rendered.push_str(&line[start..end]);
+                rendered.push_str(RESET);
+            },
+            None => rendered.push_str(&line[start..end]),
+        }
+    }
+
+    rendered
+}
+
+/// The byte offset in `original` of the char at the byte `offset` of `line`
+fn original_offset(original: &str, line: &str, offset: usize) -> usize {
+    if original == line {
+        return offset;
+    }
+
+    let chars = line[..offset].chars().count();
+    original
+        .char_indices()
+        .nth(chars)
+        .map_or(original.len(), |(pos, _)| pos)
+}
+
+#[cfg(test)]
+mod test {
+    use find_digits::{extract_line, Confusables};
+
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let strings = ["two1nine", "eightwo", "treb7uchet", "abcdefg"];
+        let expected = [
+            "\x1b[1;32mtwo\x1b[0m1\x1b[1;36mnine\x1b[0m",
+            "\x1b[1;32meigh\x1b[0m\x1b[1;33mt\x1b[0m\x1b[1;36mwo\x1b[0m",
+            "treb\x1b[1;33m7\x1b[0muchet",
+            "abcdefg",
+        ];
+
+        for (pos, line) in strings.into_iter().enumerate() {
+            assert_eq!(render(line, line, extract_line(line).ok().as_ref()), expected[pos]);
+        }
+    }
+
+    #[test]
+    fn test_render_original() {
+        let confusables = Confusables::builtin();
+        let strings = ["\u{3C4}w\u{43E}1nin\u{435}", "abclx", "s\u{435}v\u{435}n"];
+        let expected = [
+            "\x1b[1;32m\u{3C4}w\u{43E}\x1b[0m1\x1b[1;36mnin\u{435}\x1b[0m",
+            "abc\x1b[1;33ml\x1b[0mx",
+            "\x1b[1;33ms\u{435}v\u{435}n\x1b[0m",
+        ];
+
+        for (pos, original) in strings.into_iter().enumerate() {
+            let line = confusables.normalize(original);
+            assert_eq!(render(original, &line, extract_line(&line).ok().as_ref()), expected[pos]);
+        }
+    }
+}
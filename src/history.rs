@@ -0,0 +1,190 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    sync::{Mutex, PoisonError},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use clap::Args;
+use find_digits::{
+    units::{format_timestamp, parse_duration},
+    Summary,
+};
+use rusqlite::{params, Connection};
+
+const HISTORY_FILE_NAME: &str = "history.db";
+
+/// One row per completed input, the timestamps are the seconds since the Unix epoch
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS history (
+    timestamp INTEGER NOT NULL,
+    input TEXT NOT NULL,
+    parsed_lines INTEGER NOT NULL,
+    incorrect_lines INTEGER NOT NULL,
+    total_sum INTEGER NOT NULL,
+    elapsed_micros INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS history_timestamp ON history (timestamp);
+";
+
+#[derive(Args, Debug, Clone)]
+pub struct HistoryArgs {
+    /// Only show summaries recorded within this period, e.g. `7d` or `12h`
+    #[arg(long, value_parser = parse_duration)]
+    since: Option<Duration>,
+}
+
+/// Summary of one completed input as stored in the history database
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    /// seconds since the Unix epoch when the input was completed
+    pub timestamp: u64,
+    pub file: String,
+    pub summary: Summary,
+    pub elapsed: Duration,
+}
+
+/// The history database, shared by the workers of the servers
+pub struct History {
+    database: Mutex<Connection>,
+    path: PathBuf,
+}
+
+impl History {
+    /// Open the database, the file, its directory and the table are created when missing
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let error = |e: &dyn std::fmt::Display| format!("Fail to open history database {}: {e}", path.display());
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| error(&e))?;
+        }
+        let database = Connection::open(path).map_err(|e| error(&e))?;
+        database.execute_batch(SCHEMA).map_err(|e| error(&e))?;
+
+        Ok(Self {
+            database: Mutex::new(database),
+            path: path.to_owned(),
+        })
+    }
+
+    /// Add the summary of the completed input
+    pub fn record(&self, file: &str, summary: &Summary, elapsed: Duration) -> Result<(), String> {
+        let elapsed_micros = u64::try_from(elapsed.as_micros()).unwrap_or(u64::MAX);
+        // SQLite integers are signed, the totals above `i64::MAX` are stored by their bits
+        #[allow(clippy::cast_possible_wrap)]
+        let columns = [
+            now(),
+            summary.parsed_lines,
+            summary.incorrect_lines,
+            summary.total_sum,
+            elapsed_micros,
+        ]
+        .map(|column| column as i64);
+
+        self.database
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .execute(
+                "INSERT INTO history (timestamp, input, parsed_lines, incorrect_lines, total_sum, elapsed_micros) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![columns[0], file, columns[1], columns[2], columns[3], columns[4]],
+            )
+            .map(|_| ())
+            .map_err(|e| format!("Fail to record history into {}: {e}", self.path.display()))
+    }
+
+    /// The entries completed at `since` or later, the oldest first
+    pub fn entries(&self, since: u64) -> Result<Vec<Entry>, String> {
+        let error = |e: rusqlite::Error| format!("Fail to read history database {}: {e}", self.path.display());
+        let database = self.database.lock().unwrap_or_else(PoisonError::into_inner);
+
+        let mut select = database
+            .prepare(
+                "SELECT timestamp, input, parsed_lines, incorrect_lines, total_sum, elapsed_micros FROM history \
+                 WHERE timestamp >= ?1 ORDER BY timestamp, rowid",
+            )
+            .map_err(error)?;
+        #[allow(clippy::cast_sign_loss, clippy::cast_possible_wrap)]
+        let entries = select
+            .query_map(params![since as i64], |row| {
+                Ok(Entry {
+                    timestamp: row.get::<_, i64>(0)? as u64,
+                    file: row.get(1)?,
+                    summary: Summary {
+                        parsed_lines: row.get::<_, i64>(2)? as u64,
+                        incorrect_lines: row.get::<_, i64>(3)? as u64,
+                        total_sum: row.get::<_, i64>(4)? as u64,
+                        ..Summary::default()
+                    },
+                    elapsed: Duration::from_micros(row.get::<_, i64>(5)? as u64),
+                })
+            })
+            .and_then(Iterator::collect)
+            .map_err(error)?;
+
+        Ok(entries)
+    }
+}
+
+/// `--history-file` or `$XDG_DATA_HOME/find_digits/history.db` or `~/.local/share/find_digits/history.db`
+pub fn location(history_file: Option<&Path>) -> Result<PathBuf, String> {
+    if let Some(path) = history_file {
+        return Ok(path.to_owned());
+    }
+
+    let data_home = env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".local/share")))
+        .ok_or_else(|| "History file location is unknown, use --history-file".to_owned())?;
+
+    Ok(data_home.join(env!("CARGO_PKG_NAME")).join(HISTORY_FILE_NAME))
+}
+
+pub fn run(args: &HistoryArgs, path: &Path) -> Result<(), String> {
+    let since = args.since.map_or(0, |since| now().saturating_sub(since.as_secs()));
+
+    for entry in History::open(path)?.entries(since)? {
+        println!("{} {} {}, Elapsed {:?}", format_timestamp(entry.timestamp), entry.file, entry.summary, entry.elapsed);
+    }
+
+    Ok(())
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::temp_file;
+
+    #[test]
+    fn test_record() {
+        let path = temp_file(".db", "");
+        let history = History::open(&path).unwrap();
+
+        let summary = Summary {
+            parsed_lines: 9,
+            incorrect_lines: 2,
+            total_sum: u64::MAX,
+            ..Summary::default()
+        };
+        history
+            .record("some/where/my\tfile.txt", &summary, Duration::from_millis(1500))
+            .unwrap();
+        history
+            .record("127.0.0.1:9000", &Summary::default(), Duration::ZERO)
+            .unwrap();
+
+        let entries = History::open(&path).unwrap().entries(0).unwrap();
+        let files: Vec<&str> = entries.iter().map(|entry| entry.file.as_str()).collect();
+        assert_eq!(files, ["some/where/my\tfile.txt", "127.0.0.1:9000"]);
+        assert_eq!(entries[0].summary, summary);
+        assert_eq!(entries[0].elapsed, Duration::from_millis(1500));
+        assert!(entries[0].timestamp.abs_diff(now()) < 60);
+        assert!(history.entries(now() + 60).unwrap().is_empty());
+    }
+}
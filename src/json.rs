@@ -0,0 +1,41 @@
+use std::fmt::Write;
+
+/// The string field of a JSON record by its dotted path like `payload.text`, array items are numbered: `items.0`
+///
+/// # Errors
+///
+/// the record isn't valid JSON, there is no such field or it isn't a string
+pub fn field(record: &str, path: &str) -> Result<String, String> {
+    let value: serde_json::Value = serde_json::from_str(record).map_err(|e| format!("invalid JSON: {e}"))?;
+
+    let mut pointer = String::with_capacity(path.len() + 1);
+    for key in path.split('.') {
+        let _ = write!(pointer, "/{}", key.replace('~', "~0").replace('/', "~1"));
+    }
+
+    match value.pointer(&pointer) {
+        Some(serde_json::Value::String(text)) => Ok(text.clone()),
+        Some(_) => Err(format!("field {path} isn't a string")),
+        None => Err(format!("no field {path}")),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_field() {
+        let record = r#"{"level": "info", "payload": {"text": "two1nine", "items": ["7x", {"a/b": "x3"}]}, "n": 5}"#;
+        let paths = ["level", "payload.text", "payload.items.0", "payload.items.1.a/b"];
+        let expected = ["info", "two1nine", "7x", "x3"];
+
+        for (pos, path) in paths.into_iter().enumerate() {
+            assert_eq!(field(record, path).as_deref(), Ok(expected[pos]));
+        }
+
+        assert_eq!(field(record, "n"), Err("field n isn't a string".to_owned()));
+        assert_eq!(field(record, "payload.missing"), Err("no field payload.missing".to_owned()));
+        assert!(field("{\"text\": ", "text").is_err());
+    }
+}
@@ -0,0 +1,72 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+#![warn(clippy::cargo, clippy::pedantic)]
+#![allow(
+    clippy::cargo_common_metadata,
+    clippy::cast_precision_loss,
+    clippy::multiple_crate_versions
+)]
+
+//! Find the first and the last digits (numeric or spelled with letters) in a line of text
+//!
+//! The extraction functions never panic, whatever the line and the positions given to them are,
+//! the fuzz targets of `fuzz/` check it.
+//! Without the `std` feature only the allocation-free scanner of [`bare`] is built.
+
+/// Items of the `std` feature
+macro_rules! with_std {
+    ($($item:item)*) => {
+        $(
+            #[cfg(feature = "std")]
+            $item
+        )*
+    };
+}
+
+pub mod bare;
+
+with_std! {
+    mod batch;
+    mod byteset;
+    #[cfg(feature = "capi")]
+    pub mod capi;
+    mod confusables;
+    pub mod counting;
+    mod dictionary;
+    mod extractor;
+    mod finder;
+    mod fuzzy;
+    mod generator;
+    pub mod json;
+    mod line;
+    mod lines;
+    #[cfg(feature = "python")]
+    pub mod python;
+    mod scanner;
+    mod stats;
+    mod stream;
+    mod summary;
+    // the directories are used by the tests of the binary only
+    #[cfg(all(test, feature = "python"))]
+    #[allow(dead_code)]
+    mod testing;
+    mod unicode;
+    pub mod units;
+    #[cfg(feature = "wasm")]
+    pub mod wasm;
+
+    pub use batch::{extract_batch, extract_batch_with};
+    pub use confusables::Confusables;
+    pub use dictionary::Dictionary;
+    pub use extractor::Extractor;
+    pub use finder::{Combine, DigitFinder, DigitFinderBuilder};
+    pub use generator::Generator;
+    pub use line::{extract_line, extract_line_with, LineError, LineValue};
+    pub use lines::{lines, lines_with, trim_line, LineResult, Lines};
+    pub use scanner::{
+        cross_check, extract_number, extract_number_with, find, find_with, first_digit, last_digit, matches_with, r_find,
+        r_find_with, Match, MatchKind, Overlap, ScanOptions, SearchResult,
+    };
+    pub use stats::{GroupStats, InputEnds, LineStats, TokenStats, TopLines};
+    pub use stream::StreamScanner;
+    pub use summary::{ByteCount, Summary};
+}
@@ -0,0 +1,99 @@
+use std::{error::Error, fmt};
+
+use crate::{
+    scanner::{backward_match, forward_match},
+    Match, ScanOptions,
+};
+
+/// Calibration value of a line together with the matches it was built from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LineValue {
+    pub value: u32,
+    pub first: Match,
+    pub last: Match,
+}
+
+/// Why a line has no calibration value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum LineError {
+    Empty,
+    NoDigits,
+}
+
+impl fmt::Display for LineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "empty line"),
+            Self::NoDigits => write!(f, "no digits in the line"),
+        }
+    }
+}
+
+impl Error for LineError {}
+
+/// Calibration value of the line with the first and the last matches
+///
+/// # Errors
+///
+/// the line is empty or has no digits
+pub fn extract_line(line: &str) -> Result<LineValue, LineError> {
+    extract_line_with(line, &ScanOptions::default())
+}
+
+/// [`extract_line`] with custom scan options
+///
+/// # Errors
+///
+/// the line is empty or has no digits
+pub fn extract_line_with(line: &str, options: &ScanOptions) -> Result<LineValue, LineError> {
+    if line.is_empty() {
+        return Err(LineError::Empty);
+    }
+
+    let first = forward_match(line, options).ok_or(LineError::NoDigits)?;
+    let last = backward_match(line, first.start, options).ok_or(LineError::NoDigits)?;
+
+    Ok(LineValue {
+        value: Match::combine(&first, &last),
+        first,
+        last,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_extract_line() {
+        let strings = ["eightwothree", "treb7uchet", "abcdefg", ""];
+        let expected = [Ok(83), Ok(77), Err(LineError::NoDigits), Err(LineError::Empty)];
+
+        for (pos, line) in strings.into_iter().enumerate() {
+            assert_eq!(extract_line(line).map(|v| v.value), expected[pos]);
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let strings = ["two1nine", "abcdefg", ""];
+        let expected = [
+            r#"{"Ok":{"value":29,"first":{"digit":2,"start":0,"end":3,"kind":"word"},"last":{"digit":9,"start":4,"end":8,"kind":"word"}}}"#,
+            r#"{"Err":"no_digits"}"#,
+            r#"{"Err":"empty"}"#,
+        ];
+
+        for (pos, line) in strings.into_iter().enumerate() {
+            let result = extract_line(line);
+            let text = serde_json::to_string(&result).unwrap();
+            assert_eq!(text, expected[pos]);
+            assert_eq!(serde_json::from_str::<Result<LineValue, LineError>>(&text).unwrap(), result);
+        }
+
+        assert!(serde_json::from_str::<Match>(r#"{"digit":12,"start":0,"end":1,"kind":"digit"}"#).is_err());
+    }
+}
@@ -0,0 +1,110 @@
+use std::io::{self, BufRead};
+
+use crate::{Extractor, LineError, LineValue, ScanOptions};
+
+/// A line of a reader with its value
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LineResult {
+    /// counted from zero, the lines which fail to be read are counted too
+    pub number: usize,
+    /// without the line ending `\n`, `\r\n` or `\r` and the byte order mark of the first line, see [`trim_line`]
+    pub line: String,
+    pub value: Result<LineValue, LineError>,
+}
+
+/// The lines of a reader with their values, see [`lines`]
+#[derive(Debug)]
+pub struct Lines<R, E> {
+    reader: io::Lines<R>,
+    number: usize,
+    extractor: E,
+}
+
+impl<R: BufRead, E: Extractor> Iterator for Lines<R, E> {
+    type Item = io::Result<LineResult>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let read_result = self.reader.next()?;
+        let number = self.number;
+        self.number += 1;
+
+        Some(read_result.map(|mut line| {
+            let trimmed = trim_line(&line, number == 0);
+            if trimmed.len() != line.len() {
+                line = trimmed.to_owned();
+            }
+            LineResult {
+                number,
+                value: self.extractor.extract(&line),
+                line,
+            }
+        }))
+    }
+}
+
+/// The line without a trailing `\r` and, the first line of a text, without the UTF-8 byte order mark,
+/// so the files of Windows editors give the same values as the other ones
+#[must_use]
+pub fn trim_line(line: &str, first: bool) -> &str {
+    let line = line.strip_suffix('\r').unwrap_or(line);
+    if first {
+        line.strip_prefix('\u{FEFF}').unwrap_or(line)
+    } else {
+        line
+    }
+}
+
+/// Every line of the reader (a file, a socket, a buffer in memory) with its calibration value
+///
+/// A line with invalid UTF-8 is an error of the kind [`io::ErrorKind::InvalidData`], the next lines are read after it.
+///
+/// ```
+/// let text = "two1nine\nabcdefg\n";
+/// let values: Vec<_> = find_digits::lines(text.as_bytes()).map(|result| result.unwrap().value.ok()).collect();
+/// assert_eq!(values.len(), 2);
+/// ```
+pub fn lines<R: BufRead>(reader: R) -> Lines<R, ScanOptions> {
+    lines_with(reader, ScanOptions::default())
+}
+
+/// [`lines`] with another extractor, e.g. [`ScanOptions`] or a [`crate::DigitFinder`]
+pub fn lines_with<R: BufRead, E: Extractor>(reader: R, extractor: E) -> Lines<R, E> {
+    Lines {
+        reader: reader.lines(),
+        number: 0,
+        extractor,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::DigitFinder;
+
+    #[test]
+    fn test_lines() {
+        let text = b"\xEF\xBB\xBFtwo1nine\r\n\nab\xFFc\nTREB7uchet\r\nxtwone3four\r";
+
+        let values: Vec<_> = lines(&text[..])
+            .map(|result| result.map(|line| (line.number, line.line, line.value.map(|value| value.value))))
+            .map(|result| result.map_err(|e| e.kind()))
+            .collect();
+        assert_eq!(
+            values,
+            [
+                Ok((0, "two1nine".to_owned(), Ok(29))),
+                Ok((1, String::new(), Err(LineError::Empty))),
+                Err(io::ErrorKind::InvalidData),
+                Ok((3, "TREB7uchet".to_owned(), Ok(77))),
+                Ok((4, "xtwone3four".to_owned(), Ok(24))),
+            ]
+        );
+
+        let finder = DigitFinder::builder().case_sensitive(false).build();
+        let values: Vec<_> = lines_with("ONE\nfive6\n".as_bytes(), finder)
+            .map(|result| result.unwrap().value.map(|value| value.value))
+            .collect();
+        assert_eq!(values, [Ok(11), Ok(56)]);
+    }
+}
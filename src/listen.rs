@@ -0,0 +1,204 @@
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    net::TcpListener,
+    str,
+    sync::{Arc, Mutex, PoisonError},
+    thread,
+    time::Instant,
+};
+
+#[cfg(unix)]
+use std::os::unix::net::UnixListener;
+
+use clap::Args;
+use find_digits::Summary;
+
+use crate::process::{self, Processing};
+
+#[derive(Args, Debug, Clone)]
+pub struct ListenArgs {
+    /// `host:port` or `unix:/path/to/socket`
+    #[arg(long, default_value = "127.0.0.1:9000")]
+    pub address: String,
+}
+
+/// Accept connections on `host:port` or `unix:/path/to/socket`, every received line is processed as an input line
+///
+/// Every line is answered by the running totals of its connection, or by its error (e.g. of `--on-no-digits error`),
+/// the global totals are reported when a connection is closed.
+pub fn run(address: &str, processing: Processing) -> Result<(), String> {
+    let processing = Arc::new(processing);
+    process::reload_on_hangup(&processing);
+    let totals = Arc::new(Mutex::new(Summary::default()));
+
+    if let Some(path) = address.strip_prefix("unix:") {
+        #[cfg(unix)]
+        {
+            let listener = bind_unix(path).map_err(|e| format!("Fail to listen on {address}: {e}"))?;
+            println!("Listening on {address}");
+
+            for (number, stream) in listener.incoming().enumerate() {
+                let stream = stream.map_err(|e| format!("Fail to accept connection on {address}: {e}"))?;
+                let (processing, totals) = (processing.clone(), totals.clone());
+                let peer = format!("{address}#{number}");
+                thread::spawn(move || serve(&peer, BufReader::new(&stream), &stream, &processing, &totals));
+            }
+
+            return Ok(());
+        }
+
+        #[cfg(not(unix))]
+        return Err(format!("Unix socket {path} isn't supported on this platform"));
+    }
+
+    let listener = TcpListener::bind(address).map_err(|e| format!("Fail to listen on {address}: {e}"))?;
+    println!("Listening on {address}");
+
+    for stream in listener.incoming() {
+        let stream = stream.map_err(|e| format!("Fail to accept connection on {address}: {e}"))?;
+        let peer = stream
+            .peer_addr()
+            .map_or_else(|_| address.to_owned(), |peer| peer.to_string());
+        // the totals are answered line by line, they must not wait for more lines
+        let _ = stream.set_nodelay(true);
+        let (processing, totals) = (processing.clone(), totals.clone());
+        thread::spawn(move || serve(&peer, BufReader::new(&stream), &stream, &processing, &totals));
+    }
+
+    Ok(())
+}
+
+/// Bind the socket file, a socket left by a previous run is replaced but a file of any other type is kept
+#[cfg(unix)]
+fn bind_unix(path: &str) -> io::Result<UnixListener> {
+    use std::{fs, os::unix::fs::FileTypeExt};
+
+    match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() => fs::remove_file(path)?,
+        Ok(_) => {
+            return Err(io::Error::new(io::ErrorKind::AddrInUse, format!("{path} already exists and isn't a socket")))
+        },
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {},
+        Err(e) => return Err(e),
+    }
+
+    UnixListener::bind(path)
+}
+
+/// Process the lines of one connection and add its totals to the global ones
+fn serve(peer: &str, input: impl BufRead, output: impl Write, processing: &Processing, totals: &Mutex<Summary>) {
+    let (mut summary, start) = (processing.summary(), Instant::now());
+    if let Err(e) = sum_connection(peer, input, output, processing, &mut summary) {
+        println!("Connection {peer} failed: {e}");
+    }
+    println!("Connection {peer}: {summary}");
+    if summary.parsed_lines > 0 {
+        processing.record_history(peer, &summary, start.elapsed());
+    }
+
+    let mut totals = totals.lock().unwrap_or_else(PoisonError::into_inner);
+    totals.merge(&summary);
+    println!("Total of all connections: {totals}");
+}
+
+fn sum_connection(
+    peer: &str,
+    mut input: impl BufRead,
+    mut output: impl Write,
+    processing: &Processing,
+    summary: &mut Summary,
+) -> io::Result<()> {
+    let mut cache = processing.line_cache();
+    let mut buffer = Vec::new();
+
+    for number in 0.. {
+        buffer.clear();
+        if input.read_until(b'\n', &mut buffer)? == 0 {
+            break;
+        }
+
+        let line = buffer.strip_suffix(b"\n").unwrap_or(&buffer);
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+
+        summary.parsed_lines += 1;
+        let processed = match str::from_utf8(line) {
+            Ok(line) => processing
+                .process_line(peer, number, line, summary, &mut cache)
+                .map(drop),
+            Err(e) => {
+                println!("Connection {peer} broken line(number {number}): {e:?}");
+                Ok(())
+            },
+        };
+
+        // a failed line is answered by its error, the connection goes on
+        match processed {
+            Ok(()) => writeln!(output, "{summary}")?,
+            Err(e) => writeln!(output, "Error: {e}")?,
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::{io::Read, net::TcpStream};
+
+    use clap::Parser;
+
+    use super::*;
+
+    #[test]
+    fn test_sum_connection() {
+        let processing = Processing::new(&crate::Args::parse_from(["find_digits", "listen"])).unwrap();
+        let (mut output, mut summary) = (Vec::new(), processing.summary());
+
+        let input = &b"two1nine\r\nabcdefg\n\xff\n4nineeightseven2"[..];
+        sum_connection("test", input, &mut output, &processing, &mut summary).unwrap();
+
+        assert_eq!((summary.parsed_lines, summary.incorrect_lines, summary.total_sum), (4, 1, 29 + 42));
+        assert_eq!(
+            String::from_utf8(output).unwrap().lines().last(),
+            Some("Parsed lines: 4, Incorrect lines 1, Total amount: 71")
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_bind_unix() {
+        let file = crate::testing::temp_file(".txt", "1\n2\n");
+        let path = file.to_str().unwrap();
+        assert!(bind_unix(path).unwrap_err().to_string().ends_with("isn't a socket"));
+        assert_eq!(std::fs::read_to_string(path).unwrap(), "1\n2\n");
+
+        let directory = crate::testing::temp_dir();
+        let path = directory.path().join("socket");
+        let path = path.to_str().unwrap();
+        drop(bind_unix(path).unwrap());
+        // the socket file is left when the listener is dropped, it's bound again
+        bind_unix(path).unwrap();
+    }
+
+    #[test]
+    fn test_serve() {
+        let processing = Processing::new(&crate::Args::parse_from(["find_digits", "listen"])).unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+        let totals = Mutex::new(Summary::default());
+
+        client.write_all(b"two1nine\neightwothree\n").unwrap();
+        client.shutdown(std::net::Shutdown::Write).unwrap();
+        serve("test", BufReader::new(&server), &server, &processing, &totals);
+        drop(server);
+
+        let mut replies = String::new();
+        client.read_to_string(&mut replies).unwrap();
+        assert_eq!(
+            replies,
+            "Parsed lines: 1, Incorrect lines 0, Total amount: 29\nParsed lines: 2, Incorrect lines 0, Total amount: 112\n"
+        );
+        assert_eq!(totals.into_inner().unwrap().total_sum, 112);
+    }
+}
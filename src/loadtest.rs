@@ -0,0 +1,275 @@
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    net::TcpStream,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use clap::Args;
+use find_digits::{units::parse_duration, Generator};
+
+const HTTP_DEFAULT_PATH: &str = "/extract";
+
+#[derive(Args, Debug, Clone)]
+pub struct LoadtestArgs {
+    /// Server to drive: `http://host:port[/path]` or `tcp://host:port`
+    #[arg(long)]
+    target: String,
+    /// Requested lines per second over all connections
+    #[arg(long, default_value_t = 1000)]
+    rps: u64,
+    #[arg(long, value_parser = parse_duration, default_value = "10s")]
+    duration: Duration,
+    #[arg(long, default_value_t = 4)]
+    connections: u64,
+    /// Seed of the generated calibration lines
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Target {
+    Http {
+        address: String,
+        host: String,
+        path: String,
+    },
+    Tcp {
+        address: String,
+    },
+}
+
+impl Target {
+    fn parse(target: &str) -> Result<Self, String> {
+        if let Some(rest) = target.strip_prefix("http://") {
+            let (address, path) = match rest.find('/') {
+                Some(pos) => (&rest[..pos], &rest[pos..]),
+                None => (rest, HTTP_DEFAULT_PATH),
+            };
+
+            return Ok(Self::Http {
+                address: with_port(address, 80),
+                host: address.to_owned(),
+                path: path.to_owned(),
+            });
+        }
+
+        if let Some(address) = target.strip_prefix("tcp://") {
+            return Ok(Self::Tcp {
+                address: address.trim_end_matches('/').to_owned(),
+            });
+        }
+
+        Err(format!("unsupported target {target}, expected http:// or tcp://"))
+    }
+
+    fn address(&self) -> &str {
+        match self {
+            Self::Http { address, .. } | Self::Tcp { address } => address,
+        }
+    }
+}
+
+fn with_port(address: &str, port: u16) -> String {
+    if address.contains(':') {
+        address.to_owned()
+    } else {
+        format!("{address}:{port}")
+    }
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    sent: AtomicU64,
+    succeeded: AtomicU64,
+    failed: AtomicU64,
+    latency_micros: AtomicU64,
+}
+
+pub fn run(args: &LoadtestArgs) -> Result<(), String> {
+    let target = Target::parse(&args.target)?;
+    if args.connections == 0 || args.rps == 0 {
+        return Err("--rps and --connections must be positive".to_owned());
+    }
+
+    println!(
+        "Load testing {} with {} lines/s over {} connections for {:?}",
+        args.target, args.rps, args.connections, args.duration
+    );
+
+    let counters = Arc::new(Counters::default());
+    let stop = Arc::new(AtomicBool::new(false));
+    let interval = Duration::from_secs_f64(args.connections as f64 / args.rps as f64);
+
+    let workers: Vec<_> = (0..args.connections)
+        .map(|number| {
+            let target = target.clone();
+            let counters = Arc::clone(&counters);
+            let stop = Arc::clone(&stop);
+            let seed = args.seed.wrapping_add(number);
+
+            thread::spawn(move || worker(&target, interval, seed, &counters, &stop))
+        })
+        .collect();
+
+    let start_timestamp = Instant::now();
+    thread::sleep(args.duration);
+    stop.store(true, Ordering::Relaxed);
+
+    for handle in workers {
+        if let Err(e) = handle.join() {
+            println!("Load test worker panicked: {e:?}");
+        }
+    }
+
+    let elapsed = start_timestamp.elapsed();
+    let sent = counters.sent.load(Ordering::Relaxed);
+    let succeeded = counters.succeeded.load(Ordering::Relaxed);
+    let failed = counters.failed.load(Ordering::Relaxed);
+    let latency = counters.latency_micros.load(Ordering::Relaxed);
+
+    println!(
+        "Sent: {sent}, Succeeded: {succeeded}, Failed: {failed}, Error rate: {:.2}%, Throughput: {:.1} lines/s, \
+         Mean latency: {:?}, Elapsed {elapsed:?}",
+        if sent == 0 {
+            0.0
+        } else {
+            failed as f64 * 100.0 / sent as f64
+        },
+        succeeded as f64 / elapsed.as_secs_f64(),
+        Duration::from_micros(latency.checked_div(succeeded).unwrap_or_default()),
+    );
+
+    Ok(())
+}
+
+fn worker(target: &Target, interval: Duration, seed: u64, counters: &Counters, stop: &AtomicBool) {
+    let mut generator = Generator::new(seed);
+    let mut connection: Option<BufReader<TcpStream>> = None;
+    let mut next_send = Instant::now();
+
+    while !stop.load(Ordering::Relaxed) {
+        let now = Instant::now();
+        if next_send > now {
+            thread::sleep(next_send - now);
+        }
+        next_send += interval;
+
+        let line = generator.next_line();
+        counters.sent.fetch_add(1, Ordering::Relaxed);
+
+        let request_timestamp = Instant::now();
+        let result = match connection.as_mut() {
+            Some(stream) => send(target, stream, &line),
+            None => TcpStream::connect(target.address()).and_then(|stream| {
+                stream.set_nodelay(true)?;
+                let stream = connection.insert(BufReader::new(stream));
+                send(target, stream, &line)
+            }),
+        };
+
+        if result.is_ok() {
+            counters.succeeded.fetch_add(1, Ordering::Relaxed);
+            let micros = u64::try_from(request_timestamp.elapsed().as_micros()).unwrap_or(u64::MAX);
+            counters.latency_micros.fetch_add(micros, Ordering::Relaxed);
+        } else {
+            counters.failed.fetch_add(1, Ordering::Relaxed);
+            // reconnect on the next request
+            connection = None;
+        }
+    }
+}
+
+fn send(target: &Target, stream: &mut BufReader<TcpStream>, line: &str) -> io::Result<()> {
+    match target {
+        Target::Http { host, path, .. } => {
+            let request = format!(
+                "POST {path} HTTP/1.1\r\nHost: {host}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\n\r\n{line}\n",
+                line.len() + 1
+            );
+            stream.get_mut().write_all(request.as_bytes())?;
+
+            read_http_response(stream)
+        },
+        Target::Tcp { .. } => {
+            stream.get_mut().write_all(format!("{line}\n").as_bytes())?;
+
+            let mut response = String::new();
+            if stream.read_line(&mut response)? == 0 {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+
+            Ok(())
+        },
+    }
+}
+
+fn read_http_response(stream: &mut BufReader<TcpStream>) -> io::Result<()> {
+    let mut status = String::new();
+    if stream.read_line(&mut status)? == 0 {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+
+    let mut content_length = 0;
+    loop {
+        let mut header = String::new();
+        if stream.read_line(&mut header)? == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().map_err(|_| io::ErrorKind::InvalidData)?;
+            }
+        }
+    }
+
+    let mut body = vec![0; content_length];
+    stream.read_exact(&mut body)?;
+
+    match status.split_whitespace().nth(1) {
+        Some(code) if code.starts_with('2') => Ok(()),
+        _ => Err(io::Error::other(format!("unexpected response {}", status.trim_end()))),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_target_parse() {
+        assert_eq!(
+            Target::parse("http://localhost:8080"),
+            Ok(Target::Http {
+                address: "localhost:8080".to_owned(),
+                host: "localhost:8080".to_owned(),
+                path: HTTP_DEFAULT_PATH.to_owned(),
+            })
+        );
+        assert_eq!(
+            Target::parse("http://example.com/api/extract"),
+            Ok(Target::Http {
+                address: "example.com:80".to_owned(),
+                host: "example.com".to_owned(),
+                path: "/api/extract".to_owned(),
+            })
+        );
+        assert_eq!(
+            Target::parse("tcp://127.0.0.1:9000"),
+            Ok(Target::Tcp {
+                address: "127.0.0.1:9000".to_owned(),
+            })
+        );
+        assert!(Target::parse("grpc://127.0.0.1:9000").is_err());
+    }
+}
@@ -6,320 +6,942 @@
 )]
 
 use std::{
-    fs::File,
-    io::{BufRead, BufReader},
+    fmt::Write as _,
+    num::NonZeroUsize,
+    ops::Range,
+    path::PathBuf,
     process::exit,
-    time::{Instant, SystemTime, UNIX_EPOCH},
+    sync::Arc,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
-use clap::Parser;
-
-const REPORT_DELAY: std::time::Duration = std::time::Duration::from_secs(10);
-
-static LETTERS_DIGITS: [(&str, &str, char); 9] = [
-    ("one", "one", '1'),
-    ("two", "two", '2'),
-    ("six", "six", '6'),
-    ("fou", "four", '4'),
-    ("fiv", "five", '5'),
-    ("nin", "nine", '9'),
-    ("sev", "seven", '7'),
-    ("eig", "eight", '8'),
-    ("thr", "three", '3'),
-];
-
-static LETTERS_DIGITS_REV: [(&str, &str, char); 9] = [
-    ("one", "one", '1'),
-    ("two", "two", '2'),
-    ("six", "six", '6'),
-    ("our", "four", '4'),
-    ("ive", "five", '5'),
-    ("ine", "nine", '9'),
-    ("ven", "seven", '7'),
-    ("ght", "eight", '8'),
-    ("ree", "three", '3'),
-];
-
-const LETTERS_DIGIT_MIN_LEN: usize = 3;
-
-#[derive(Parser, Debug)]
-#[command(author, version, about, long_about = None)]
+use archive::Archive;
+use backend::IoBackend;
+use clap::{error::ErrorKind, Parser, Subcommand, ValueEnum};
+use decompress::Compression;
+use encoding_rs::Encoding;
+use find_digits::{
+    counting::ByteCounter,
+    units::{parse_duration, parse_line_range, parse_size},
+    Overlap, Summary,
+};
+use group::GroupBy;
+use process::{sum_file, Processing};
+use records::RecordSep;
+use regex::Regex;
+use report::StatsArgs;
+use tee::message;
+use verify::{ExtractorKind, Verification};
+use watchdog::Limits;
+
+mod archive;
+mod backend;
+mod bench;
+mod checkpoint;
+mod clipboard;
+#[cfg(feature = "parquet")]
+mod columnar;
+mod completions;
+mod config;
+#[cfg(feature = "tui")]
+mod dashboard;
+mod decompress;
+mod dict_check;
+mod explain;
+mod follow;
+mod generate;
+mod group;
+mod highlight;
+mod history;
+mod listen;
+mod loadtest;
+mod metrics;
+mod mmap;
+mod output;
+mod process;
+mod records;
+mod rejects;
+mod remote;
+mod repl;
+mod report;
+mod response;
+mod rewrite;
+mod rpc;
+#[cfg(feature = "s3")]
+mod s3;
+mod scan;
+mod serve;
+mod signals;
+mod sink;
+mod spans;
+mod tee;
+#[cfg(test)]
+mod testing;
+mod transcode;
+#[cfg(target_os = "linux")]
+mod uring;
+mod vectors;
+mod verify;
+mod watch;
+mod watchdog;
+mod websocket;
+
+/// The commands processing lines, the options of the processing are given after them too (see [`config::command`]),
+/// before the other commands only
+const PROCESSING_COMMANDS: [&str; 7] = ["sum", "scan", "stats", "serve", "listen", "repl", "dict-check"];
+
+/// Exit code of a file abandoned by the watchdog
+const EXIT_ABORTED: i32 = 3;
+/// Exit code of a file whose total amount doesn't fit the accumulator
+const EXIT_OVERFLOW: i32 = 4;
+
+#[derive(Parser, Debug, Clone)]
+#[allow(clippy::struct_excessive_bools)]
+#[command(author, version, about, long_about = None, subcommand_negates_reqs = true)]
 struct Args {
+    /// The input file, an `http://` / `https://` URL, an `s3://bucket/key` object or `-` for stdin
+    #[arg(short, long)]
+    file: Option<String>,
+
+    /// Read the text of the system clipboard instead of a file, e.g. a puzzle input copied from the browser
+    #[arg(
+        long,
+        conflicts_with_all = ["file", "column", "entries", "follow", "watch", "mmap", "checkpoint", "self_check"]
+    )]
+    clipboard: bool,
+
+    /// Name of the scanned string column of a Parquet file
+    #[arg(long, required_if_eq("input_format", "parquet"))]
+    column: Option<String>,
+
+    /// Only process the files of an archive (`.tar`, `.tar.gz`, `.zip`, ...) with the names matching this expression
+    #[arg(long, value_parser = Regex::new)]
+    entries: Option<Regex>,
+
+    /// How the lines are read, `ndjson` scans the `--field` of every JSON record, `parquet` the cells of the `--column`
+    #[arg(long, value_enum, default_value_t)]
+    input_format: InputFormat,
+
+    /// Dotted path of the scanned string field of the JSON records, e.g. `payload.text` or `items.0`
+    #[arg(long, required_if_eq("input_format", "ndjson"))]
+    field: Option<String>,
+
+    /// Count any Unicode decimal digit (e.g. Arabic-Indic or Devanagari), not only ASCII 0-9
+    #[arg(long)]
+    unicode_digits: bool,
+
+    /// Count fullwidth digits `０-９` of CJK documents
+    #[arg(long)]
+    fullwidth_digits: bool,
+
+    /// Match keycap emoji like 1️⃣ as whole digit graphemes
+    #[arg(long)]
+    keycap_digits: bool,
+
+    /// Only count digits which are not adjacent to other digits, multi-digit tokens like `12` are skipped
+    #[arg(long)]
+    isolated_digits_only: bool,
+
+    /// Tolerate up to this amount of substitutions, insertions or deletions in spelled digits (`thre`, `sevcn`)
+    #[arg(long, default_value_t = 0, value_parser = clap::value_parser!(u8).range(0..=2))]
+    fuzzy: u8,
+
+    /// Match spelled digits only between non-letters, e.g. `three` isn't a digit of `threefold`, for prose
+    #[arg(long)]
+    word_boundaries: bool,
+
+    /// End the records by this separator instead of newlines: a literal with the escapes `\0`, `\n`, `\r`, `\t`
+    /// and `\\`, or a regular expression between slashes, e.g. `'/\n\s*\n/'` for blank-line paragraphs
+    #[arg(long, value_parser = RecordSep::parse, conflicts_with_all = ["mmap", "follow", "watch", "column"])]
+    record_sep: Option<RecordSep>,
+
+    /// Decode the input from this encoding, e.g. `latin1`, `utf-16le` or `shift_jis`, instead of reading it as UTF-8
+    #[arg(long, value_parser = transcode::parse_encoding, conflicts_with_all = ["mmap", "follow", "watch", "column"])]
+    encoding: Option<&'static Encoding>,
+
+    /// The records are NUL-separated, e.g. of `find -print0`, the same as `--record-sep '\0'`
+    #[arg(short = 'z', long, conflicts_with_all = ["record_sep", "mmap", "follow", "watch", "column"])]
+    null_data: bool,
+
+    /// Whether the spelled digits may share letters: `allow` reads `eightwo` as 8 and 2, `consume` as 8 only
+    #[arg(long, value_enum, default_value_t)]
+    overlap: Overlap,
+
+    /// Spelled digits: one `<word> <digit>` pair per line, English `one`..`nine` by default
+    #[arg(long)]
+    dictionary: Option<PathBuf>,
+
+    /// Replace common confusables (`O` → `0`, `l`/`I` → `1`, Cyrillic look-alikes, ...) before matching
+    #[arg(long)]
+    confusables: bool,
+
+    /// Replace confusables by the mapping table: one `<from> <to>` pair of characters per line
+    #[arg(long)]
+    confusables_file: Option<PathBuf>,
+
+    /// Only process the lines matching this regular expression, the other ones are counted as excluded
+    #[arg(long, value_parser = Regex::new)]
+    include_regex: Option<Regex>,
+
+    /// Don't process the lines matching this regular expression, they're counted as excluded
+    #[arg(long, value_parser = Regex::new)]
+    exclude_regex: Option<Regex>,
+
+    /// Leave out the comment lines beginning with this prefix, e.g. `#`, leading whitespace is ignored
+    #[arg(long)]
+    skip_comments: Option<String>,
+
+    /// Don't count the empty lines as incorrect, e.g. trailing newlines and blank separators
+    #[arg(long)]
+    ignore_empty: bool,
+
+    /// Sum the values of the lines per key besides the grand total: the first capture of a regular expression
+    /// between slashes, e.g. `'/device=(\w+)/'`, or a column number of the comma-separated fields
+    #[arg(long, value_parser = GroupBy::parse, conflicts_with = "checkpoint")]
+    group_by: Option<GroupBy>,
+
+    /// Ignore this amount of the first lines, e.g. headers
+    #[arg(long, default_value_t = 0)]
+    skip: usize,
+
+    /// Stop after this amount of the lines following the skipped ones
+    #[arg(long)]
+    limit: Option<usize>,
+
+    /// Process a random subset of the lines of this rate, e.g. `0.01`, and estimate the total amount
+    #[arg(long, value_parser = parse_rate)]
+    sample: Option<f64>,
+
+    /// Process every line of this stride only, starting from the first one, and estimate the total amount
+    #[arg(long, conflicts_with = "sample")]
+    every: Option<NonZeroUsize>,
+
+    /// Seed of the sampling, the same seed chooses the same lines
+    #[arg(long, default_value_t = 0, requires = "sample")]
+    seed: u64,
+
+    /// Only process the lines with these numbers, e.g. `1000:2000` (the end is exclusive), `:500` or `1000:`
+    #[arg(long, value_parser = parse_line_range, conflicts_with_all = ["skip", "limit"])]
+    lines: Option<Range<usize>>,
+
+    /// Parse the repeated lines once, faster on highly repetitive machine-generated inputs
+    #[arg(long)]
+    dedup: bool,
+
+    /// Cross-check the forward and the reverse scans on every line, and with a naive scan when the digits are
+    /// the ASCII ones and the exact words, and report any violated invariant
+    #[arg(long)]
+    paranoid: bool,
+
+    /// Compare the values of every line with the ones of a slow regex-based reference extractor and report
+    /// the lines they disagree on, `fail` exits with an error after the report then
+    #[arg(long, value_enum, num_args = 0..=1, require_equals = true, default_missing_value = "report")]
+    verify: Option<Verification>,
+
+    /// The engine extracting the values of the lines, `regex` is the reference extractor of `--verify`
+    #[arg(long, value_enum, default_value_t)]
+    extractor: ExtractorKind,
+
+    /// Process the file again sequentially after the parallel processing and fail when the totals or the value
+    /// of any line differ, e.g. at the boundaries of the batches or of the mapped chunks
+    #[arg(long, conflicts_with_all = ["follow", "resume", "sink"])]
+    self_check: bool,
+
+    /// Abort the file when it isn't processed within this time, e.g. `30m`
+    #[arg(long, value_parser = parse_duration)]
+    file_timeout: Option<Duration>,
+
+    /// Abort the file when nothing is read for this period, e.g. a hung network mount
+    #[arg(long, value_parser = parse_duration)]
+    stall_timeout: Option<Duration>,
+
+    /// Amount of the worker threads processing the lines, the amount of logical CPUs by default
+    #[arg(long)]
+    threads: Option<NonZeroUsize>,
+
+    /// Capacity of the read buffer, e.g. `1M` for fast disks and long lines
+    #[arg(long, default_value = "8K", value_parser = parse_size)]
+    buffer_size: usize,
+
+    /// Scan the lines longer than this while they're read instead of buffering them, e.g. `64M` against a line
+    /// of gigabytes; their values are counted by the totals only
+    #[arg(
+        long,
+        value_parser = parse_size,
+        conflicts_with_all = ["record_sep", "null_data", "mmap", "follow", "watch", "column", "self_check", "sink"]
+    )]
+    max_line_bytes: Option<usize>,
+
+    /// How the local file is read, `tokio` reads it on a tokio runtime, `uring` reads ahead on Linux
+    #[arg(long, value_enum, default_value_t)]
+    io_backend: IoBackend,
+
+    /// Reads kept in flight ahead of the consumed one by `--io-backend uring`, see `benches/uring.rs`
+    #[arg(long, default_value_t = 4, value_parser = clap::value_parser!(u32).range(1..=4096))]
+    uring_depth: u32,
+
+    /// Size of a single read of `--io-backend uring`, e.g. `1M` for a device with large blocks
+    #[arg(long, default_value = "256K", value_parser = parse_block_size)]
+    uring_block_size: u32,
+
+    /// Keep the file open and process the lines appended to it, like `tail -f`
+    #[arg(long, conflicts_with = "mmap")]
+    follow: bool,
+
+    /// Process the file again whenever it changes, e.g. while editing it
+    #[arg(long, conflicts_with = "follow")]
+    watch: bool,
+
+    /// Memory map the file and process its newline-aligned chunks in parallel
+    #[arg(long)]
+    mmap: bool,
+
+    /// What happens when the total amount exceeds 64 bits
+    #[arg(long, value_enum, default_value_t)]
+    overflow: Overflow,
+
+    /// What the first and the last digits are of: every line has a value, or the whole input has one
+    #[arg(long, value_enum, default_value_t)]
+    scope: Scope,
+
+    /// What a line without digits is: `skip` counts it as incorrect, `zero` gives it the value 0, `error` aborts
+    #[arg(long, value_enum, default_value_t)]
+    on_no_digits: NoDigits,
+
+    /// Save the position and the totals to this file with every progress report, e.g. to `--resume` after a crash
+    #[arg(long, conflicts_with_all = ["mmap", "follow", "watch", "column", "scope"])]
+    checkpoint: Option<PathBuf>,
+
+    /// Continue from the position saved in the `--checkpoint` file, from the beginning when there's none yet
+    #[arg(long, requires = "checkpoint")]
+    resume: bool,
+
+    /// Send the throughput and error-rate gauges to the `StatsD` daemon at `host:port` with every report
+    #[arg(long)]
+    statsd: Option<String>,
+
+    /// Send the throughput and error-rate gauges to the OTLP/HTTP collector, e.g. `http://localhost:4318`
+    #[arg(long)]
+    otlp_endpoint: Option<String>,
+
+    /// Write the report (or the lines of `scan`) to this file instead of stdout,
+    /// the file is replaced only once the output is complete
     #[arg(short, long)]
-    file: String,
+    output: Option<PathBuf>,
+
+    /// Write every line without a value to this file as `<line number>\t<reason>\t<line>`, the reasons are
+    /// `empty`, `no_digits`, `invalid_record` and `invalid_utf8`
+    #[arg(long, conflicts_with = "self_check")]
+    rejects: Option<PathBuf>,
+
+    /// Insert a row per line (number, value, matched tokens) and a row of the totals into `sqlite://results.db`,
+    /// with the `sqlite` feature
+    #[arg(long)]
+    sink: Option<String>,
+
+    /// Copy the raw input to stdout unchanged, the report goes to stderr (or to the `--output` file)
+    #[arg(long, conflicts_with_all = ["mmap", "follow", "watch", "column"])]
+    tee: bool,
+
+    /// Show a live dashboard of the throughput, the totals, the values and the rejected lines while the file is
+    /// processed, with the `tui` feature
+    #[arg(long, conflicts_with_all = ["tee", "mmap"])]
+    tui: bool,
+
+    /// Record the summary of every completed input into the history database: the processed file, a followed file
+    /// when it's stopped, a connection of `listen` or `serve` with some lines
+    #[arg(long)]
+    record_history: bool,
+
+    /// Config file of the default options, `find_digits.toml` of the current directory by default
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// The `[profile.<name>]` table of the config file overriding its options, e.g. `aoc` or `logs`
+    #[arg(long)]
+    profile: Option<String>,
+
+    /// History database location, by default `$XDG_DATA_HOME/find_digits/history.db`
+    #[arg(long, global = true)]
+    history_file: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
 }
 
-fn main() {
-    let args = Args::parse();
+/// Layout of the input lines
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum InputFormat {
+    /// every line is scanned
+    #[default]
+    Text,
+    /// every line is a JSON record, its field is scanned
+    Ndjson,
+    /// every cell of the `--column` of a Parquet file is a line, with the `parquet` feature
+    Parquet,
+}
 
-    let reader = match File::open(&args.file) {
-        Ok(f) => BufReader::new(f),
-        Err(e) => {
-            println!("Fail to open file {}: {e:?}", &args.file);
-            exit(1);
-        },
-    };
+/// Policy of the total amount exceeding the accumulator
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Overflow {
+    /// abort with the line number where the total overflowed
+    #[default]
+    Checked,
+    /// stop at the largest total
+    Saturating,
+    /// keep the total modulo 2^64
+    Wrapping,
+}
 
-    let mut timestamp = Instant::now();
-    let start_timestamp = Instant::now();
+/// What the first and the last digits are searched in
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Scope {
+    /// the total amount of the values of the lines
+    #[default]
+    Line,
+    /// a single value of the first digit of the input and its last digit, e.g. of an unstructured blob
+    File,
+}
 
-    let mut total_sum = 0_u32;
-    let mut parsed_lines = 0_u32;
-    let mut incorrect_lines = 0_u32;
+/// Policy of the lines without digits
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum NoDigits {
+    /// count them among the incorrect lines
+    #[default]
+    Skip,
+    /// add them with the value 0
+    Zero,
+    /// abort with the number of the first one found
+    Error,
+}
 
-    for (number, read_result) in reader.lines().enumerate() {
-        parsed_lines += 1;
+#[derive(Subcommand, Debug, Clone)]
+enum Command {
+    /// Sum the values of the lines of the file, the same as no command
+    Sum,
+    /// Print the lines of the file: highlighted, explained, their spans or only the matching ones
+    Scan(scan::ScanArgs),
+    /// Sum the values of the lines of the file and report their distribution, frequencies and extremes
+    Stats(report::StatsArgs),
+    /// Process the lines received on `host:port` or `unix:/path/to/socket` and keep the totals of every connection
+    Listen(listen::ListenArgs),
+    /// Generate calibration lines and drive a running server, reporting throughput and error rate
+    Loadtest(loadtest::LoadtestArgs),
+    /// Run the extraction engines over a generated or given corpus, reporting lines/s and MB/s of every engine
+    Bench(bench::BenchArgs),
+    /// Write generated calibration lines (digits, spelled digits, overlapping words and noise) and their totals
+    Generate(generate::GenerateArgs),
+    /// Show the summaries of previously processed files
+    History(history::HistoryArgs),
+    /// Copy the file with its spelled digits replaced by their numerals, everything else is kept byte for byte
+    Rewrite(rewrite::RewriteArgs),
+    /// Serve `POST /extract` returning the values of the posted lines, `GET /stats` with the totals of all of them
+    /// and the WebSocket endpoint `/ws` streaming the values of the lines of the messages
+    Serve(serve::ServeArgs),
+    /// Check the dictionary of `--dictionary` (the built-in one without it) for words the matcher can't resolve
+    DictCheck,
+    /// Type or paste lines and see their matched tokens, spans and values at once, `:reload` reads the dictionary
+    /// and the options again
+    Repl,
+    /// Export canonical test vectors (lines, values and match spans for every option preset) as JSON
+    Vectors(vectors::VectorsArgs),
+    /// Print the completion script of bash, zsh, fish, powershell or elvish
+    Completions(completions::CompletionsArgs),
+}
 
-        let line = match read_result {
-            Ok(l) => l,
-            Err(e) => {
-                println!("File {} broken line(number {number}): {e:?}", &args.file);
-                continue;
-            },
-        };
+impl Args {
+    /// The input file, [`clipboard::NAME`] of the clipboard
+    fn input(&self) -> Option<&str> {
+        self.file.as_deref().or(self.clipboard.then_some(clipboard::NAME))
+    }
 
-        if line.is_empty() {
-            incorrect_lines += 1;
-            continue;
+    /// What ends the records, `None` of the newline-terminated lines
+    fn record_sep(&self) -> Option<RecordSep> {
+        if self.null_data {
+            return Some(RecordSep::Literal(vec![0]));
         }
+        self.record_sep.clone()
+    }
 
-        match extract_number(&line) {
-            Some(amount) => {
-                total_sum += amount;
-            },
-            None => {
-                incorrect_lines += 1;
-            },
+    /// How the lines are printed by `scan`, `None` of the other commands
+    fn scan(&self) -> Option<&scan::ScanArgs> {
+        match &self.command {
+            Some(Command::Scan(scan_args)) => Some(scan_args),
+            _ => None,
         }
+    }
 
-        if timestamp.elapsed() > REPORT_DELAY {
-            timestamp = Instant::now();
-            println!(
-                "{:?} Parsed lines: {parsed_lines}, Incorrect lines {incorrect_lines}, Total amount: {total_sum}",
-                SystemTime::now().duration_since(UNIX_EPOCH)
-            );
+    /// The reports of `stats`, none of the other commands
+    fn stats(&self) -> StatsArgs {
+        match &self.command {
+            Some(Command::Stats(stats_args)) => stats_args.clone(),
+            _ => StatsArgs::default(),
         }
     }
+}
 
-    println!(
-        "{:?} Parsed lines: {parsed_lines}, Incorrect lines {incorrect_lines}, Total amount: {total_sum}, Elapsed {:?}",
-        SystemTime::now().duration_since(UNIX_EPOCH),
-        start_timestamp.elapsed()
-    );
+fn main() {
+    let args = config::parse();
+    signals::init();
+
+    match &args.command {
+        Some(Command::Loadtest(loadtest_args)) => {
+            if let Err(e) = loadtest::run(loadtest_args) {
+                println!("Load test failed: {e}");
+                exit(1);
+            }
+        },
+        Some(Command::Bench(bench_args)) => {
+            let result = process::scan_options(&args)
+                .and_then(|options| Ok((options, Processing::new(&args)?)))
+                .and_then(|(options, processing)| bench::run(bench_args, &options, &processing));
+            if let Err(e) = result {
+                println!("{e}");
+                exit(1);
+            }
+        },
+        Some(Command::Generate(generate_args)) => {
+            if let Err(e) = generate::run(generate_args) {
+                println!("{e}");
+                exit(1);
+            }
+        },
+        Some(Command::History(history_args)) => {
+            let result =
+                history::location(args.history_file.as_deref()).and_then(|path| history::run(history_args, &path));
+            if let Err(e) = result {
+                println!("{e}");
+                exit(1);
+            }
+        },
+        Some(Command::Rewrite(rewrite_args)) => {
+            if let Err(e) = process::scan_options(&args).and_then(|options| rewrite::run(rewrite_args, &options)) {
+                println!("{e}");
+                exit(1);
+            }
+        },
+        Some(Command::Serve(serve_args)) => {
+            if let Err(e) = Processing::new(&args).and_then(|processing| serve::run(serve_args, processing)) {
+                println!("{e}");
+                exit(1);
+            }
+        },
+        Some(Command::DictCheck) => {
+            let name = args
+                .dictionary
+                .as_ref()
+                .map_or_else(|| "built-in".to_owned(), |path| path.display().to_string());
+            if let Err(e) = process::scan_options(&args).and_then(|options| dict_check::run(&options, &name)) {
+                println!("{e}");
+                exit(1);
+            }
+        },
+        Some(Command::Repl) => {
+            if let Err(e) = repl::run(&args) {
+                println!("{e}");
+                exit(1);
+            }
+        },
+        Some(Command::Vectors(vectors_args)) => {
+            if let Err(e) = vectors::run(vectors_args) {
+                println!("{e}");
+                exit(1);
+            }
+        },
+        Some(Command::Completions(completions_args)) => completions::run(completions_args),
+        Some(Command::Listen(listen_args)) => {
+            let address = &listen_args.address;
+            if let Err(e) = Processing::new(&args).and_then(|processing| listen::run(address, processing)) {
+                println!("{e}");
+                exit(1);
+            }
+        },
+        None | Some(Command::Sum | Command::Scan(_) | Command::Stats(_)) => {
+            let Some(file) = args.input() else {
+                config::command()
+                    .error(ErrorKind::MissingRequiredArgument, "the input --file or --clipboard is required")
+                    .exit();
+            };
+
+            signals::trap_interrupt();
+            if args.tee {
+                tee::redirect_messages();
+            }
+            if !args.watch {
+                run_sum(&args, file);
+            } else if let Err(e) = watch::run(file, || run_sum(&args, file)) {
+                println!("{e}");
+                exit(1);
+            } else {
+                exit(signals::EXIT_INTERRUPTED);
+            }
+        },
+    }
+}
 
-    println!("\nTotal amount: {total_sum}");
+fn parse_block_size(value: &str) -> Result<u32, String> {
+    match parse_size(value).map(u32::try_from) {
+        Ok(Ok(size)) if size > 0 => Ok(size),
+        Ok(_) => Err(format!("block size {value} is not within 1-4G")),
+        Err(e) => Err(e),
+    }
 }
 
-fn extract_number(line: &str) -> Option<u32> {
-    let res = find(line);
-    let r_res = r_find(line, res.last_parsed_position);
+fn parse_rate(value: &str) -> Result<f64, String> {
+    match value.trim().parse() {
+        Ok(rate) if rate > 0.0 && rate <= 1.0 => Ok(rate),
+        _ => Err(format!("rate {value} is not a number in (0, 1]")),
+    }
+}
 
-    let (Some(fst), Some(lst)) = (res.number, r_res.number) else {
-        return None;
+fn run_sum(args: &Args, file: &str) {
+    let processing = match Processing::new(args) {
+        Ok(processing) => Arc::new(processing),
+        Err(e) => {
+            message!("{e}");
+            exit(1);
+        },
     };
 
-    format!("{fst}{lst}").parse().ok()
-}
+    if let Err(e) = check_input(args, file) {
+        message!("{e}");
+        exit(1);
+    }
 
-#[derive(Debug, PartialEq, Eq)]
-struct SearchResult {
-    number: Option<char>,
-    last_parsed_position: usize,
-}
+    if args.follow {
+        process::reload_on_hangup(&processing);
+    }
 
-fn find(line: &str) -> SearchResult {
-    let line_length = line.len();
-    let mut pos = 0;
+    let limits = Limits {
+        timeout: args.file_timeout,
+        // nothing is read from a mapped file, the progress can't be watched, a followed file is idle normally
+        stall_timeout: args.stall_timeout.filter(|_| !args.mmap && !args.follow),
+    };
+    let progress = ByteCounter::default();
+
+    let job = {
+        let (file, processing, progress) = (file.to_owned(), processing.clone(), progress.clone());
+        let (mapped, io_backend, entries) = (args.mmap, args.io_backend, args.entries.clone());
+        let archive = Archive::of_file(&file);
+        let (follow, stop, clipboard) = (args.follow, signals::interrupt_flag(), args.clipboard);
+        #[cfg(feature = "parquet")]
+        let column = args
+            .column
+            .clone()
+            .filter(|_| args.input_format == InputFormat::Parquet);
+        move || match (archive, io_backend) {
+            #[cfg(feature = "parquet")]
+            _ if column.is_some() => {
+                columnar::sum_parquet(&file, column.as_deref().unwrap_or_default(), &processing, &progress)
+            },
+            _ if clipboard => clipboard::sum_clipboard(&processing, &progress),
+            _ if follow => follow::sum_follow(&file, &processing, &progress, &stop),
+            (Some(archive), _) => archive::sum_archive(&file, archive, &processing, entries.as_ref(), &progress),
+            _ if remote::is_url(&file) => remote::sum_url(&file, &processing, &progress),
+            _ if remote::is_s3(&file) => remote::sum_s3(&file, &processing, &progress),
+            _ if mapped => mmap::sum_mapped(&file, &processing),
+            (None, IoBackend::Std) => sum_file(&file, &processing, &progress),
+            (None, IoBackend::Tokio) => backend::sum_tokio(&file, &processing, &progress),
+            (None, IoBackend::Uring) => backend::sum_uring(&file, &processing, &progress),
+        }
+    };
+
+    #[cfg(feature = "tui")]
+    let screen = match processing
+        .dashboard
+        .clone()
+        .map(|board| dashboard::show(board, file, progress.clone()))
+    {
+        Some(Err(e)) => {
+            message!("{e}");
+            exit(1);
+        },
+        screen => screen.and_then(Result::ok),
+    };
 
-    let mut character = line.chars();
+    let start_timestamp = Instant::now();
+    let result = if limits.is_unlimited() {
+        Ok(job())
+    } else {
+        watchdog::guard(limits, &progress, job)
+    };
+    let elapsed = start_timestamp.elapsed();
 
-    while line_length > pos {
-        if let Some(c) = character.next() {
-            if c.is_ascii_digit() {
-                return SearchResult {
-                    number: Some(c),
-                    last_parsed_position: pos,
-                };
-            }
-        }
+    #[cfg(feature = "tui")]
+    if let Some(Err(e)) = screen.map(dashboard::Screen::close) {
+        message!("{e}");
+    }
+    let mut summary = match result {
+        Ok(summary) => summary,
+        Err(e) => {
+            message!("Error: file {file} {e}");
+            exit(EXIT_ABORTED);
+        },
+    };
 
-        let rest = line_length - pos;
-        if rest < LETTERS_DIGIT_MIN_LEN {
-            pos += 1;
-            continue;
+    exit_unfinished(args, file, &processing, &summary, &progress, elapsed);
+
+    if args.self_check {
+        if let Err(e) = process::self_check(file, &processing, &summary) {
+            message!("Error: {e}");
+            exit(1);
         }
+        message!("Self-check of file {file} passed, the sequential processing gives the same values");
+    }
 
-        for i in LETTERS_DIGITS {
-            if line[pos..pos + LETTERS_DIGIT_MIN_LEN] == *i.0 {
-                let length = i.1.len();
-                if rest >= length && line[pos..pos + length] == *i.1 {
-                    return SearchResult {
-                        number: Some(i.2),
-                        last_parsed_position: pos,
-                    };
+    if summary.overflowed {
+        match args.overflow {
+            Overflow::Checked => {
+                // the lines are processed in parallel, the overflowing line is found by another sequential pass
+                match process::locate_overflow(file, &processing, processing.summary()) {
+                    Some(number) => {
+                        message!("Error: file {file} total amount overflowed 64 bits at line number {number}");
+                    },
+                    None => message!("Error: file {file} total amount overflowed 64 bits"),
                 }
-
-                // it's correct while all beginnings of digits are unique
-                break;
-            }
+                exit(EXIT_OVERFLOW);
+            },
+            Overflow::Saturating => summary.total_sum = u64::MAX,
+            Overflow::Wrapping => {},
         }
+    }
+    // the entries of an archive are recorded while they're processed, any other input is a single one
+    if processing.files().is_empty() {
+        processing.add_file(file, &summary, elapsed);
+    }
 
-        pos += 1;
+    if let Some(metrics) = &processing.metrics {
+        metrics.export(file, &summary);
     }
 
-    SearchResult {
-        number: None,
-        last_parsed_position: line_length,
+    // the lines are the output of `scan`, there's no report
+    let scan = args.scan().is_some();
+    if !scan {
+        processing.emit(&render_report(args, &processing, &summary, elapsed));
+    }
+    if let Err(e) = processing.commit_output() {
+        message!("{e}");
+        exit(1);
+    }
+    if let Some(Err(e)) = processing
+        .sink
+        .as_ref()
+        .map(|sink| sink.finish(&summary, &processing.files(), elapsed))
+    {
+        message!("{e}");
+        exit(1);
     }
+    if args.verify == Some(Verification::Fail) && processing.disagreements() > 0 {
+        message!("{} lines disagree with the reference extractor", processing.disagreements());
+        exit(1);
+    }
+    processing.record_history(file, &summary, elapsed);
 }
 
-fn r_find(line: &str, found_pos: usize) -> SearchResult {
-    let line_length = line.len();
-    let mut pos = line_length;
-
-    let mut character = line.chars().rev();
+/// Exit when a line failed the run or it was interrupted, the rejected lines so far are written
+fn exit_unfinished(
+    args: &Args,
+    file: &str,
+    processing: &Processing,
+    summary: &Summary,
+    progress: &ByteCounter,
+    elapsed: Duration,
+) {
+    let (failure, interrupted) = (processing.failure(), signals::interrupted());
+    if failure.is_none() && !interrupted {
+        return;
+    }
+    if let Err(e) = processing.flush_rejects() {
+        message!("{e}");
+    }
 
-    while pos > found_pos {
-        if let Some(c) = character.next() {
-            if c.is_ascii_digit() {
-                return SearchResult {
-                    number: Some(c),
-                    last_parsed_position: pos,
-                };
-            }
-        }
+    if let Some(e) = failure {
+        message!("Error: {e}");
+        exit(1);
+    }
 
-        if pos < LETTERS_DIGIT_MIN_LEN {
-            pos -= 1;
-            continue;
-        }
+    // the chunks of a mapped file are processed in parallel, there's no single offset
+    let offset = if args.mmap {
+        String::new()
+    } else {
+        format!(" after reading {} bytes", progress.get())
+    };
+    message!("Interrupted{offset}, partial {summary}, Elapsed {elapsed:?}");
+    // the stop is the end of a followed file
+    if args.follow {
+        processing.record_history(file, summary, elapsed);
+    }
+    if let Some(path) = &args.checkpoint {
+        message!("The run continues with --resume from checkpoint {}", path.display());
+    }
+    exit(signals::EXIT_INTERRUPTED);
+}
 
-        for i in LETTERS_DIGITS_REV {
-            if line[pos - LETTERS_DIGIT_MIN_LEN..pos] == *i.0 {
-                let length = i.1.len();
-                if pos >= length && line[pos - length..pos] == *i.1 {
-                    return SearchResult {
-                        number: Some(i.2),
-                        last_parsed_position: pos - length,
-                    };
-                }
+/// `Err` of the options the input can't be processed with
+fn check_input(args: &Args, file: &str) -> Result<(), String> {
+    #[cfg(not(feature = "parquet"))]
+    if args.input_format == InputFormat::Parquet {
+        return Err("Parquet input isn't supported by this build, rebuild it with `--features parquet`".to_owned());
+    }
 
-                // it's correct while all endings of digits are unique
-                break;
-            }
-        }
+    #[cfg(not(feature = "tui"))]
+    if args.tui {
+        return Err("The dashboard isn't supported by this build, rebuild it with `--features tui`".to_owned());
+    }
+    if args.tui && args.scan().is_some() {
+        return Err("The dashboard is shown by the reports only, `scan` prints the lines meanwhile".to_owned());
+    }
 
-        pos -= 1;
+    // the other readers don't report their positions, the entries of an archive would be resumed one by one
+    if args.checkpoint.is_some() && (Archive::of_file(file).is_some() || args.io_backend == IoBackend::Tokio) {
+        return Err("Checkpoints are supported by the std and uring readers of a single input only".to_owned());
+    }
+    if args.tee && (Archive::of_file(file).is_some() || args.io_backend == IoBackend::Tokio) {
+        return Err("The input is copied by the std and uring readers of a single input only".to_owned());
     }
 
-    SearchResult {
-        number: None,
-        last_parsed_position: pos,
+    // the async readers read lines
+    if args.record_sep().is_some() && args.io_backend != IoBackend::Std {
+        return Err("Custom record separators are supported by the std reader only".to_owned());
+    }
+    if args.encoding.is_some() && args.io_backend != IoBackend::Std {
+        return Err("Other encodings than UTF-8 are supported by the std reader only".to_owned());
+    }
+    // the tokio reader reads the raw bytes of a local file, the uring one reads a file at offsets
+    if file == "-" && args.io_backend != IoBackend::Std {
+        return Err("The standard input is read by the std reader only".to_owned());
+    }
+    if Archive::of_file(file).is_some() && args.io_backend != IoBackend::Std {
+        return Err(format!("The entries of archive {file} are read by the std reader only"));
+    }
+    // the followed, mapped, remote, clipboard and parquet inputs are read by their own readers
+    if args.io_backend != IoBackend::Std
+        && (args.follow
+            || args.mmap
+            || args.clipboard
+            || remote::is_url(file)
+            || remote::is_s3(file)
+            || args.input_format == InputFormat::Parquet)
+    {
+        return Err("The tokio and uring readers read a local file only".to_owned());
+    }
+    if args.io_backend == IoBackend::Tokio
+        && Compression::of_file(file).is_ok_and(|compression| compression != Compression::None)
+    {
+        return Err(format!("Compressed file {file} is read by the std and uring readers only"));
     }
-}
 
-#[cfg(test)]
-mod test {
-    use super::*;
-
-    #[test]
-    fn test_find() {
-        let strings = [
-            "eightwothree",
-            "abcone2threexyz",
-            "treb7uchet",
-            "7pqrstsixteen",
-            "abcdefg",
-        ];
-        let expected = [
-            SearchResult {
-                number: Some('8'),
-                last_parsed_position: 0,
-            },
-            SearchResult {
-                number: Some('1'),
-                last_parsed_position: 3,
-            },
-            SearchResult {
-                number: Some('7'),
-                last_parsed_position: 4,
-            },
-            SearchResult {
-                number: Some('7'),
-                last_parsed_position: 0,
-            },
-            SearchResult {
-                number: None,
-                last_parsed_position: 7,
-            },
+    // the long lines aren't kept, only the matching options apply to them
+    if args.max_line_bytes.is_some() {
+        let unsupported = [
+            (args.fuzzy > 0, "--fuzzy"),
+            (args.confusables || args.confusables_file.is_some(), "the confusables"),
+            (
+                args.include_regex.is_some() || args.exclude_regex.is_some() || args.skip_comments.is_some(),
+                "the line filters",
+            ),
+            (args.input_format != InputFormat::Text, "other input formats than text"),
+            (args.extractor != ExtractorKind::Scanner || args.verify.is_some(), "other extractors"),
+            (args.group_by.is_some(), "--group-by"),
+            (args.scan().is_some(), "`scan`"),
+            (args.io_backend != IoBackend::Std, "other readers than std"),
         ];
-
-        for (pos, line) in strings.into_iter().enumerate() {
-            let res = find(line);
-
-            assert_eq!(res, expected[pos]);
+        if let Some((_, what)) = unsupported.iter().find(|(used, _)| *used) {
+            return Err(format!(
+                "--max-line-bytes can't be used with {what}, the long lines are scanned by the matching options only"
+            ));
         }
     }
 
-    #[test]
-    fn test_r_find() {
-        let strings = [
-            ("eightwothree", 0),
-            ("abcone2threexyz", 3),
-            ("treb7uchet", 4),
-            ("7pqrstsixteen", 0),
-            ("abcdefg", 7),
-            ("abcdefg", 0),
-        ];
-        let expected = [
-            SearchResult {
-                number: Some('3'),
-                last_parsed_position: 7,
-            },
-            SearchResult {
-                number: Some('3'),
-                last_parsed_position: 7,
-            },
-            SearchResult {
-                number: Some('7'),
-                last_parsed_position: 5,
-            },
-            SearchResult {
-                number: Some('6'),
-                last_parsed_position: 6,
-            },
-            SearchResult {
-                number: None,
-                // because 7 it's the smallest number by condition in previous array
-                last_parsed_position: 7,
-            },
-            SearchResult {
-                number: None,
-                last_parsed_position: 0,
-            },
-        ];
-
-        for (pos, (line, found_pos)) in strings.into_iter().enumerate() {
-            let res = r_find(line, found_pos);
-
-            assert_eq!(res, expected[pos]);
-        }
+    // the sequential pass reads the local file again, the lines of `scan` would be printed twice
+    if args.self_check
+        && (file == "-"
+            || Archive::of_file(file).is_some()
+            || remote::is_url(file)
+            || remote::is_s3(file)
+            || args.input_format == InputFormat::Parquet
+            || args.scan().is_some())
+    {
+        return Err("The self-check is supported by the reports of a single local text file only".to_owned());
     }
 
-    #[test]
-    fn test_extract_number() {
-        let strings = [
-            "eightwothree",
-            "abcone2threexyz",
-            "treb7uchet",
-            "7pqrstsixteen",
-            "abcdefg",
-        ];
-        let expected = [Some(83), Some(13), Some(77), Some(76), None];
+    Ok(())
+}
 
-        for (pos, line) in strings.into_iter().enumerate() {
-            let result = extract_number(line);
-            assert_eq!(result, expected[pos])
-        }
+/// The summary with the requested reports, as printed after the processing
+fn render_report(args: &Args, processing: &Processing, summary: &Summary, elapsed: Duration) -> String {
+    let reports = args.stats();
+    let mut text = String::new();
+    let _ = writeln!(text, "{:?} {summary}, Elapsed {elapsed:?}", SystemTime::now().duration_since(UNIX_EPOCH));
+    if args.lines.is_some() {
+        let start = processing.lines.start;
+        let _ = writeln!(text, "Line range: {start}..{}", start as u64 + summary.parsed_lines);
+    }
+    if let Some((rate, estimate)) = summary.sample_estimate() {
+        let _ = writeln!(
+            text,
+            "Sampled lines: {} of {} (rate {rate:.4}), estimated total amount: {estimate:.0}",
+            summary.parsed_lines - summary.unsampled_lines,
+            summary.parsed_lines
+        );
+    }
+    if summary.excluded_lines != 0 {
+        let _ = writeln!(text, "Excluded lines: {}", summary.excluded_lines);
+    }
+    if summary.empty_lines != 0 {
+        let among = if args.ignore_empty {
+            "not incorrect"
+        } else {
+            "among the incorrect ones"
+        };
+        let _ = writeln!(text, "Empty lines: {} ({among})", summary.empty_lines);
+    }
+    if summary.input_bytes.is_encoded() {
+        let _ = writeln!(text, "Input size: {}", summary.input_bytes);
+    }
+    let _ = writeln!(text, "Line values: {}", summary.stats);
+    if !reports.percentiles.is_empty() {
+        let percentiles: Vec<String> = reports
+            .percentiles
+            .iter()
+            .map(|&percentile| match summary.stats.quantile(percentile / 100.0) {
+                Some(value) => format!("p{percentile} {value}"),
+                None => format!("p{percentile} -"),
+            })
+            .collect();
+        let _ = writeln!(text, "Percentiles: {}", percentiles.join(", "));
+    }
+    if let Some(format) = reports.histogram {
+        text.push_str(&report::histogram(&summary.stats, format));
     }
+    if let Some(format) = reports.digit_frequency {
+        text.push_str(&report::digit_frequency(&summary.stats, format));
+    }
+    if let Some(format) = reports.token_frequency {
+        text.push_str(&report::token_frequency(&summary.tokens, format));
+    }
+    if reports.top != 0 {
+        let _ = write!(text, "Highest values:\n{}", report::top_lines(&summary.highest));
+    }
+    if reports.bottom != 0 {
+        let _ = write!(text, "Lowest values:\n{}", report::top_lines(&summary.lowest));
+    }
+    if !summary.groups.is_empty() {
+        let _ = write!(text, "Groups:\n{}", report::groups(&summary.groups));
+    }
+    let files = processing.files();
+    if files.len() > 1 {
+        text.push_str(&report::files(&files, reports.files));
+    }
+    let total = if args.scope == Scope::File {
+        report::input_ends(&summary.ends)
+    } else {
+        format!("\nTotal amount: {}\n", summary.total_sum)
+    };
+    // the sizes of the files written, nothing is known of a report printed to stdout
+    if args.output.is_some() || args.rejects.is_some() {
+        text.push_str(&report::output_size(text.len() + total.len(), processing.rejected_bytes()));
+    }
+
+    text + &total
 }
@@ -0,0 +1,242 @@
+#[cfg(feature = "statsd")]
+use std::net::UdpSocket;
+use std::time::{Duration, Instant};
+#[cfg(feature = "otlp")]
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use find_digits::Summary;
+
+#[cfg(any(feature = "statsd", feature = "otlp"))]
+use crate::tee::message;
+
+/// Where the throughput and error-rate gauges of the processing are sent
+pub struct Metrics {
+    #[cfg(feature = "statsd")]
+    statsd: Option<UdpSocket>,
+    /// `http://collector:4318/v1/metrics`
+    #[cfg(feature = "otlp")]
+    otlp: Option<String>,
+    start: Instant,
+}
+
+impl Metrics {
+    /// `None` without any exporter
+    pub fn new(statsd: Option<&str>, otlp: Option<&str>) -> Result<Option<Self>, String> {
+        if statsd.is_none() && otlp.is_none() {
+            return Ok(None);
+        }
+
+        #[cfg(not(feature = "statsd"))]
+        if statsd.is_some() {
+            return Err("StatsD export isn't supported by this build, rebuild it with `--features statsd`".to_owned());
+        }
+        #[cfg(not(feature = "otlp"))]
+        if otlp.is_some() {
+            return Err("OTLP export isn't supported by this build, rebuild it with `--features otlp`".to_owned());
+        }
+
+        Ok(Some(Self {
+            #[cfg(feature = "statsd")]
+            statsd: match statsd {
+                Some(address) => Some(
+                    UdpSocket::bind("0.0.0.0:0")
+                        .and_then(|socket| socket.connect(address).map(|()| socket))
+                        .map_err(|e| format!("Fail to reach StatsD {address}: {e}"))?,
+                ),
+                None => None,
+            },
+            #[cfg(feature = "otlp")]
+            otlp: otlp.map(|endpoint| format!("{}/v1/metrics", endpoint.trim_end_matches('/'))),
+            start: Instant::now(),
+        }))
+    }
+
+    /// Send the gauges of the totals so far, a failed export is reported and the processing goes on
+    pub fn export(&self, file: &str, summary: &Summary) {
+        let gauges = gauges(summary, self.start.elapsed());
+
+        #[cfg(feature = "statsd")]
+        if let Some(socket) = &self.statsd {
+            if let Err(e) = socket.send(statsd_packet(&gauges).as_bytes()) {
+                message!("Fail to export metrics to StatsD: {e}");
+            }
+        }
+
+        #[cfg(feature = "otlp")]
+        if let Some(url) = &self.otlp {
+            let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+            let result = ureq::post(url)
+                .header("Content-Type", "application/json")
+                .send(otlp_request(file, &gauges, timestamp));
+            if let Err(e) = result {
+                message!("Fail to export metrics to {url}: {e}");
+            }
+        }
+
+        #[cfg(not(feature = "otlp"))]
+        let _ = (file, gauges);
+    }
+}
+
+/// The names and the values of the exported gauges
+fn gauges(summary: &Summary, elapsed: Duration) -> [(&'static str, f64); 3] {
+    let parsed = summary.parsed_lines as f64;
+    [
+        ("find_digits.lines_per_second", parsed / elapsed.as_secs_f64().max(f64::EPSILON)),
+        (
+            "find_digits.error_rate",
+            if summary.parsed_lines == 0 {
+                0.0
+            } else {
+                summary.incorrect_lines as f64 / parsed
+            },
+        ),
+        ("find_digits.parsed_lines", parsed),
+    ]
+}
+
+/// One `name:value|g` line per gauge
+#[cfg(feature = "statsd")]
+fn statsd_packet(gauges: &[(&str, f64)]) -> String {
+    let lines: Vec<String> = gauges.iter().map(|(name, value)| format!("{name}:{value}|g")).collect();
+    lines.join("\n")
+}
+
+/// The OTLP/HTTP JSON encoding of the gauges
+#[cfg(feature = "otlp")]
+fn otlp_request(file: &str, gauges: &[(&str, f64)], timestamp: Duration) -> String {
+    let metrics: Vec<serde_json::Value> = gauges
+        .iter()
+        .map(|(name, value)| {
+            serde_json::json!({
+                "name": name,
+                "gauge": {"dataPoints": [{
+                    "timeUnixNano": timestamp.as_nanos().to_string(),
+                    "asDouble": value,
+                    "attributes": [{"key": "file", "value": {"stringValue": file}}],
+                }]},
+            })
+        })
+        .collect();
+
+    serde_json::json!({"resourceMetrics": [{
+        "resource": {"attributes": [{"key": "service.name", "value": {"stringValue": "find_digits"}}]},
+        "scopeMetrics": [{"scope": {"name": "find_digits"}, "metrics": metrics}],
+    }]})
+    .to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_gauges() {
+        let summary = Summary {
+            parsed_lines: 200,
+            incorrect_lines: 50,
+            ..Summary::default()
+        };
+
+        assert_eq!(
+            gauges(&summary, Duration::from_secs(4)),
+            [
+                ("find_digits.lines_per_second", 50.0),
+                ("find_digits.error_rate", 0.25),
+                ("find_digits.parsed_lines", 200.0)
+            ]
+        );
+        assert_eq!(gauges(&Summary::default(), Duration::ZERO)[1], ("find_digits.error_rate", 0.0));
+    }
+
+    #[test]
+    fn test_new() {
+        assert!(Metrics::new(None, None).unwrap().is_none());
+        #[cfg(not(feature = "statsd"))]
+        assert!(Metrics::new(Some("127.0.0.1:8125"), None).is_err());
+        #[cfg(not(feature = "otlp"))]
+        assert!(Metrics::new(None, Some("http://127.0.0.1:4318")).is_err());
+    }
+
+    #[cfg(feature = "statsd")]
+    #[test]
+    fn test_statsd() {
+        let collector = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let address = collector.local_addr().unwrap().to_string();
+        let metrics = Metrics::new(Some(&address), None).unwrap().unwrap();
+
+        let summary = Summary {
+            parsed_lines: 4,
+            incorrect_lines: 1,
+            ..Summary::default()
+        };
+        metrics.export("in.txt", &summary);
+
+        let mut packet = [0; 512];
+        let length = collector.recv(&mut packet).unwrap();
+        let packet = std::str::from_utf8(&packet[..length]).unwrap();
+        let lines: Vec<&str> = packet.lines().collect();
+
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("find_digits.lines_per_second:") && lines[0].ends_with("|g"));
+        assert_eq!(lines[1..], ["find_digits.error_rate:0.25|g", "find_digits.parsed_lines:4|g"]);
+    }
+
+    #[cfg(feature = "otlp")]
+    #[test]
+    fn test_otlp() {
+        use std::{
+            io::{BufRead, BufReader, Read, Write},
+            net::TcpListener,
+            thread,
+        };
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!("http://{}/", listener.local_addr().unwrap());
+        let collector = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(&stream);
+
+            let mut head = Vec::new();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line == "\r\n" {
+                    break;
+                }
+                head.push(line);
+            }
+            let length: usize = head
+                .iter()
+                .find_map(|header| {
+                    header
+                        .to_ascii_lowercase()
+                        .strip_prefix("content-length:")
+                        .map(str::to_owned)
+                })
+                .unwrap()
+                .trim()
+                .parse()
+                .unwrap();
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+
+            (&stream)
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")
+                .unwrap();
+            (head[0].clone(), String::from_utf8(body).unwrap())
+        });
+
+        let metrics = Metrics::new(None, Some(&endpoint)).unwrap().unwrap();
+        metrics.export("in.txt", &Summary::default());
+
+        let (request_line, body) = collector.join().unwrap();
+        assert_eq!(request_line, "POST /v1/metrics HTTP/1.1\r\n");
+
+        let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+        let metrics = &body["resourceMetrics"][0]["scopeMetrics"][0]["metrics"];
+        assert_eq!(metrics[1]["name"], "find_digits.error_rate");
+        assert_eq!(metrics[1]["gauge"]["dataPoints"][0]["asDouble"], 0.0);
+        assert_eq!(metrics[1]["gauge"]["dataPoints"][0]["attributes"][0]["value"]["stringValue"], "in.txt");
+    }
+}
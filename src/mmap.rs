@@ -0,0 +1,182 @@
+use std::{fs::File, iter, process::exit, str};
+
+use find_digits::{counting::ByteCounter, ByteCount, Summary};
+use memmap2::Mmap;
+use rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
+
+use crate::{
+    decompress::Compression,
+    process::{sum_file, Processing},
+    rejects::Reason,
+    signals,
+    tee::message,
+};
+
+/// Process the memory mapped file in newline-aligned chunks, one chunk per worker thread
+pub fn sum_mapped(file: &str, processing: &Processing) -> Summary {
+    if Compression::of_file(file).is_ok_and(|compression| compression != Compression::None) {
+        message!("Compressed file {file} can't be split, it's read by the std reader");
+        return sum_file(file, processing, &ByteCounter::default());
+    }
+
+    let f = match File::open(file) {
+        Ok(f) => f,
+        Err(e) => {
+            message!("Fail to open file {file}: {e:?}");
+            exit(1);
+        },
+    };
+
+    // SAFETY: the mapping is read only and lives shorter than the file handle,
+    // the file must not be truncated by another process while it's processed
+    let data = match unsafe { Mmap::map(&f) } {
+        Ok(data) => data,
+        Err(e) => {
+            message!("Fail to map file {file}: {e:?}");
+            exit(1);
+        },
+    };
+
+    let chunks = split(&data, processing.pool.current_num_threads());
+
+    let mut summary = processing.pool.install(|| {
+        // the line number of the first line of every chunk, the newlines are counted in parallel too
+        #[allow(clippy::naive_bytecount)]
+        let line_counts: Vec<_> = chunks
+            .par_iter()
+            .map(|&(_, chunk)| chunk.iter().filter(|&&byte| byte == b'\n').count())
+            .collect();
+        let first_lines: Vec<_> = line_counts
+            .iter()
+            .scan(0, |first, count| {
+                let number = *first;
+                *first += count;
+                Some(number)
+            })
+            .collect();
+
+        chunks
+            .par_iter()
+            .zip(first_lines)
+            .map(|(&(offset, chunk), first_line)| sum_chunk(file, offset, first_line, chunk, processing))
+            .reduce(Summary::default, |mut summary, chunk| {
+                summary.merge(&chunk);
+                summary
+            })
+    });
+
+    let size = data.len() as u64;
+    summary.input_bytes = ByteCount {
+        logical: size,
+        physical: size,
+    };
+
+    summary
+}
+
+/// Split the data into about `parts` chunks, every chunk but the last ends with a newline.
+/// Every chunk goes with its offset in the data.
+fn split(data: &[u8], parts: usize) -> Vec<(usize, &[u8])> {
+    let approximate = data.len().div_ceil(parts.max(1)).max(1);
+    let mut chunks = Vec::with_capacity(parts);
+    let mut start = 0;
+
+    while start < data.len() {
+        let end = (start + approximate).min(data.len());
+        let end = data[end..]
+            .iter()
+            .position(|&byte| byte == b'\n')
+            .map_or(data.len(), |pos| end + pos + 1);
+
+        chunks.push((start, &data[start..end]));
+        start = end;
+    }
+
+    chunks
+}
+
+/// The same lines as [`std::io::BufRead::lines`] gives: without `\n` or `\r\n`
+fn sum_chunk(file: &str, offset: usize, first_line: usize, chunk: &[u8], processing: &Processing) -> Summary {
+    let mut summary = processing.summary();
+    let mut cache = processing.line_cache();
+
+    // the piece after the final newline is empty, it isn't a line
+    let mut lines = chunk.split(|&byte| byte == b'\n').peekable();
+    let lines = iter::from_fn(|| {
+        let line = lines.next()?;
+        (!line.is_empty() || lines.peek().is_some()).then_some(line)
+    });
+
+    let mut line_offset = offset;
+    for (number, line) in lines.enumerate() {
+        let start = line_offset;
+        line_offset += line.len() + 1;
+
+        let number = first_line + number;
+        if number >= processing.lines.end || signals::interrupted() || processing.failure().is_some() {
+            break;
+        }
+        if number < processing.lines.start {
+            continue;
+        }
+        summary.parsed_lines += 1;
+
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        match str::from_utf8(line) {
+            Ok(line) => {
+                if let Err(e) = processing.process_line(file, number, line, &mut summary, &mut cache) {
+                    processing.fail(e);
+                    break;
+                }
+            },
+            Err(e) => {
+                message!("File {file} broken line(number {number}, byte offset {start}): {e:?}");
+                processing.reject(number, Reason::InvalidUtf8, None);
+            },
+        }
+    }
+
+    summary
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::temp_file;
+
+    #[test]
+    fn test_split() {
+        let data = b"two1nine\neightwothree\nabcone2threexyz\nxtwone3four\n4nineeightseven2";
+
+        for parts in 1..10 {
+            let chunks = split(data, parts);
+
+            assert_eq!(chunks.iter().map(|(_, chunk)| *chunk).collect::<Vec<_>>().concat(), data);
+            assert!(chunks.iter().rev().skip(1).all(|(_, chunk)| chunk.ends_with(b"\n")));
+            assert!(chunks
+                .iter()
+                .all(|&(offset, chunk)| &data[offset..offset + chunk.len()] == chunk));
+        }
+
+        assert!(split(b"", 4).is_empty());
+    }
+
+    #[test]
+    fn test_sum_mapped() {
+        use clap::Parser;
+
+        use crate::{process::sum_file, Args};
+
+        let path =
+            temp_file(".txt", "two1nine\r\neightwothree\n\nabcdefg\nabcone2threexyz\nxtwone3four\n4nineeightseven2");
+        let file = path.to_str().unwrap();
+
+        let processing = Processing::new(&Args::parse_from(["find_digits", "--file", file])).unwrap();
+        let expected = sum_file(file, &processing, &find_digits::counting::ByteCounter::default());
+
+        for threads in ["1", "2", "3", "4"] {
+            let processing = Processing::new(&Args::parse_from(["find_digits", "--file", file, "--threads", threads]));
+            assert_eq!(sum_mapped(file, &processing.unwrap()), expected);
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    process,
+};
+
+/// A file written next to its destination under a temporary name and renamed over it once complete,
+/// a crash never leaves a half-written destination
+pub struct AtomicFile {
+    path: PathBuf,
+    temporary: PathBuf,
+    writer: BufWriter<File>,
+}
+
+impl AtomicFile {
+    pub fn create(path: &Path) -> Result<Self, String> {
+        // in the same directory, a rename across file systems isn't atomic
+        let mut name = path.file_name().unwrap_or_default().to_owned();
+        name.push(format!(".{}.tmp", process::id()));
+        let temporary = path.with_file_name(name);
+
+        let file =
+            File::create(&temporary).map_err(|e| format!("Fail to create output {}: {e:?}", temporary.display()))?;
+
+        Ok(Self {
+            path: path.to_owned(),
+            temporary,
+            writer: BufWriter::new(file),
+        })
+    }
+
+    pub fn write(&mut self, text: &str) -> Result<(), String> {
+        self.writer
+            .write_all(text.as_bytes())
+            .map_err(|e| format!("Fail to write output {}: {e:?}", self.temporary.display()))
+    }
+
+    /// Replace the destination by the written file, the data is on the disk before the rename
+    pub fn commit(self) -> Result<(), String> {
+        let file = self
+            .writer
+            .into_inner()
+            .map_err(|e| format!("Fail to write output {}: {e:?}", self.temporary.display()))?;
+
+        file.sync_all()
+            .and_then(|()| fs::rename(&self.temporary, &self.path))
+            .map_err(|e| format!("Fail to save output {}: {e:?}", self.path.display()))
+    }
+}
+
+/// The bytes of a binary output, e.g. a Parquet file
+impl Write for AtomicFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.writer.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::temp_dir;
+
+    #[test]
+    fn test_commit() {
+        let dir = temp_dir();
+        let path = dir.path().join("results.txt");
+        fs::write(&path, "previous results\n").unwrap();
+
+        let mut output = AtomicFile::create(&path).unwrap();
+        output.write("Total amount: ").unwrap();
+        output.write("142\n").unwrap();
+        // nothing is replaced until the output is complete
+        assert_eq!(fs::read_to_string(&path).unwrap(), "previous results\n");
+
+        output.commit().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "Total amount: 142\n");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
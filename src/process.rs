@@ -0,0 +1,2097 @@
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    fmt,
+    fs::{self, File},
+    io::{self, BufRead, BufReader, IsTerminal, Read},
+    mem,
+    num::NonZeroUsize,
+    ops::Range,
+    panic,
+    path::PathBuf,
+    process::exit,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        mpsc::{self, Receiver, RecvTimeoutError, SyncSender},
+        Arc, Mutex, OnceLock, PoisonError, RwLock,
+    },
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use encoding_rs::Encoding;
+use find_digits::{
+    counting::{ByteCounter, CountingReader},
+    cross_check, json, matches_with, trim_line, ByteCount, Confusables, Dictionary, Extractor, LineError, LineValue,
+    Match, ScanOptions, StreamScanner, Summary, TopLines,
+};
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use regex::Regex;
+
+#[cfg(feature = "tui")]
+use crate::dashboard::Dashboard;
+use crate::{
+    checkpoint::{Checkpoint, Position, Progress},
+    config,
+    decompress::Compression,
+    explain,
+    group::GroupBy,
+    highlight,
+    history::{self, History},
+    metrics::Metrics,
+    output::AtomicFile,
+    records::{self, RecordSep, Records},
+    rejects::{Reason, Rejects},
+    report::FileTotals,
+    scan::Show,
+    signals,
+    sink::Sink,
+    spans,
+    tee::{message, TeeReader},
+    transcode,
+    verify::Reference,
+    Args, InputFormat, NoDigits, Scope,
+};
+
+const REPORT_DELAY: Duration = Duration::from_secs(10);
+/// How long `SIGUSR1` may wait for its report while there's nothing processed
+const SIGNAL_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+/// Lines in a batch
+const BATCH_LINES: usize = 4096;
+/// Bytes of the lines in a batch, bounds the memory of long lines
+const BATCH_BYTES: usize = 1 << 20;
+/// Batches queued per worker, the reader waits once the workers fall behind
+const QUEUE_DEPTH: usize = 2;
+
+/// Distinct lines cached by a worker
+const DEDUP_LINES: usize = 1 << 20;
+/// How long a `SIGHUP` may wait for its reload
+const RELOAD_CHECK_INTERVAL: Duration = Duration::from_millis(250);
+/// Shown among the rejected lines instead of a line longer than `--max-line-bytes`
+const LONG_LINE: &str = "<a line longer than --max-line-bytes>";
+
+/// The normalized form of a line (when it differs) and its value
+type CachedLine = (Option<String>, Result<LineValue, LineError>);
+
+/// The rules of a worker and the results of the lines it has already seen, the repeated lines are parsed once
+#[derive(Debug)]
+pub struct LineCache {
+    rules: Arc<Rules>,
+    /// of the rules, they're taken again and the seen lines are forgotten once they're reloaded
+    generation: usize,
+    seen: SeenLines,
+}
+
+impl LineCache {
+    fn refresh(&mut self, processing: &Processing) {
+        let generation = processing.generation.load(Ordering::Acquire);
+        if generation != self.generation {
+            self.rules = processing.rules();
+            self.generation = generation;
+            self.seen.clear();
+        }
+    }
+}
+
+#[derive(Debug)]
+struct SeenLines {
+    entries: Option<HashMap<String, CachedLine>>,
+}
+
+impl SeenLines {
+    fn get(&self, line: &str) -> Option<CachedLine> {
+        self.entries.as_ref()?.get(line).cloned()
+    }
+
+    /// Remember the result of the line, once the cache is full the new lines are parsed every time
+    fn insert(&mut self, original: &str, line: &str, result: Result<LineValue, LineError>) {
+        if let Some(entries) = &mut self.entries {
+            if entries.len() < DEDUP_LINES {
+                let normalized = (line != original).then(|| line.to_owned());
+                entries.insert(original.to_owned(), (normalized, result));
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        if let Some(entries) = &mut self.entries {
+            entries.clear();
+        }
+    }
+}
+
+/// What the lines are matched and filtered with, read again on `SIGHUP` by the long-running modes
+#[derive(Debug)]
+pub struct Rules {
+    options: ScanOptions,
+    confusables: Option<Confusables>,
+    /// only the lines matching the include pattern and not matching the exclude one are processed
+    include: Option<Regex>,
+    exclude: Option<Regex>,
+    /// the lines beginning with this prefix are comments, they're left out too
+    comment_prefix: Option<String>,
+    /// the values of the lines are extracted by this engine
+    extractor: Box<dyn Extractor>,
+    /// the values of every line are compared with the ones of this extractor
+    reference: Option<Reference>,
+}
+
+impl Rules {
+    pub fn new(args: &Args) -> Result<Self, String> {
+        let confusables = match &args.confusables_file {
+            Some(path) => {
+                let table = fs::read_to_string(path)
+                    .map_err(|e| format!("Fail to read confusables file {}: {e:?}", path.display()))?;
+                Some(Confusables::parse(&table)?)
+            },
+            None => args.confusables.then(Confusables::builtin),
+        };
+
+        let options = scan_options(args)?;
+        Ok(Self {
+            extractor: args.extractor.build(&options)?,
+            reference: args.verify.map(|_| Reference::new(&options)).transpose()?,
+            options,
+            confusables,
+            include: args.include_regex.clone(),
+            exclude: args.exclude_regex.clone(),
+            comment_prefix: args.skip_comments.clone().filter(|prefix| !prefix.is_empty()),
+        })
+    }
+
+    /// The line with its tokens, their spans and its value, as `scan --show explain` prints it
+    pub fn explain(&self, number: usize, line: &str) -> String {
+        let normalized = self.normalize(line);
+        explain::render(number, line, &normalized, &self.extractor.extract(&normalized), &self.options)
+    }
+
+    /// The line after the confusables replacement
+    fn normalize<'a>(&self, line: &'a str) -> Cow<'a, str> {
+        match &self.confusables {
+            Some(confusables) => confusables.normalize(line),
+            None => Cow::Borrowed(line),
+        }
+    }
+
+    /// `true` when the line passes the line filters
+    fn is_included(&self, line: &str) -> bool {
+        !self
+            .comment_prefix
+            .as_ref()
+            .is_some_and(|prefix| line.trim_start().starts_with(prefix.as_str()))
+            && self.include.as_ref().map_or(true, |include| include.is_match(line))
+            && !self.exclude.as_ref().is_some_and(|exclude| exclude.is_match(line))
+    }
+}
+
+/// Which lines are processed
+#[derive(Debug, Clone, Copy)]
+enum Sampling {
+    /// a random subset of this rate, chosen by the seed
+    Random { rate: f64, seed: u64 },
+    /// every line of this stride, starting from the first one
+    Stride(NonZeroUsize),
+}
+
+/// Everything the lines are processed with, built once from the arguments
+#[allow(clippy::struct_excessive_bools)]
+pub struct Processing {
+    rules: RwLock<Arc<Rules>>,
+    /// incremented by every reload of the rules
+    generation: AtomicUsize,
+    paranoid: bool,
+    /// lines the extractor and the reference extractor of `--verify` disagree on
+    disagreements: AtomicU64,
+    /// the first error of a line which stopped the processing, e.g. of `--on-no-digits error`
+    failure: OnceLock<String>,
+    /// the dotted path of the scanned field of the JSON records, the whole line is scanned without it
+    field: Option<String>,
+    /// the lines to process, all of them without sampling
+    sampling: Option<Sampling>,
+    /// count the matched tokens of the lines
+    count_tokens: bool,
+    /// keep the first and the last digits of the whole input, `--scope file`
+    input_ends: bool,
+    /// what the lines without digits are
+    no_digits: NoDigits,
+    /// the empty lines aren't incorrect
+    ignore_empty: bool,
+    /// the values are summed per key of the lines too
+    group_by: Option<GroupBy>,
+    /// print every line with its matches, the lines are processed in order by a single worker then
+    explain: bool,
+    /// echo every line, its matches colored when `true`, the lines are processed in order too
+    highlight: Option<bool>,
+    /// print the spans of the first and the last matches of every line as JSON, in order too
+    spans: bool,
+    /// print only the lines which yield a value when `true`, which don't otherwise, in order too
+    filter: Option<bool>,
+    /// parse the repeated lines once per worker
+    dedup: bool,
+    /// amounts of the lines with the highest and the lowest values to keep
+    top: usize,
+    bottom: usize,
+    /// the numbers of the lines to process, the other ones are neither read nor counted
+    pub lines: Range<usize>,
+    /// its size is the amount of the workers processing the lines
+    pub pool: ThreadPool,
+    /// capacity of the read buffer
+    pub buffer_size: usize,
+    /// the reads in flight and the size of a read of `--io-backend uring`
+    pub uring_depth: u32,
+    pub uring_block_size: u32,
+    /// the longer lines are scanned while they're read instead of being buffered
+    max_line_bytes: Option<usize>,
+    /// what ends the records instead of the newlines
+    record_sep: Option<RecordSep>,
+    /// the input is decoded from it, UTF-8 without it
+    encoding: Option<&'static Encoding>,
+    /// the gauges are exported with every report
+    pub metrics: Option<Metrics>,
+    /// where the position and the totals are saved with every report
+    pub checkpoint: Option<PathBuf>,
+    /// the saved position to continue from
+    pub resume: Option<Checkpoint>,
+    /// the lines and the report go to this file instead of stdout, it's gone once committed
+    output: Mutex<Option<AtomicFile>>,
+    /// receives the lines without a value
+    rejects: Option<Rejects>,
+    /// copy the raw input to stdout
+    pub tee: bool,
+    /// receives the rows of the lines and of the totals
+    pub sink: Option<Sink>,
+    /// receives the summary of every completed input, of `--record-history`
+    history: Option<History>,
+    /// the totals of every input of a run of several ones
+    files: Mutex<Vec<FileTotals>>,
+    /// the values of the processed lines, recorded for `--self-check`
+    line_values: Option<Mutex<LineValues>>,
+    /// the live totals and the rejected lines drawn by `--tui`
+    #[cfg(feature = "tui")]
+    pub dashboard: Option<Arc<Dashboard>>,
+}
+
+impl Processing {
+    pub fn new(args: &Args) -> Result<Self, String> {
+        let rules = Rules::new(args)?;
+
+        if args.buffer_size == 0 {
+            return Err("The read buffer size must not be zero".to_owned());
+        }
+        if args.max_line_bytes == Some(0) {
+            return Err("The maximal line size must not be zero".to_owned());
+        }
+
+        let resume = match &args.checkpoint {
+            Some(path) if args.resume => Checkpoint::load(path)?,
+            _ => None,
+        };
+        if let Some(checkpoint) = &resume {
+            if Some(&checkpoint.file) != args.file.as_ref() {
+                return Err(format!(
+                    "Checkpoint {} is of file {}",
+                    args.checkpoint
+                        .as_ref()
+                        .map_or_else(String::new, |path| path.display().to_string()),
+                    checkpoint.file
+                ));
+            }
+        }
+
+        let (scan, reports) = (args.scan(), args.stats());
+        let show = scan.map(|scan_args| scan_args.show);
+
+        let mut pool = ThreadPoolBuilder::new();
+        if show.is_some() {
+            pool = pool.num_threads(1);
+        } else if let Some(threads) = args.threads {
+            pool = pool.num_threads(threads.get());
+        }
+        let pool = pool
+            .build()
+            .map_err(|e| format!("Fail to start worker threads: {e:?}"))?;
+
+        Ok(Self {
+            rules: RwLock::new(Arc::new(rules)),
+            generation: AtomicUsize::new(0),
+            paranoid: args.paranoid,
+            disagreements: AtomicU64::new(0),
+            failure: OnceLock::new(),
+            field: match args.input_format {
+                InputFormat::Text | InputFormat::Parquet => None,
+                InputFormat::Ndjson => args.field.clone(),
+            },
+            sampling: match (args.sample, args.every) {
+                (Some(rate), _) => Some(Sampling::Random { rate, seed: args.seed }),
+                (_, Some(stride)) => Some(Sampling::Stride(stride)),
+                _ => None,
+            },
+            count_tokens: reports.token_frequency.is_some(),
+            input_ends: args.scope == Scope::File,
+            no_digits: args.on_no_digits,
+            ignore_empty: args.ignore_empty,
+            group_by: args.group_by.clone(),
+            explain: show == Some(Show::Explain),
+            highlight: scan
+                .filter(|scan_args| scan_args.show == Show::Highlight)
+                .map(|scan_args| {
+                    scan_args
+                        .color
+                        .enabled(args.output.is_none() && !args.tee && io::stdout().is_terminal())
+                }),
+            spans: show == Some(Show::Spans),
+            filter: match show {
+                Some(Show::Matching) => Some(true),
+                Some(Show::Nonmatching) => Some(false),
+                _ => None,
+            },
+            dedup: args.dedup,
+            top: reports.top,
+            bottom: reports.bottom,
+            lines: match &args.lines {
+                Some(lines) => lines.clone(),
+                None => args.skip..args.limit.map_or(usize::MAX, |limit| args.skip.saturating_add(limit)),
+            },
+            pool,
+            buffer_size: args.buffer_size,
+            uring_depth: args.uring_depth,
+            uring_block_size: args.uring_block_size,
+            max_line_bytes: args.max_line_bytes,
+            record_sep: args.record_sep(),
+            encoding: args.encoding,
+            metrics: Metrics::new(args.statsd.as_deref(), args.otlp_endpoint.as_deref())?,
+            checkpoint: args.checkpoint.clone(),
+            resume,
+            output: Mutex::new(args.output.as_deref().map(AtomicFile::create).transpose()?),
+            rejects: args.rejects.as_deref().map(Rejects::create).transpose()?,
+            tee: args.tee,
+            sink: args
+                .sink
+                .as_deref()
+                .map(|url| Sink::open(url, args.input().unwrap_or_default()))
+                .transpose()?,
+            history: args
+                .record_history
+                .then(|| history::location(args.history_file.as_deref()).and_then(|path| History::open(&path)))
+                .transpose()?,
+            files: Mutex::default(),
+            line_values: args.self_check.then(Mutex::default),
+            #[cfg(feature = "tui")]
+            dashboard: args.tui.then(Arc::default),
+        })
+    }
+
+    /// Print the output of the lines or the report, or write it to the output file
+    pub fn emit(&self, text: &str) {
+        let mut output = self.output.lock().unwrap_or_else(PoisonError::into_inner);
+        let Some(output) = output.as_mut() else {
+            if self.tee {
+                eprint!("{text}");
+            } else {
+                print!("{text}");
+            }
+            return;
+        };
+
+        if let Err(e) = output.write(text) {
+            message!("{e}");
+            exit(1);
+        }
+    }
+
+    /// Replace the output file by the written output and flush the rejects file, nothing happens without them
+    pub fn commit_output(&self) -> Result<(), String> {
+        self.flush_rejects()?;
+        match self.output.lock().unwrap_or_else(PoisonError::into_inner).take() {
+            Some(output) => output.commit(),
+            None => Ok(()),
+        }
+    }
+
+    /// Add the summary of the completed input (a file, a connection) to the history of `--record-history`
+    pub fn record_history(&self, input: &str, summary: &Summary, elapsed: Duration) {
+        if let Some(Err(e)) = self
+            .history
+            .as_ref()
+            .map(|history| history.record(input, summary, elapsed))
+        {
+            message!("{e}");
+        }
+    }
+
+    /// Bytes written to the `--rejects` file, none without it
+    pub fn rejected_bytes(&self) -> Option<u64> {
+        self.rejects.as_ref().map(Rejects::written)
+    }
+
+    /// Write the rejected lines found so far, e.g. of an interrupted run
+    pub fn flush_rejects(&self) -> Result<(), String> {
+        self.rejects.as_ref().map_or(Ok(()), Rejects::flush)
+    }
+
+    /// Save the committed position and totals, a failure is reported and the processing goes on
+    fn save_checkpoint(&self, file: &str, progress: Option<&Progress>) {
+        if let (Some(path), Some(progress)) = (&self.checkpoint, progress) {
+            if let Err(e) = progress.checkpoint(file).save(path) {
+                message!("{e}");
+            }
+        }
+    }
+
+    /// Empty summary keeping the lines this processing reports
+    pub fn summary(&self) -> Summary {
+        Summary {
+            highest: TopLines::highest(self.top),
+            lowest: TopLines::lowest(self.bottom),
+            ..Summary::default()
+        }
+    }
+
+    /// Cache of the results of the repeated lines, it caches nothing without `--dedup`
+    pub fn line_cache(&self) -> LineCache {
+        LineCache {
+            generation: self.generation.load(Ordering::Acquire),
+            rules: self.rules(),
+            seen: SeenLines {
+                entries: self.dedup.then(HashMap::new),
+            },
+        }
+    }
+
+    fn rules(&self) -> Arc<Rules> {
+        Arc::clone(&self.rules.read().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    /// Lines the extractor and the reference extractor disagreed on so far
+    pub fn disagreements(&self) -> u64 {
+        self.disagreements.load(Ordering::Relaxed)
+    }
+
+    /// Stop the processing by the error of a line, the first one is kept
+    pub fn fail(&self, error: String) {
+        let _ = self.failure.set(error);
+    }
+
+    /// The error the processing was stopped by, see [`Self::fail`]
+    pub fn failure(&self) -> Option<&str> {
+        self.failure.get().map(String::as_str)
+    }
+
+    /// Replace the rules by the ones of the arguments, the lines in flight are processed with the previous ones
+    pub fn reload(&self, args: &Args) -> Result<(), String> {
+        let rules = Rules::new(args)?;
+        *self.rules.write().unwrap_or_else(PoisonError::into_inner) = Arc::new(rules);
+        self.generation.fetch_add(1, Ordering::Release);
+        Ok(())
+    }
+
+    /// The lines of the reader as [`Batcher::push`] records them, the records of `--record-sep` are lines too
+    fn lines_of<'a>(&self, reader: impl BufRead + 'a) -> Box<dyn Iterator<Item = io::Result<String>> + 'a> {
+        let Some(separator) = &self.record_sep else {
+            return Box::new(reader.lines());
+        };
+
+        Box::new(Records::new(reader, separator.clone()).map(|record| {
+            records::text(record).map(|mut line| {
+                if line.ends_with('\n') {
+                    line.pop();
+                    if line.ends_with('\r') {
+                        line.pop();
+                    }
+                }
+                line
+            })
+        }))
+    }
+
+    /// The value and all the matches of the line after the confusables replacement, nothing is counted
+    pub fn inspect(&self, line: &str) -> (Result<LineValue, LineError>, Vec<Match>) {
+        let rules = self.rules();
+        let line = rules.normalize(line);
+        (rules.extractor.extract(&line), matches_with(&line, &rules.options).collect())
+    }
+
+    /// Add the value of the line to the summary and return it, `number` is used in the reports only
+    ///
+    /// # Errors
+    ///
+    /// the line has no digits with `--on-no-digits error`, the processing should stop
+    pub fn process_line(
+        &self,
+        file: &str,
+        number: usize,
+        line: &str,
+        summary: &mut Summary,
+        cache: &mut LineCache,
+    ) -> Result<Option<u32>, String> {
+        let value = self.add_line(file, number, line, summary, cache)?;
+        if let Some(values) = &self.line_values {
+            values
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .record(number, value);
+        }
+        Ok(value)
+    }
+
+    /// [`Self::process_line`] of a received line, e.g. of a request: a line of invalid UTF-8 is incorrect and rejected
+    pub fn process_bytes(
+        &self,
+        file: &str,
+        number: usize,
+        line: &[u8],
+        summary: &mut Summary,
+        cache: &mut LineCache,
+    ) -> Result<Option<u32>, String> {
+        let Ok(line) = std::str::from_utf8(line) else {
+            summary.incorrect_lines += 1;
+            self.reject(number, Reason::InvalidUtf8, None);
+            return Ok(None);
+        };
+
+        self.process_line(file, number, line, summary, cache)
+    }
+
+    /// Add the value of a line scanned while it was read to the summary, see `--max-line-bytes`
+    ///
+    /// Its text isn't kept: it's neither printed nor among the top lines and its tokens aren't counted.
+    fn process_scanned(
+        &self,
+        file: &str,
+        number: usize,
+        result: Result<LineValue, LineError>,
+        summary: &mut Summary,
+    ) -> Result<(), String> {
+        if !self.is_sampled(number) {
+            summary.unsampled_lines += 1;
+            return Ok(());
+        }
+
+        let value = match result {
+            Ok(value) => value,
+            Err(LineError::NoDigits) if self.no_digits != NoDigits::Skip => {
+                return self.add_no_digits(file, number, None, summary);
+            },
+            Err(e) => {
+                summary.incorrect_lines += 1;
+                self.reject(number, e.into(), Some(LONG_LINE));
+                return Ok(());
+            },
+        };
+        summary.add_value(value.value);
+        if self.input_ends {
+            summary.ends.add(number as u64, &value);
+        }
+        Ok(())
+    }
+
+    /// Add the lines of the batch to the summary, the scanned ones are taken
+    fn process_batch(
+        &self,
+        file: &str,
+        batch: &mut Batch,
+        summary: &mut Summary,
+        cache: &mut LineCache,
+    ) -> Result<(), String> {
+        summary.parsed_lines += batch.broken.len() as u64;
+        for &number in &batch.broken {
+            self.reject(number, Reason::InvalidUtf8, None);
+        }
+        for (number, line) in batch.lines() {
+            summary.parsed_lines += 1;
+            self.process_line(file, number, line, summary, cache)?;
+        }
+        for (number, result) in batch.scanned.drain(..) {
+            summary.parsed_lines += 1;
+            self.process_scanned(file, number, result, summary)?;
+        }
+        Ok(())
+    }
+
+    /// Record the totals of an input of a run of several ones, they're reported apart besides the merged ones
+    pub fn add_file(&self, file: &str, summary: &Summary, elapsed: Duration) {
+        let totals = FileTotals::new(file, summary, elapsed);
+        self.files.lock().unwrap_or_else(PoisonError::into_inner).push(totals);
+    }
+
+    /// The recorded totals of the inputs in the order they were processed
+    pub fn files(&self) -> Vec<FileTotals> {
+        self.files.lock().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+
+    /// The recorded values of the lines processed so far, the following lines are recorded anew
+    fn take_line_values(&self) -> LineValues {
+        self.line_values
+            .as_ref()
+            .map(|values| mem::take(&mut *values.lock().unwrap_or_else(PoisonError::into_inner)))
+            .unwrap_or_default()
+    }
+
+    fn add_line(
+        &self,
+        file: &str,
+        number: usize,
+        line: &str,
+        summary: &mut Summary,
+        cache: &mut LineCache,
+    ) -> Result<Option<u32>, String> {
+        if !self.is_sampled(number) {
+            summary.unsampled_lines += 1;
+            return Ok(None);
+        }
+
+        let line = trim_line(line, number == 0);
+        // the key is of the whole line, e.g. besides the scanned field of a JSON record
+        let raw = line;
+        let text;
+        let line = match &self.field {
+            Some(path) => match json::field(line, path) {
+                Ok(field) => {
+                    text = field;
+                    text.as_str()
+                },
+                Err(e) => {
+                    message!("File {file} incorrect record (line number {number}): {e}");
+                    summary.incorrect_lines += 1;
+                    self.reject(number, Reason::InvalidRecord, Some(line));
+                    return Ok(None);
+                },
+            },
+            None => line,
+        };
+
+        cache.refresh(self);
+        let LineCache { rules, seen, .. } = cache;
+        if !rules.is_included(line) {
+            summary.excluded_lines += 1;
+            return Ok(None);
+        }
+
+        let original = line;
+        let (line, result) = if let Some((normalized, result)) = seen.get(original) {
+            (normalized.map_or(Cow::Borrowed(original), Cow::Owned), result)
+        } else {
+            let (line, result) = self.extract(rules, file, number, original);
+            seen.insert(original, &line, result);
+            (line, result)
+        };
+
+        if self.explain {
+            self.emit(&explain::render(number, original, &line, &result, &rules.options));
+        }
+        if self.filter == Some(result.is_ok()) {
+            self.emit(&format!("{original}\n"));
+        }
+        match self.highlight {
+            Some(true) => self.emit(&format!("{}\n", highlight::render(original, &line, result.as_ref().ok()))),
+            Some(false) => self.emit(&format!("{original}\n")),
+            None => {},
+        }
+        if let Some(sink) = &self.sink {
+            if let Err(e) = sink.add_line(file, number, &line, result.as_ref().ok(), &rules.options) {
+                message!("{e}");
+                exit(1);
+            }
+        }
+
+        let value = match result {
+            Ok(value) => value,
+            Err(LineError::NoDigits) if self.no_digits != NoDigits::Skip => {
+                self.add_no_digits(file, number, Some(original), summary)?;
+                self.add_group(raw, 0, summary);
+                return Ok(Some(0));
+            },
+            Err(LineError::Empty) if self.ignore_empty => {
+                summary.empty_lines += 1;
+                return Ok(None);
+            },
+            Err(e) => {
+                summary.empty_lines += u64::from(e == LineError::Empty);
+                summary.incorrect_lines += 1;
+                self.reject(number, e.into(), Some(original));
+                return Ok(None);
+            },
+        };
+
+        if self.spans {
+            self.emit(&format!("{}\n", spans::render(file, number, &value)));
+        }
+
+        summary.add_value(value.value);
+
+        if self.count_tokens {
+            summary.tokens.add(&line, &value.first);
+            summary.tokens.add(&line, &value.last);
+        }
+
+        if self.input_ends {
+            summary.ends.add(number as u64, &value);
+        }
+        self.add_group(raw, value.value, summary);
+        summary.highest.add(value.value, number as u64, original);
+        summary.lowest.add(value.value, number as u64, original);
+        Ok(Some(value.value))
+    }
+
+    /// The value [`Self::add_line`] adds of the line, without its side effects: nothing is printed, checked,
+    /// rejected or recorded
+    ///
+    /// # Errors
+    ///
+    /// `--on-no-digits error`
+    fn line_value(&self, file: &str, number: usize, line: &str, cache: &mut LineCache) -> Result<Option<u32>, String> {
+        if !self.is_sampled(number) {
+            return Ok(None);
+        }
+
+        let line = trim_line(line, number == 0);
+        let text;
+        let line = match &self.field {
+            Some(path) => match json::field(line, path) {
+                Ok(field) => {
+                    text = field;
+                    text.as_str()
+                },
+                Err(_) => return Ok(None),
+            },
+            None => line,
+        };
+
+        cache.refresh(self);
+        let LineCache { rules, seen, .. } = cache;
+        if !rules.is_included(line) {
+            return Ok(None);
+        }
+
+        let result = match seen.get(line) {
+            Some((_, result)) => result,
+            None => rules.extractor.extract(&rules.normalize(line)),
+        };
+        match result {
+            Ok(value) => Ok(Some(value.value)),
+            Err(LineError::NoDigits) => self.no_digits_value(file, number, Some(line)),
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// The value of a line without digits by `--on-no-digits`, none when it's skipped; a scanned line has no text
+    ///
+    /// # Errors
+    ///
+    /// `--on-no-digits error`
+    pub fn no_digits_value(&self, file: &str, number: usize, line: Option<&str>) -> Result<Option<u32>, String> {
+        match self.no_digits {
+            NoDigits::Skip => Ok(None),
+            NoDigits::Zero => Ok(Some(0)),
+            NoDigits::Error => {
+                let line = line.map_or_else(String::new, |line| format!(", line: {line:?}"));
+                Err(format!("file {file} line number {number} has no digits{line}"))
+            },
+        }
+    }
+
+    /// Add the line without digits by `--on-no-digits zero`, see [`Self::no_digits_value`]
+    fn add_no_digits(
+        &self,
+        file: &str,
+        number: usize,
+        line: Option<&str>,
+        summary: &mut Summary,
+    ) -> Result<(), String> {
+        if self.no_digits_value(file, number, line)?.is_none() {
+            return Ok(());
+        }
+
+        summary.add_value(0);
+        if let Some(line) = line {
+            summary.highest.add(0, number as u64, line);
+            summary.lowest.add(0, number as u64, line);
+        }
+        Ok(())
+    }
+
+    /// Add the value to the group of the key of the line, the lines without a key are a group too
+    fn add_group(&self, line: &str, value: u32, summary: &mut Summary) {
+        if let Some(group_by) = &self.group_by {
+            summary.groups.add(group_by.key(line).unwrap_or_default(), value);
+        }
+    }
+
+    /// Show the totals on the dashboard, `false` without it
+    #[cfg_attr(not(feature = "tui"), allow(clippy::unused_self))]
+    fn show_totals(&self, summary: &Summary) -> bool {
+        #[cfg(feature = "tui")]
+        if let Some(dashboard) = &self.dashboard {
+            dashboard.update(summary);
+            return true;
+        }
+        let _ = summary;
+        false
+    }
+
+    /// Write the line without a value to the `--rejects` file and show it on the dashboard,
+    /// `line` is none of a line whose text isn't known
+    pub fn reject(&self, number: usize, reason: Reason, line: Option<&str>) {
+        if let Some(rejects) = &self.rejects {
+            if let Err(e) = rejects.add(number, reason, line) {
+                message!("{e}");
+                exit(1);
+            }
+        }
+
+        #[cfg(feature = "tui")]
+        if let (Some(dashboard), Some(line)) = (&self.dashboard, line) {
+            dashboard.reject(number, line);
+        }
+    }
+
+    /// The line after the confusables replacement and its value
+    fn extract<'a>(
+        &self,
+        rules: &Rules,
+        file: &str,
+        number: usize,
+        line: &'a str,
+    ) -> (Cow<'a, str>, Result<LineValue, LineError>) {
+        let line = rules.normalize(line);
+
+        if self.paranoid {
+            if let Err(e) = cross_check(&line, &rules.options) {
+                message!("File {file} paranoid check failed (line number {number}): {e}, line: {line:?}");
+            }
+        }
+
+        let result = rules.extractor.extract(&line);
+        if let Some(reference) = &rules.reference {
+            if let Err(e) = reference.check(&line, &result) {
+                self.disagreements.fetch_add(1, Ordering::Relaxed);
+                message!("File {file} verification failed (line number {number}): {e}, line: {line:?}");
+            }
+        }
+
+        (line, result)
+    }
+
+    /// `true` when the line is chosen by the sampling, the choice depends on the seed and the line number only
+    fn is_sampled(&self, number: usize) -> bool {
+        match self.sampling {
+            None => true,
+            Some(Sampling::Random { rate, seed }) => {
+                let random = mix(seed ^ mix(number as u64));
+                // the 53 bits of the mantissa
+                ((random >> 11) as f64 / (1_u64 << 53) as f64) < rate
+            },
+            Some(Sampling::Stride(stride)) => number % stride.get() == 0,
+        }
+    }
+}
+
+/// Reload the rules from the options read again (see [`config::reload`]) on every `SIGHUP`, the totals go on
+///
+/// A failed reload is reported and the previous rules are kept.
+pub fn reload_on_hangup(processing: &Arc<Processing>) {
+    signals::trap_hangup();
+
+    let processing = Arc::clone(processing);
+    thread::spawn(move || loop {
+        thread::sleep(RELOAD_CHECK_INTERVAL);
+        if signals::reload_requested() {
+            match config::reload().and_then(|args| processing.reload(&args)) {
+                Ok(()) => message!("Reloaded the dictionary and the line filters"),
+                Err(e) => message!("Fail to reload the configuration, the previous one is kept: {e}"),
+            }
+        }
+    });
+}
+
+/// `SplitMix64` finalizer, the close inputs give unrelated outputs
+fn mix(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^ (x >> 31)
+}
+
+/// The matching options of the arguments
+pub fn scan_options(args: &Args) -> Result<ScanOptions, String> {
+    let dictionary = match &args.dictionary {
+        Some(path) => {
+            let table =
+                fs::read_to_string(path).map_err(|e| format!("Fail to read dictionary {}: {e:?}", path.display()))?;
+            Some(Arc::new(Dictionary::parse(&table)?))
+        },
+        None => None,
+    };
+
+    Ok(ScanOptions {
+        unicode_digits: args.unicode_digits,
+        fullwidth_digits: args.fullwidth_digits,
+        keycap_digits: args.keycap_digits,
+        isolated_digits_only: args.isolated_digits_only,
+        fuzzy: usize::from(args.fuzzy),
+        word_boundaries: args.word_boundaries,
+        dictionary,
+        overlap: args.overlap,
+    })
+}
+
+/// `-` is stdin
+pub fn sum_file(file: &str, processing: &Processing, physical_bytes: &ByteCounter) -> Summary {
+    if file == "-" {
+        return sum_read(file, processing, io::stdin(), physical_bytes);
+    }
+
+    match File::open(file) {
+        Ok(f) => sum_read(file, processing, f, physical_bytes),
+        Err(e) => {
+            message!("Fail to open file {file}: {e:?}");
+            exit(1);
+        },
+    }
+}
+
+/// Process the lines of the raw input of the file, e.g. a file opened by another reader
+pub fn sum_read(file: &str, processing: &Processing, input: impl Read + Send, physical_bytes: &ByteCounter) -> Summary {
+    let input: Box<dyn Read + Send> = if processing.tee {
+        Box::new(TeeReader::new(input, io::stdout()))
+    } else {
+        Box::new(input)
+    };
+    let mut physical =
+        BufReader::with_capacity(processing.buffer_size, CountingReader::with_counter(input, physical_bytes.clone()));
+    let compression = Compression::detect(file, physical.fill_buf().unwrap_or_default());
+    // the decoded stream, differs from the physical one once the input is compressed
+    let decoder = match compression.decoder(physical) {
+        Ok(decoder) => decoder,
+        Err(e) => {
+            message!("Fail to decompress file {file}: {e}");
+            exit(1);
+        },
+    };
+    let (logical, logical_bytes) = CountingReader::new(transcode::decode(decoder, processing.encoding));
+    let mut reader = BufReader::with_capacity(processing.buffer_size, logical);
+
+    if let Some(checkpoint) = &processing.resume {
+        // a decoded stream can't be seeked, the processed part is read again but not processed
+        let offset = checkpoint.position.offset;
+        match io::copy(&mut (&mut reader).take(offset), &mut io::sink()) {
+            Ok(skipped) if skipped == offset => {},
+            Ok(_) => {
+                message!("File {file} is shorter than its checkpoint, it can't be resumed");
+                exit(1);
+            },
+            Err(e) => {
+                message!("Fail to read file {file}: {e:?}");
+                exit(1);
+            },
+        }
+    }
+
+    let (counter, tee, separator) = (logical_bytes.clone(), processing.tee, processing.record_sep.clone());
+    let mut summary = pipeline(file, processing, move |collector| {
+        match (separator, processing.max_line_bytes) {
+            (Some(separator), _) => read_records(&mut Records::new(&mut reader, separator), collector, &counter),
+            (None, Some(limit)) => read_bounded(&mut reader, collector, &counter, limit, processing),
+            (None, None) => read_batches(&mut reader, collector, &counter),
+        }
+        // e.g. the lines past `--limit`, the next command of the pipeline gets the whole input
+        if tee && !signals::interrupted() {
+            if let Err(e) = io::copy(&mut reader, &mut io::sink()) {
+                message!("Fail to copy the rest of file {file}: {e:?}");
+            }
+        }
+    });
+
+    summary.input_bytes = ByteCount {
+        logical: logical_bytes.get(),
+        physical: physical_bytes.get(),
+    };
+
+    summary
+}
+
+/// Add the values of the lines of the file sequentially to the summary until its total amount overflows:
+/// the number of the line where it happened
+///
+/// Nothing but the values is computed, the lines aren't printed, rejected or recorded again.
+pub fn locate_overflow(file: &str, processing: &Processing, mut summary: Summary) -> Option<usize> {
+    let reader = decoded_reader(file, processing).ok()?;
+    let mut cache = processing.line_cache();
+
+    for (number, read_result) in processing.lines_of(reader).enumerate().take(processing.lines.end) {
+        if number < processing.lines.start {
+            continue;
+        }
+        if let Ok(line) = read_result {
+            if let Some(value) = processing.line_value(file, number, &line, &mut cache).ok()? {
+                summary.add_value(value);
+            }
+        }
+
+        if summary.overflowed {
+            return Some(number);
+        }
+    }
+
+    None
+}
+
+/// What is recorded of a line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Record {
+    Unseen,
+    NoValue,
+    Value(u32),
+}
+
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unseen => write!(f, "not processed"),
+            Self::NoValue => write!(f, "no value"),
+            Self::Value(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+/// The value of every line by its number, a byte per line
+#[derive(Debug, Default, PartialEq, Eq)]
+struct LineValues {
+    values: Vec<u8>,
+}
+
+impl LineValues {
+    /// of the lines which weren't processed
+    const UNSEEN: u8 = u8::MAX;
+    /// of the lines which were processed without giving a value
+    const NO_VALUE: u8 = u8::MAX - 1;
+
+    fn record(&mut self, number: usize, value: Option<u32>) {
+        if self.values.len() <= number {
+            self.values.resize(number + 1, Self::UNSEEN);
+        }
+        self.values[number] = value
+            .and_then(|value| u8::try_from(value).ok())
+            .unwrap_or(Self::NO_VALUE);
+    }
+
+    fn get(&self, number: usize) -> Record {
+        match self.values.get(number).copied().unwrap_or(Self::UNSEEN) {
+            Self::UNSEEN => Record::Unseen,
+            Self::NO_VALUE => Record::NoValue,
+            value => Record::Value(u32::from(value)),
+        }
+    }
+
+    /// The number of the first line recorded differently by the other values, and both its records
+    fn first_difference(&self, other: &Self) -> Option<(usize, Record, Record)> {
+        (0..self.values.len().max(other.values.len()))
+            .map(|number| (number, self.get(number), other.get(number)))
+            .find(|(_, own, other)| own != other)
+    }
+}
+
+/// Process the file again sequentially, on the calling thread, and compare the totals and the values of every line
+/// with the ones the workers gave, a difference is a bug of the parallel processing
+pub fn self_check(file: &str, processing: &Processing, summary: &Summary) -> Result<(), String> {
+    let parallel = processing.take_line_values();
+    let expected = sum_sequential(file, processing)?;
+    let sequential = processing.take_line_values();
+
+    if let Some((number, parallel, sequential)) = parallel.first_difference(&sequential) {
+        return Err(format!(
+            "Self-check of file {file} failed at line number {number}: {parallel} in parallel, {sequential} sequentially"
+        ));
+    }
+
+    let summary = Summary {
+        input_bytes: ByteCount::default(),
+        ..summary.clone()
+    };
+    if summary != expected {
+        return Err(format!(
+            "Self-check of file {file} failed, the totals differ: {summary} in parallel, {expected} sequentially"
+        ));
+    }
+
+    Ok(())
+}
+
+/// The lines of the file as `BufRead::lines` gives them, processed one by one
+fn sum_sequential(file: &str, processing: &Processing) -> Result<Summary, String> {
+    let reader = decoded_reader(file, processing)?;
+    let (mut summary, mut cache) = (processing.summary(), processing.line_cache());
+
+    for (number, read_result) in processing.lines_of(reader).enumerate().take(processing.lines.end) {
+        if number < processing.lines.start {
+            continue;
+        }
+        summary.parsed_lines += 1;
+        match read_result {
+            Ok(line) => {
+                processing.process_line(file, number, &line, &mut summary, &mut cache)?;
+            },
+            // invalid UTF-8, the line is counted only
+            Err(e) if e.kind() == io::ErrorKind::InvalidData => {},
+            Err(e) => return Err(format!("Fail to read file {file} at line number {number}: {e:?}")),
+        }
+    }
+
+    Ok(summary)
+}
+
+/// The decompressed and transcoded text of a local file, as the sequential passes read it
+fn decoded_reader(file: &str, processing: &Processing) -> Result<impl BufRead, String> {
+    let f = File::open(file).map_err(|e| format!("Fail to open file {file}: {e:?}"))?;
+    let mut physical = BufReader::with_capacity(processing.buffer_size, f);
+    let compression = Compression::detect(file, physical.fill_buf().unwrap_or_default());
+    let decoder = compression
+        .decoder(physical)
+        .map_err(|e| format!("Fail to decompress file {file}: {e}"))?;
+
+    Ok(BufReader::with_capacity(processing.buffer_size, transcode::decode(decoder, processing.encoding)))
+}
+
+/// Numbered lines handed from the reader to a worker at once, the lines share a single reused buffer
+#[derive(Debug, Default)]
+pub struct Batch {
+    text: String,
+    /// the numbers and the spans of the lines in the text
+    lines: Vec<(usize, Range<usize>)>,
+    /// the numbers and the values of the lines longer than `--max-line-bytes`, their text isn't kept
+    scanned: Vec<(usize, Result<LineValue, LineError>)>,
+    /// the numbers of the lines with invalid UTF-8 among them, they're counted and rejected only
+    broken: Vec<usize>,
+    /// the batches are numbered in the order of the input
+    sequence: u64,
+    /// of the input after the lines
+    end: Position,
+}
+
+impl Batch {
+    fn with_capacity() -> Self {
+        Self {
+            text: String::with_capacity(BATCH_BYTES),
+            lines: Vec::with_capacity(BATCH_LINES),
+            scanned: Vec::new(),
+            broken: Vec::new(),
+            sequence: 0,
+            end: Position::default(),
+        }
+    }
+
+    fn lines(&self) -> impl Iterator<Item = (usize, &str)> {
+        self.lines
+            .iter()
+            .map(|(number, span)| (*number, &self.text[span.clone()]))
+    }
+
+    fn is_full(&self) -> bool {
+        self.lines.len() + self.scanned.len() >= BATCH_LINES || self.text.len() >= BATCH_BYTES
+    }
+
+    fn clear(&mut self) {
+        self.text.clear();
+        self.lines.clear();
+        self.scanned.clear();
+        self.broken.clear();
+    }
+
+    fn is_empty(&self) -> bool {
+        self.lines.is_empty() && self.scanned.is_empty() && self.broken.is_empty()
+    }
+}
+
+/// Run the source on its own thread, process its batches by the workers and merge their totals
+///
+/// The processed batches are returned to the source to be filled again.
+/// The totals of a resumed run include the ones of its checkpoint, the checkpoint is updated with every report.
+pub fn pipeline<'a, F>(file: &'a str, processing: &Processing, source: F) -> Summary
+where
+    F: FnOnce(Batcher<'a>) + Send,
+{
+    let workers = processing.pool.current_num_threads();
+    let (batch_sender, batch_receiver) = mpsc::sync_channel(workers * QUEUE_DEPTH);
+    // taken by the worker failing to process a line, the reader stops then
+    let batch_receiver = Mutex::new(Some(batch_receiver));
+    let (recycle_sender, recycle_receiver) = mpsc::channel();
+    let (part_sender, part_receiver) = mpsc::channel();
+
+    let resumed = processing.resume.as_ref();
+    let start = resumed.map_or_else(Position::default, |checkpoint| checkpoint.position);
+
+    thread::scope(|scope| {
+        let reader = scope.spawn(move || {
+            source(Batcher {
+                file,
+                batches: batch_sender,
+                recycled: recycle_receiver,
+                batch: Batch::with_capacity(),
+                number: start.line,
+                offset: start.offset,
+                sequence: 0,
+                lines: processing.lines.clone(),
+            });
+        });
+
+        for _ in 0..workers {
+            let (batch_receiver, recycle_sender, part_sender) =
+                (&batch_receiver, recycle_sender.clone(), part_sender.clone());
+            scope.spawn(move || {
+                let mut cache = processing.line_cache();
+
+                loop {
+                    let batch = batch_receiver
+                        .lock()
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .as_ref()
+                        .map(Receiver::recv);
+                    let Some(Ok(mut batch)) = batch else {
+                        // the reader is done or another worker failed
+                        break;
+                    };
+
+                    let mut part = processing.summary();
+                    if let Err(e) = processing.process_batch(file, &mut batch, &mut part, &mut cache) {
+                        processing.fail(e);
+                        batch_receiver.lock().unwrap_or_else(PoisonError::into_inner).take();
+                        break;
+                    }
+
+                    let (sequence, end) = (batch.sequence, batch.end);
+                    batch.clear();
+                    // the reader may be done already
+                    let _ = recycle_sender.send(batch);
+
+                    if part_sender.send((sequence, end, part)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop((recycle_sender, part_sender));
+
+        let mut timestamp = Instant::now();
+        let mut summary = resumed.map_or_else(Summary::default, |checkpoint| checkpoint.summary.clone());
+        let mut progress = processing.checkpoint.as_ref().map(|_| Progress::new(resumed));
+
+        loop {
+            // the parts are awaited shortly, a requested report doesn't wait for the next part
+            match part_receiver.recv_timeout(SIGNAL_CHECK_INTERVAL) {
+                Ok((sequence, end, part)) => {
+                    summary.merge(&part);
+                    if let Some(progress) = &mut progress {
+                        progress.add(sequence, end, part);
+                    }
+                },
+                Err(RecvTimeoutError::Timeout) => {},
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+            let shown = processing.show_totals(&summary);
+
+            if signals::progress_requested() || timestamp.elapsed() > REPORT_DELAY {
+                timestamp = Instant::now();
+                // the dashboard shows the totals already
+                if !shown {
+                    message!("{:?} {summary}", SystemTime::now().duration_since(UNIX_EPOCH));
+                }
+                if let Some(metrics) = &processing.metrics {
+                    metrics.export(file, &summary);
+                }
+                processing.save_checkpoint(file, progress.as_ref());
+            }
+        }
+
+        reader.join().unwrap_or_else(|e| panic::resume_unwind(e));
+
+        // a completed run doesn't need its checkpoint any more
+        if signals::interrupted() {
+            processing.save_checkpoint(file, progress.as_ref());
+        } else if let (Some(path), None) = (&processing.checkpoint, processing.failure()) {
+            let _ = fs::remove_file(path);
+        }
+
+        summary
+    })
+}
+
+/// Collects the lines of a source into batches for the workers
+///
+/// A line is read by `read_line` into [`Batcher::buffer`] and then recorded by [`Batcher::push`].
+pub struct Batcher<'a> {
+    file: &'a str,
+    batches: SyncSender<Batch>,
+    recycled: Receiver<Batch>,
+    batch: Batch,
+    /// the number of the next line
+    number: usize,
+    /// of the decoded input after the last line, known to the sources which report [`Batcher::consumed`]
+    offset: u64,
+    /// of the next sent batch
+    sequence: u64,
+    /// the numbers of the lines to send, the reading stops after them
+    lines: Range<usize>,
+}
+
+impl Batcher<'_> {
+    /// The buffer to append the next line to
+    pub fn buffer(&mut self) -> &mut String {
+        &mut self.batch.text
+    }
+
+    /// Record the line appended by `read_line` with its result,
+    /// `false` at the end of the input or when the workers are gone and the reading should stop
+    ///
+    /// The line is the same as [`BufRead::lines`] gives: without `\n` or `\r\n`.
+    pub fn push(&mut self, start: usize, read_result: io::Result<usize>) -> bool {
+        self.record(start, read_result.map(|read| (read != 0).then_some(ReadLine::Appended)))
+    }
+
+    /// Record the value of a line scanned while it was read instead of appended, see [`Batcher::push`]
+    pub fn push_scanned(&mut self, scanned: io::Result<Result<LineValue, LineError>>) -> bool {
+        let start = self.batch.text.len();
+        self.record(start, scanned.map(|result| Some(ReadLine::Scanned(result))))
+    }
+
+    /// `None` is the end of the input
+    fn record(&mut self, start: usize, read_result: io::Result<Option<ReadLine>>) -> bool {
+        if self.number >= self.lines.end || signals::interrupted() {
+            self.batch.text.truncate(start);
+            return false;
+        }
+
+        match read_result {
+            Ok(None) => return false,
+            _ if self.number < self.lines.start => self.batch.text.truncate(start),
+            Ok(Some(ReadLine::Scanned(result))) => self.batch.scanned.push((self.number, result)),
+            Ok(Some(ReadLine::Appended)) => {
+                let mut line = &self.batch.text[start..];
+                if let Some(stripped) = line.strip_suffix('\n') {
+                    line = stripped.strip_suffix('\r').unwrap_or(stripped);
+                }
+
+                self.batch.lines.push((self.number, start..start + line.len()));
+            },
+            // e.g. a truncated compressed file, the following lines can't be read either
+            Err(e) if e.kind() != io::ErrorKind::InvalidData => {
+                message!("Fail to read file {} at line number {}: {e:?}", self.file, self.number);
+                return false;
+            },
+            Err(e) => {
+                self.batch.broken.push(self.number);
+                message!("File {} broken line(number {}): {e:?}", self.file, self.number);
+                // nothing is appended on invalid UTF-8, anything else is dropped
+                self.batch.text.truncate(start);
+            },
+        }
+
+        self.number += 1;
+
+        if self.number >= self.lines.end {
+            return false;
+        }
+        if !self.batch.is_full() {
+            return true;
+        }
+
+        self.flush()
+    }
+
+    /// Record the offset of the decoded input after the last read line
+    pub fn consumed(&mut self, offset: u64) {
+        self.offset = offset;
+    }
+
+    /// Send the lines collected so far, e.g. before waiting for more input, `false` when the workers are gone
+    pub fn flush(&mut self) -> bool {
+        if self.batch.is_empty() {
+            return true;
+        }
+
+        let empty = self.recycled.try_recv().unwrap_or_else(|_| Batch::with_capacity());
+        let batch = mem::replace(&mut self.batch, empty);
+        self.send(batch)
+    }
+
+    /// Send the last batch
+    pub fn finish(mut self) {
+        if !self.batch.is_empty() {
+            let batch = mem::replace(&mut self.batch, Batch::with_capacity());
+            self.send(batch);
+        }
+    }
+
+    fn send(&mut self, mut batch: Batch) -> bool {
+        batch.sequence = self.sequence;
+        batch.end = Position {
+            offset: self.offset,
+            line: self.number,
+        };
+        self.sequence += 1;
+
+        self.batches.send(batch).is_ok()
+    }
+}
+
+/// How a line was read by a source
+enum ReadLine {
+    /// to the buffer of the batch
+    Appended,
+    /// by a [`StreamScanner`], only its value is kept
+    Scanned(Result<LineValue, LineError>),
+}
+
+/// Read the numbered lines until the end of the input or until the workers are gone
+///
+/// `logical_bytes` counts the bytes read into the reader, the ones not in its buffer are consumed.
+fn read_batches<R: Read>(reader: &mut BufReader<R>, mut collector: Batcher, logical_bytes: &ByteCounter) {
+    loop {
+        let buffer = collector.buffer();
+        let start = buffer.len();
+        let read_result = reader.read_line(buffer);
+        collector.consumed(logical_bytes.get() - reader.buffer().len() as u64);
+
+        if !collector.push(start, read_result) {
+            break;
+        }
+    }
+
+    collector.finish();
+}
+
+/// [`read_batches`] of the records of `--record-sep`
+fn read_records<R: BufRead>(records: &mut Records<R>, mut collector: Batcher, logical_bytes: &ByteCounter) {
+    loop {
+        let record = records.next();
+        collector.consumed(logical_bytes.get() - records.pending() as u64);
+
+        let buffer = collector.buffer();
+        let start = buffer.len();
+        let read_result = match record.map(records::text) {
+            Some(Ok(text)) => {
+                buffer.push_str(&text);
+                // the separator was read too, an empty record isn't the end of the input
+                Ok(text.len() + 1)
+            },
+            Some(Err(e)) => Err(e),
+            None => Ok(0),
+        };
+
+        if !collector.push(start, read_result) {
+            break;
+        }
+    }
+
+    collector.finish();
+}
+
+/// [`read_batches`] of the lines up to `limit` bytes, the longer ones are scanned while they're read
+fn read_bounded<R: Read>(
+    reader: &mut BufReader<R>,
+    mut collector: Batcher,
+    logical_bytes: &ByteCounter,
+    limit: usize,
+    processing: &Processing,
+) {
+    let mut bytes = Vec::new();
+    loop {
+        bytes.clear();
+        let read_result = (&mut *reader).take(limit as u64 + 1).read_until(b'\n', &mut bytes);
+
+        let more = match read_result {
+            Ok(read) if read > limit && !bytes.ends_with(b"\n") => {
+                let scanned = scan_line(reader, &bytes, processing.rules().options.clone());
+                collector.consumed(logical_bytes.get() - reader.buffer().len() as u64);
+                collector.push_scanned(scanned)
+            },
+            read_result => {
+                collector.consumed(logical_bytes.get() - reader.buffer().len() as u64);
+                let buffer = collector.buffer();
+                let start = buffer.len();
+                let read_result = read_result.and_then(|read| match std::str::from_utf8(&bytes) {
+                    Ok(text) => {
+                        buffer.push_str(text);
+                        Ok(read)
+                    },
+                    Err(e) => Err(io::Error::new(io::ErrorKind::InvalidData, e)),
+                });
+                collector.push(start, read_result)
+            },
+        };
+        if !more {
+            break;
+        }
+    }
+
+    collector.finish();
+}
+
+/// The value of the line beginning with `head`, its rest is read and scanned in chunks up to its newline
+fn scan_line<R: Read>(
+    reader: &mut BufReader<R>,
+    head: &[u8],
+    options: ScanOptions,
+) -> io::Result<Result<LineValue, LineError>> {
+    let mut scanner = StreamScanner::new(options);
+    let mut valid = scanner.feed(head);
+
+    loop {
+        let available = match reader.fill_buf() {
+            Ok(available) => available,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        let (chunk, ended) = match available.iter().position(|&byte| byte == b'\n') {
+            Some(newline) => (&available[..newline], true),
+            None => (available, available.is_empty()),
+        };
+        // the rest of an invalid line is read still, the next line begins after it
+        if valid.is_ok() {
+            valid = scanner.feed(chunk);
+        }
+        let used = chunk.len() + usize::from(ended && !available.is_empty());
+        reader.consume(used);
+
+        if ended {
+            break;
+        }
+    }
+
+    valid
+        .and_then(|()| scanner.end())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    Ok(scanner.value())
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Write;
+
+    use clap::Parser;
+    use flate2::{write::GzEncoder, Compression as Level};
+
+    use super::*;
+    use crate::mmap::{self, sum_mapped};
+    use crate::testing::{temp_dir, temp_file};
+
+    #[test]
+    fn test_threads() {
+        let lines = [
+            "two1nine",
+            "",
+            "abcdefg",
+            "xtwone3four",
+            "4nineeightseven2",
+            "7pqrstsixteen",
+        ];
+        let text = lines.map(|line| format!("{line}\n")).concat().repeat(BATCH_LINES / 2);
+
+        let path = temp_file(".txt", text);
+        let file = path.to_str().unwrap();
+
+        let summaries: Vec<_> = ["1", "2", "4"]
+            .into_iter()
+            .map(|threads| {
+                let processing =
+                    Processing::new(&Args::parse_from(["find_digits", "--file", file, "--threads", threads]));
+                sum_file(file, &processing.unwrap(), &ByteCounter::default())
+            })
+            .collect();
+
+        assert_eq!(summaries[0].parsed_lines, 3 * BATCH_LINES as u64);
+        assert!(summaries.iter().all(|summary| *summary == summaries[0]));
+    }
+
+    #[test]
+    fn test_lines() {
+        let text = b"two1nine\r\n\r\nab\rc1\n\xFF7\nx\xC3\xA9\n\n4nine\r\neightseven2";
+        let path = temp_file(".txt", text);
+        let file = path.to_str().unwrap();
+
+        let processing = Processing::new(&Args::parse_from(["find_digits", "--file", file])).unwrap();
+
+        // the lines as `BufRead::lines` gives them
+        let mut expected = Summary::default();
+        for (number, line) in (&text[..]).lines().enumerate() {
+            expected.parsed_lines += 1;
+            if let Ok(line) = line {
+                processing
+                    .process_line(file, number, &line, &mut expected, &mut processing.line_cache())
+                    .unwrap();
+            }
+        }
+
+        let summary = sum_file(file, &processing, &ByteCounter::default());
+        assert_eq!((summary.parsed_lines, summary.incorrect_lines, summary.total_sum), (8, 3, 29 + 11 + 49 + 82));
+        assert_eq!(
+            Summary {
+                input_bytes: ByteCount::default(),
+                ..summary
+            },
+            expected
+        );
+    }
+
+    #[test]
+    fn test_record_sep() {
+        let path = temp_file(".txt", b"two\n1nine\0\0\xFF7\0x\ny\n\n  \neight\n");
+        let file = path.to_str().unwrap();
+
+        let arguments = [
+            vec!["-z"],
+            vec!["--record-sep", "/\\n\\s*\\n/"],
+            vec!["--record-sep", "\\0", "--dedup"],
+        ];
+        let expected = [(4, 1, 29 + 88), (2, 0, 88), (4, 1, 29 + 88)];
+
+        for (pos, arguments) in arguments.iter().enumerate() {
+            let args = [&["find_digits", "--file", file][..], arguments].concat();
+            let processing = Processing::new(&Args::parse_from(args)).unwrap();
+            let summary = sum_file(file, &processing, &ByteCounter::default());
+            assert_eq!(
+                (summary.parsed_lines, summary.incorrect_lines, summary.total_sum),
+                expected[pos],
+                "{arguments:?}"
+            );
+
+            let summary = Summary {
+                input_bytes: ByteCount::default(),
+                ..summary
+            };
+            assert_eq!(sum_sequential(file, &processing).unwrap(), summary, "{arguments:?}");
+        }
+    }
+
+    #[test]
+    fn test_max_line_bytes() {
+        let long = format!("{}7{}three", "x".repeat(100), "é".repeat(50));
+        let text = format!("two1nine\n{long}\r\n{}\n12\nnine{}2", "abc".repeat(20), "a".repeat(30));
+        let path = temp_file(".txt", [text.as_bytes(), b"\n\xFF", &[b'x'; 40], b"\n"].concat());
+        let file = path.to_str().unwrap();
+
+        let arguments = [
+            vec![],
+            vec!["--max-line-bytes", "16", "--buffer-size", "16"],
+            vec!["--max-line-bytes", "16", "--lines", "1:4"],
+        ];
+        let expected = [(6, 1, 29 + 73 + 12 + 92), (6, 1, 29 + 73 + 12 + 92), (3, 1, 73 + 12)];
+
+        for (pos, arguments) in arguments.iter().enumerate() {
+            let args = [&["find_digits", "--file", file][..], arguments].concat();
+            let processing = Processing::new(&Args::parse_from(args)).unwrap();
+            let summary = sum_file(file, &processing, &ByteCounter::default());
+            assert_eq!(
+                (summary.parsed_lines, summary.incorrect_lines, summary.total_sum),
+                expected[pos],
+                "{arguments:?}"
+            );
+        }
+
+        assert!(Processing::new(&Args::parse_from(["find_digits", "--max-line-bytes", "0"])).is_err());
+    }
+
+    #[test]
+    fn test_on_no_digits() {
+        let path = temp_file(".txt", "two1nine\nabc\n\n7\n\r\n");
+        let file = path.to_str().unwrap();
+
+        let arguments = [
+            vec!["--on-no-digits", "skip"],
+            vec!["--on-no-digits", "zero"],
+            vec!["--ignore-empty"],
+            vec!["--on-no-digits", "zero", "--ignore-empty"],
+        ];
+        let expected = [(5, 3, 2, 2), (5, 2, 2, 3), (5, 1, 2, 2), (5, 0, 2, 3)];
+
+        for (pos, arguments) in arguments.iter().enumerate() {
+            let args = [&["find_digits", "--file", file][..], arguments].concat();
+            let processing = Processing::new(&Args::parse_from(args)).unwrap();
+            let summary = sum_file(file, &processing, &ByteCounter::default());
+            assert_eq!(
+                (summary.parsed_lines, summary.incorrect_lines, summary.empty_lines, summary.stats.count()),
+                expected[pos],
+                "{arguments:?}"
+            );
+            assert_eq!(summary.total_sum, 106);
+        }
+
+        // the workers stop, the run fails by the first line without digits
+        let args = Args::parse_from(["find_digits", "--file", file, "--on-no-digits", "error"]);
+        let processing = Processing::new(&args).unwrap();
+        sum_file(file, &processing, &ByteCounter::default());
+        let failure = format!("file {file} line number 1 has no digits, line: \"abc\"");
+        assert_eq!(processing.failure(), Some(failure.as_str()));
+        let mut cache = processing.line_cache();
+        assert!(processing
+            .process_line(file, 0, "abc", &mut processing.summary(), &mut cache)
+            .is_err());
+        assert_eq!(processing.process_line(file, 0, "7", &mut processing.summary(), &mut cache), Ok(Some(77)));
+    }
+
+    #[test]
+    fn test_rejects() {
+        let (path, rejects) = (temp_file(".txt", ""), temp_file(".txt", ""));
+        let file = path.to_str().unwrap();
+
+        let inputs: [&[u8]; 2] = [
+            b"two1nine\nabc\n\n\xFF7\n",
+            b"{\"text\": \"two1nine\"}\n{\"text\": 7}\n",
+        ];
+        let arguments = [vec![], vec!["--input-format", "ndjson", "--field", "text"]];
+        let expected = [
+            "1\tno_digits\tabc\n2\tempty\t\n3\tinvalid_utf8\n",
+            "1\tinvalid_record\t{\"text\": 7}\n",
+        ];
+
+        for (pos, arguments) in arguments.iter().enumerate() {
+            fs::write(&path, inputs[pos]).unwrap();
+            let args = [
+                &["find_digits", "--file", file, "--rejects", rejects.to_str().unwrap()][..],
+                arguments,
+            ]
+            .concat();
+            let processing = Processing::new(&Args::parse_from(args)).unwrap();
+            sum_file(file, &processing, &ByteCounter::default());
+            processing.commit_output().unwrap();
+            assert_eq!(processing.rejected_bytes(), Some(fs::metadata(&rejects).unwrap().len()));
+
+            let mut lines: Vec<String> = fs::read_to_string(&rejects)
+                .unwrap()
+                .lines()
+                .map(|line| format!("{line}\n"))
+                .collect();
+            lines.sort();
+            assert_eq!(lines.concat(), expected[pos], "{arguments:?}");
+        }
+    }
+
+    #[test]
+    fn test_group_by() {
+        let path = temp_file(".txt", "north,two1nine\nsouth, 7\nnorth,3\nabc,x\n4\n");
+        let file = path.to_str().unwrap();
+
+        let keys = ["1", "/^(north|south),/"];
+        let expected: [&[(&str, u64, u64)]; 2] = [
+            &[("4", 1, 44), ("north", 2, 29 + 33), ("south", 1, 77)],
+            &[("", 1, 44), ("north", 2, 29 + 33), ("south", 1, 77)],
+        ];
+
+        for (pos, key) in keys.into_iter().enumerate() {
+            let processing =
+                Processing::new(&Args::parse_from(["find_digits", "--file", file, "--group-by", key])).unwrap();
+            let summary = sum_file(file, &processing, &ByteCounter::default());
+            assert_eq!(summary.groups.groups(), expected[pos], "{key}");
+            assert_eq!(summary.total_sum, 29 + 77 + 33 + 44);
+        }
+    }
+
+    #[test]
+    fn test_line_filters() {
+        let path = temp_file(".txt", "# two1nine\nab1c2\nabcdefg\n7pqrstsixteen\n# skipped 5\n");
+        let file = path.to_str().unwrap();
+
+        let arguments = [
+            vec![],
+            vec!["--exclude-regex", "^#"],
+            vec!["--include-regex", "[a-z]{5}"],
+            vec!["--include-regex", "[a-z]{5}", "--exclude-regex", "^#"],
+            vec!["--skip-comments", "#"],
+            vec!["--skip-comments", "ab"],
+            vec!["--skip", "1", "--limit", "3"],
+            vec!["--skip", "4"],
+            vec!["--limit", "0"],
+            vec!["--lines", "1:3"],
+        ];
+        let expected = [
+            (0, 1, 29 + 12 + 76 + 55),
+            (2, 1, 12 + 76),
+            (2, 1, 76 + 55),
+            (3, 1, 76),
+            (2, 1, 12 + 76),
+            (2, 0, 29 + 76 + 55),
+            (0, 1, 12 + 76),
+            (0, 0, 55),
+            (0, 0, 0),
+            (0, 1, 12),
+        ];
+
+        for (pos, arguments) in arguments.into_iter().enumerate() {
+            let args = Args::parse_from(["find_digits", "--file", file].into_iter().chain(arguments));
+            let summary = sum_file(file, &Processing::new(&args).unwrap(), &ByteCounter::default());
+
+            assert_eq!(summary.parsed_lines, [5, 5, 5, 5, 5, 5, 3, 1, 0, 2][pos]);
+            // the reading stops after the limit, the input size is of the read part
+            let mapped = sum_mapped(file, &Processing::new(&args).unwrap());
+            assert_eq!(
+                Summary {
+                    input_bytes: mapped.input_bytes,
+                    ..summary.clone()
+                },
+                mapped
+            );
+            assert_eq!((summary.excluded_lines, summary.incorrect_lines, summary.total_sum), expected[pos]);
+        }
+    }
+
+    #[test]
+    fn test_sample() {
+        let path = temp_file(".txt", "two1nine\nabcdefg\n7pqrstsixteen\n".repeat(4000));
+        let file = path.to_str().unwrap();
+
+        let sample = |seed, threads| {
+            let args = Args::parse_from([
+                "find_digits",
+                "--file",
+                file,
+                "--sample",
+                "0.1",
+                "--seed",
+                seed,
+                "--threads",
+                threads,
+            ]);
+            sum_file(file, &Processing::new(&args).unwrap(), &ByteCounter::default())
+        };
+
+        let summary = sample("42", "1");
+        assert_eq!(summary, sample("42", "4"));
+        assert_ne!(summary, sample("7", "1"));
+
+        let (rate, estimate) = summary.sample_estimate().unwrap();
+        assert!((0.09..0.11).contains(&rate), "{rate}");
+        assert!((400_000.0..440_000.0).contains(&estimate), "{estimate}");
+
+        let args = Args::parse_from(["find_digits", "--file", file, "--every", "4"]);
+        let summary = sum_file(file, &Processing::new(&args).unwrap(), &ByteCounter::default());
+        assert_eq!(
+            (summary.unsampled_lines, summary.incorrect_lines, summary.total_sum),
+            (9000, 1000, 1000 * (29 + 76))
+        );
+    }
+
+    #[test]
+    fn test_dedup() {
+        let path = temp_file(".txt", "two1nine\nabcdefg\nlO\n7pqrstsixteen\n".repeat(BATCH_LINES));
+        let file = path.to_str().unwrap();
+
+        for arguments in [
+            vec![],
+            vec!["--confusables", "stats", "--token-frequency", "--top", "3"],
+        ] {
+            let summary = |dedup: &[&str]| {
+                let args = ["find_digits", "--file", file, "--threads", "2"];
+                let args = args.iter().chain(dedup).chain(&arguments);
+                let processing = Processing::new(&Args::parse_from(args)).unwrap();
+                (sum_file(file, &processing, &ByteCounter::default()), mmap::sum_mapped(file, &processing))
+            };
+
+            assert_eq!(summary(&["--dedup"]), summary(&[]));
+        }
+    }
+
+    #[test]
+    fn test_reload() {
+        let path = temp_file(".txt", "uno 1\ndos 2\n");
+        let dictionary = path.to_str().unwrap();
+
+        let args = ["find_digits", "--dictionary", dictionary, "--dedup", "serve"];
+        let processing = Processing::new(&Args::parse_from(args)).unwrap();
+        let mut cache = processing.line_cache();
+        let mut summary = processing.summary();
+
+        let lines = ["uno-dos", "# dos-uno"];
+        let values = [[Some(12), Some(21)], [Some(34), None]];
+        for (pos, value) in values.into_iter().enumerate() {
+            if pos > 0 {
+                fs::write(&path, "uno 3\ndos 4\n").unwrap();
+                let args = [
+                    "find_digits",
+                    "--dictionary",
+                    dictionary,
+                    "--skip-comments",
+                    "#",
+                    "serve",
+                ];
+                processing.reload(&Args::parse_from(args)).unwrap();
+            }
+            for (number, line) in lines.into_iter().enumerate() {
+                assert_eq!(
+                    processing.process_line("in.txt", number, line, &mut summary, &mut cache),
+                    Ok(value[number])
+                );
+            }
+        }
+        assert_eq!((summary.total_sum, summary.excluded_lines), (12 + 21 + 34, 1));
+
+        // the previous rules are kept
+        fs::remove_file(&path).unwrap();
+        assert!(processing.reload(&Args::parse_from(args)).is_err());
+        assert_eq!(processing.process_line("in.txt", 0, "uno-dos", &mut summary, &mut cache), Ok(Some(34)));
+    }
+
+    #[test]
+    fn test_resume() {
+        let dir = temp_dir();
+        let (path, checkpoint_path) = (dir.path().join("resume.txt"), dir.path().join("resume.checkpoint"));
+        let lines = "two1nine\nabcdefg\n7pqrstsixteen\n".repeat(BATCH_LINES);
+        let mut text = lines.clone().into_bytes();
+        text.extend_from_slice(b"\xff\n4nineeightseven2\n");
+        fs::write(&path, &text).unwrap();
+        let file = path.to_str().unwrap();
+        let checkpoint_file = checkpoint_path.to_str().unwrap();
+
+        let processing = Processing::new(&Args::parse_from(["find_digits", "--file", file, "--threads", "2"])).unwrap();
+        let expected = sum_file(file, &processing, &ByteCounter::default());
+
+        // the totals of the first lines as saved by an interrupted run
+        let prefix: String = lines.split_inclusive('\n').take(100).collect();
+        let mut summary = processing.summary();
+        for (number, line) in prefix.lines().enumerate() {
+            summary.parsed_lines += 1;
+            processing
+                .process_line(file, number, line, &mut summary, &mut processing.line_cache())
+                .unwrap();
+        }
+        let position = Position {
+            offset: prefix.len() as u64,
+            line: 100,
+        };
+        Checkpoint {
+            file: file.to_owned(),
+            position,
+            summary,
+        }
+        .save(&checkpoint_path)
+        .unwrap();
+
+        let args = [
+            "find_digits",
+            "--file",
+            file,
+            "--threads",
+            "2",
+            "--checkpoint",
+            checkpoint_file,
+            "--resume",
+        ];
+        let processing = Processing::new(&Args::parse_from(args)).unwrap();
+        let resumed = sum_file(file, &processing, &ByteCounter::default());
+
+        assert_eq!(
+            (resumed.parsed_lines, resumed.incorrect_lines, resumed.total_sum, resumed.stats),
+            (expected.parsed_lines, expected.incorrect_lines, expected.total_sum, expected.stats)
+        );
+        // the completed run removes its checkpoint
+        assert_eq!(Checkpoint::load(&checkpoint_path), Ok(None));
+
+        let args = [
+            "find_digits",
+            "--file",
+            "other.txt",
+            "--checkpoint",
+            checkpoint_file,
+            "--resume",
+        ];
+        Checkpoint {
+            file: file.to_owned(),
+            position,
+            summary: Summary::default(),
+        }
+        .save(&checkpoint_path)
+        .unwrap();
+        assert!(Processing::new(&Args::parse_from(args)).is_err());
+    }
+
+    #[test]
+    fn test_ndjson() {
+        let records = [
+            r#"{"payload": {"text": "two1nine"}, "id": 5}"#,
+            r#"{"payload": {"text": "abcdefg"}}"#,
+            r#"{"payload": {}}"#,
+            r#"{"payload": "#,
+            r#"{"payload": {"text": "7pqrstsixteen"}}"#,
+        ];
+        // a Windows editor begins the file with a byte order mark and ends the lines with `\r\n`
+        let path = temp_file(".txt", format!("\u{FEFF}{}\r", records.join("\r\n")));
+        let file = path.to_str().unwrap();
+
+        let args = Args::parse_from([
+            "find_digits",
+            "--file",
+            file,
+            "--input-format",
+            "ndjson",
+            "--field",
+            "payload.text",
+        ]);
+        let summary = sum_file(file, &Processing::new(&args).unwrap(), &ByteCounter::default());
+        assert_eq!((summary.parsed_lines, summary.incorrect_lines, summary.total_sum), (5, 3, 29 + 76));
+
+        assert!(Args::try_parse_from(["find_digits", "--file", file, "--input-format", "ndjson"]).is_err());
+    }
+
+    #[test]
+    fn test_locate_overflow() {
+        let path = temp_file(".txt", "two1nine\nabcdefg\n7pqrstsixteen\n4nineeightseven2\n");
+        let file = path.to_str().unwrap();
+
+        let processing = Processing::new(&Args::parse_from(["find_digits", "--file", file])).unwrap();
+        let near = |margin| Summary {
+            total_sum: u64::MAX - margin,
+            ..Summary::default()
+        };
+
+        assert_eq!(locate_overflow(file, &processing, near(29 + 76)), Some(3));
+        assert_eq!(locate_overflow(file, &processing, near(29)), Some(2));
+        assert_eq!(locate_overflow(file, &processing, near(29 + 76 + 42)), None);
+
+        // the compressed lines are decoded first, the rejected line isn't rejected again
+        let mut encoder = GzEncoder::new(Vec::new(), Level::default());
+        encoder
+            .write_all(b"two1nine\nabcdefg\n7pqrstsixteen\n4nineeightseven2\n")
+            .unwrap();
+        let compressed = temp_file(".gz", encoder.finish().unwrap());
+        let (file, rejects) = (compressed.to_str().unwrap(), temp_file(".txt", ""));
+        let args = ["find_digits", "--file", file, "--rejects", rejects.to_str().unwrap()];
+        let processing = Processing::new(&Args::parse_from(args)).unwrap();
+
+        assert_eq!(locate_overflow(file, &processing, near(29 + 76)), Some(3));
+        assert_eq!(processing.rejected_bytes(), Some(0));
+    }
+
+    #[test]
+    fn test_self_check() {
+        let text = b"two1nine\r\n\r\nab\rc1\n\xFF7\nx\xC3\xA9\n4nine\r\n".repeat(BATCH_LINES / 3);
+        let path = temp_file(".txt", text);
+        let file = path.to_str().unwrap();
+
+        let presets = [
+            ["--threads", "4", "--dedup", "--verify"],
+            ["--threads", "3", "--mmap", "--paranoid"],
+            ["--skip", "100", "--limit", "5000"],
+        ];
+        for preset in presets {
+            let args = Args::parse_from(
+                ["find_digits", "--file", file, "--self-check"]
+                    .into_iter()
+                    .chain(preset),
+            );
+            let processing = Processing::new(&args).unwrap();
+            let summary = if args.mmap {
+                sum_mapped(file, &processing)
+            } else {
+                sum_file(file, &processing, &ByteCounter::default())
+            };
+            assert_eq!(self_check(file, &processing, &summary), Ok(()), "{preset:?}");
+
+            // a line lost by the parallel processing
+            let summary = if args.mmap {
+                sum_mapped(file, &processing)
+            } else {
+                sum_file(file, &processing, &ByteCounter::default())
+            };
+            processing.line_values.as_ref().unwrap().lock().unwrap().values[101] = LineValues::UNSEEN;
+            let error = self_check(file, &processing, &summary).unwrap_err();
+            assert!(error.ends_with("line number 101: not processed in parallel, 49 sequentially"), "{error}");
+        }
+    }
+}
@@ -0,0 +1,142 @@
+//! The `find_digits` Python module, built by `maturin build --release` (see `pyproject.toml`)
+
+use std::{
+    fs::File,
+    io::{self, BufReader},
+    path::{Path, PathBuf},
+};
+
+use pyo3::{
+    exceptions::PyOSError,
+    pyclass, pyfunction, pymethods, pymodule,
+    types::{PyModule, PyModuleMethods},
+    wrap_pyfunction, Bound, PyResult, Python,
+};
+
+use crate::{lines, matches_with, MatchKind, ScanOptions};
+
+/// The totals of a file
+#[pyclass(frozen, get_all)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Totals {
+    pub parsed_lines: u64,
+    /// the lines without digits, the lines which aren't UTF-8 are neither summed nor incorrect
+    pub incorrect_lines: u64,
+    pub total_sum: u64,
+}
+
+#[pymethods]
+impl Totals {
+    fn __repr__(&self) -> String {
+        format!(
+            "Totals(parsed_lines={}, incorrect_lines={}, total_sum={})",
+            self.parsed_lines, self.incorrect_lines, self.total_sum
+        )
+    }
+}
+
+/// The calibration value of the line, `None` without digits
+#[pyfunction]
+#[must_use]
+pub fn extract_number(line: &str) -> Option<u32> {
+    crate::extract_number(line)
+}
+
+/// `(digit, start, end, kind)` of every digit of the line, `line[start:end]` is the matched text
+///
+/// The offsets are of the characters of the string, as Python indexes it, not of its UTF-8 bytes.
+#[pyfunction]
+#[must_use]
+pub fn scan(line: &str) -> Vec<(u8, usize, usize, &'static str)> {
+    let chars = |position: usize| line[..position].chars().count();
+
+    matches_with(line, &ScanOptions::default())
+        .map(|m| {
+            let kind = match m.kind {
+                MatchKind::Digit => "digit",
+                MatchKind::Word => "word",
+            };
+            (m.value(), chars(m.start), chars(m.end), kind)
+        })
+        .collect()
+}
+
+/// The totals of the lines of the file, the other Python threads run meanwhile
+///
+/// # Errors
+///
+/// `OSError` when the file fails to be read
+#[pyfunction]
+// the path is extracted from a Python `str` or `os.PathLike` as an owned one
+#[allow(clippy::needless_pass_by_value)]
+pub fn sum_file(py: Python<'_>, path: PathBuf) -> PyResult<Totals> {
+    py.allow_threads(|| totals(&path))
+        .map_err(|e| PyOSError::new_err(format!("Fail to read file {}: {e}", path.display())))
+}
+
+fn totals(path: &Path) -> io::Result<Totals> {
+    let mut totals = Totals::default();
+
+    for result in lines(BufReader::new(File::open(path)?)) {
+        totals.parsed_lines += 1;
+        match result {
+            Ok(line) => match line.value {
+                Ok(value) => totals.total_sum += u64::from(value.value),
+                Err(_) => totals.incorrect_lines += 1,
+            },
+            Err(e) if e.kind() == io::ErrorKind::InvalidData => {},
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(totals)
+}
+
+#[pymodule]
+fn find_digits(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_function(wrap_pyfunction!(extract_number, module)?)?;
+    module.add_function(wrap_pyfunction!(scan, module)?)?;
+    module.add_function(wrap_pyfunction!(sum_file, module)?)?;
+    module.add_class::<Totals>()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::temp_file;
+
+    #[test]
+    fn test_scan() {
+        let strings = ["xtwone3four", "caf\u{e9}one7", ""];
+        let expected = [
+            vec![
+                (2, 1, 4, "word"),
+                (1, 3, 6, "word"),
+                (3, 6, 7, "digit"),
+                (4, 7, 11, "word"),
+            ],
+            vec![(1, 4, 7, "word"), (7, 7, 8, "digit")],
+            vec![],
+        ];
+
+        for (pos, line) in strings.into_iter().enumerate() {
+            assert_eq!(scan(line), expected[pos], "{line}");
+        }
+    }
+
+    #[test]
+    fn test_totals() {
+        let path = temp_file(".txt", b"two1nine\r\nabcdefg\n\xFF7\ntreb7uchet");
+
+        assert_eq!(
+            totals(&path).unwrap(),
+            Totals {
+                parsed_lines: 4,
+                incorrect_lines: 1,
+                total_sum: 29 + 77,
+            }
+        );
+        assert!(totals(&path.with_extension("missing")).is_err());
+    }
+}
@@ -0,0 +1,190 @@
+//! Records ended by `--record-sep` instead of newlines, e.g. NUL-separated paths or blank-line paragraphs
+
+use std::{
+    io::{self, BufRead},
+    mem,
+    ops::Range,
+};
+
+use regex::bytes::Regex;
+
+/// A match of a pattern is final once this many bytes follow it, e.g. `\n\s*\n` could be longer with more spaces
+const LOOKAHEAD: usize = 4096;
+
+/// What ends a record
+#[derive(Debug, Clone)]
+pub enum RecordSep {
+    Literal(Vec<u8>),
+    Pattern(Regex),
+}
+
+impl RecordSep {
+    /// `/<regex>/` is a regular expression, anything else a literal with the escapes `\0`, `\n`, `\r`, `\t` and `\\`
+    pub fn parse(value: &str) -> Result<Self, String> {
+        if let Some(pattern) = value.strip_prefix('/').and_then(|rest| rest.strip_suffix('/')) {
+            let regex = Regex::new(pattern).map_err(|e| format!("incorrect record separator {value}: {e}"))?;
+            if regex.is_match(b"") {
+                return Err(format!("record separator {value} matches an empty text"));
+            }
+            return Ok(Self::Pattern(regex));
+        }
+
+        let mut literal = Vec::new();
+        let mut chars = value.chars();
+        while let Some(c) = chars.next() {
+            let c = match (c, chars.clone().next()) {
+                ('\\', Some(escaped @ ('0' | 'n' | 'r' | 't' | '\\'))) => {
+                    chars.next();
+                    match escaped {
+                        '0' => '\0',
+                        'n' => '\n',
+                        'r' => '\r',
+                        't' => '\t',
+                        _ => '\\',
+                    }
+                },
+                _ => c,
+            };
+            literal.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+        }
+
+        if literal.is_empty() {
+            return Err("the record separator must not be empty".to_owned());
+        }
+        Ok(Self::Literal(literal))
+    }
+
+    /// The first separator of `text` beginning at `from` or later
+    fn find(&self, text: &[u8], from: usize) -> Option<Range<usize>> {
+        match self {
+            Self::Literal(literal) => text[from..]
+                .windows(literal.len())
+                .position(|window| window == literal)
+                .map(|start| from + start..from + start + literal.len()),
+            Self::Pattern(regex) => regex.find_at(text, from).map(|m| m.range()),
+        }
+    }
+
+    /// The separator found in `text` stays the same once more is read
+    fn is_final(&self, separator: &Range<usize>, text: &[u8]) -> bool {
+        match self {
+            Self::Literal(_) => true,
+            Self::Pattern(_) => separator.end + LOOKAHEAD <= text.len(),
+        }
+    }
+
+    /// Where the search continues once more is read after `text`, a separator may be in its end
+    fn resume(&self, text: &[u8]) -> usize {
+        match self {
+            Self::Literal(literal) => text.len().saturating_sub(literal.len()),
+            Self::Pattern(_) => 0,
+        }
+    }
+}
+
+/// The records of a reader without their separators, the last one may be unterminated
+pub struct Records<R> {
+    reader: R,
+    separator: RecordSep,
+    /// the read bytes which aren't returned yet
+    pending: Vec<u8>,
+    /// the beginning of the search of the next separator in `pending`
+    searched: usize,
+    end: bool,
+}
+
+impl<R: BufRead> Records<R> {
+    pub fn new(reader: R, separator: RecordSep) -> Self {
+        Self {
+            reader,
+            separator,
+            pending: Vec::new(),
+            searched: 0,
+            end: false,
+        }
+    }
+
+    /// The bytes read from the reader which aren't returned yet, the ones its buffer could hold are all taken
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+}
+
+impl<R: BufRead> Iterator for Records<R> {
+    type Item = io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.separator.find(&self.pending, self.searched) {
+                Some(separator) if self.end || self.separator.is_final(&separator, &self.pending) => {
+                    let rest = self.pending.split_off(separator.end);
+                    let mut record = mem::replace(&mut self.pending, rest);
+                    record.truncate(separator.start);
+                    self.searched = 0;
+                    return Some(Ok(record));
+                },
+                _ if self.end => {
+                    self.searched = 0;
+                    return (!self.pending.is_empty()).then(|| Ok(mem::take(&mut self.pending)));
+                },
+                _ => {},
+            }
+
+            let read = match self.reader.fill_buf() {
+                Ok(read) => read,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Some(Err(e)),
+            };
+            if read.is_empty() {
+                self.end = true;
+                continue;
+            }
+
+            self.searched = self.separator.resume(&self.pending);
+            self.pending.extend_from_slice(read);
+            let length = read.len();
+            self.reader.consume(length);
+        }
+    }
+}
+
+/// The text of a record, invalid UTF-8 is an error of the kind `InvalidData` like the one of `BufRead::lines`
+pub fn text(record: io::Result<Vec<u8>>) -> io::Result<String> {
+    String::from_utf8(record?).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_records() {
+        let separators = ["\\0", "--", "/\\n\\s*\\n/", "x"];
+        let inputs: [&[u8]; 4] = [
+            b"two1nine\0\0abc\0",
+            b"one-two--three----four-",
+            b"one\ntwo\n\n  \n\nthree\n\n",
+            b"",
+        ];
+        let expected: [&[&str]; 4] = [
+            &["two1nine", "", "abc"],
+            &["one-two", "three", "", "four-"],
+            &["one\ntwo", "three"],
+            &[],
+        ];
+
+        for (pos, separator) in separators.into_iter().enumerate() {
+            // a tiny buffer splits the separators between the reads
+            let reader = io::BufReader::with_capacity(1, inputs[pos]);
+            let records: Vec<String> = Records::new(reader, RecordSep::parse(separator).unwrap())
+                .map(|record| text(record).unwrap())
+                .collect();
+            assert_eq!(records, expected[pos], "{separator}");
+        }
+
+        assert!(RecordSep::parse("").is_err());
+        assert!(RecordSep::parse("/\\n*/").is_err());
+        assert!(RecordSep::parse("/(/").is_err());
+        assert!(matches!(RecordSep::parse("\\t\\x"), Ok(RecordSep::Literal(literal)) if literal == b"\t\\x"));
+    }
+}
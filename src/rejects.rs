@@ -0,0 +1,111 @@
+//! The `--rejects` file: every line without a value with its number and why, to audit the input
+
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::{Mutex, PoisonError},
+};
+
+use find_digits::{
+    counting::{ByteCounter, CountingWriter},
+    LineError,
+};
+
+/// Why a line has no value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reason {
+    Empty,
+    NoDigits,
+    /// the `--field` of the JSON record is missing or isn't a string
+    InvalidRecord,
+    InvalidUtf8,
+}
+
+impl Reason {
+    /// The code written to the file
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::Empty => "empty",
+            Self::NoDigits => "no_digits",
+            Self::InvalidRecord => "invalid_record",
+            Self::InvalidUtf8 => "invalid_utf8",
+        }
+    }
+}
+
+impl From<LineError> for Reason {
+    fn from(error: LineError) -> Self {
+        match error {
+            LineError::Empty => Self::Empty,
+            LineError::NoDigits => Self::NoDigits,
+        }
+    }
+}
+
+/// The rejected lines written as they're found, in the order of the workers
+pub struct Rejects {
+    path: PathBuf,
+    writer: Mutex<CountingWriter<BufWriter<File>>>,
+    written: ByteCounter,
+}
+
+impl Rejects {
+    pub fn create(path: &Path) -> Result<Self, String> {
+        let file = File::create(path).map_err(|e| format!("Fail to create rejects file {}: {e:?}", path.display()))?;
+        let (writer, written) = CountingWriter::new(BufWriter::new(file));
+        Ok(Self {
+            path: path.to_owned(),
+            writer: Mutex::new(writer),
+            written,
+        })
+    }
+
+    /// Bytes of the rejected lines written so far, the buffered ones included
+    pub fn written(&self) -> u64 {
+        self.written.get()
+    }
+
+    /// `line` is none of a line whose text isn't known, e.g. invalid UTF-8
+    pub fn add(&self, number: usize, reason: Reason, line: Option<&str>) -> Result<(), String> {
+        let mut writer = self.writer.lock().unwrap_or_else(PoisonError::into_inner);
+        writer
+            .write_all(render(number, reason, line).as_bytes())
+            .map_err(|e| format!("Fail to write rejects file {}: {e:?}", self.path.display()))
+    }
+
+    pub fn flush(&self) -> Result<(), String> {
+        let mut writer = self.writer.lock().unwrap_or_else(PoisonError::into_inner);
+        writer
+            .flush()
+            .map_err(|e| format!("Fail to write rejects file {}: {e:?}", self.path.display()))
+    }
+}
+
+/// `<number>\t<reason>\t<line>`, the line is the last field so its tabs are kept
+fn render(number: usize, reason: Reason, line: Option<&str>) -> String {
+    match line {
+        Some(line) => format!("{number}\t{}\t{line}\n", reason.code()),
+        None => format!("{number}\t{}\n", reason.code()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let rejects = [
+            (3, Reason::NoDigits, Some("abc\tdef")),
+            (4, Reason::Empty, Some("")),
+            (7, Reason::InvalidUtf8, None),
+        ];
+        let expected = ["3\tno_digits\tabc\tdef\n", "4\tempty\t\n", "7\tinvalid_utf8\n"];
+
+        for (pos, (number, reason, line)) in rejects.into_iter().enumerate() {
+            assert_eq!(render(number, reason, line), expected[pos]);
+        }
+        assert_eq!(Reason::from(LineError::NoDigits), Reason::NoDigits);
+    }
+}
@@ -0,0 +1,92 @@
+use std::process::exit;
+
+use find_digits::{counting::ByteCounter, Summary};
+
+use crate::{
+    process::{sum_read, Processing},
+    tee::message,
+};
+
+/// `true` when the file is an HTTP(S) URL
+pub fn is_url(file: &str) -> bool {
+    file.starts_with("http://") || file.starts_with("https://")
+}
+
+/// `true` when the file is an `s3://bucket/key` object
+pub fn is_s3(file: &str) -> bool {
+    file.starts_with("s3://")
+}
+
+/// The same as [`sum_url`] of an S3 object, the storage and the credentials are of the AWS environment variables
+pub fn sum_s3(url: &str, processing: &Processing, physical_bytes: &ByteCounter) -> Summary {
+    #[cfg(feature = "s3")]
+    match crate::s3::get(url, &crate::s3::Config::from_env()) {
+        Ok(body) => sum_read(url, processing, body, physical_bytes),
+        Err(e) => {
+            message!("{e}");
+            exit(1);
+        },
+    }
+
+    #[cfg(not(feature = "s3"))]
+    {
+        let _ = (processing, physical_bytes);
+        message!("S3 input {url} isn't supported by this build, rebuild it with `--features s3`");
+        exit(1);
+    }
+}
+
+/// The same as [`crate::process::sum_file`], the lines of the response body are processed while it's downloaded
+pub fn sum_url(url: &str, processing: &Processing, physical_bytes: &ByteCounter) -> Summary {
+    let response = match ureq::get(url).call() {
+        Ok(response) => response,
+        Err(e) => {
+            message!("Fail to download {url}: {e}");
+            exit(1);
+        },
+    };
+
+    sum_read(url, processing, response.into_body().into_reader(), physical_bytes)
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        thread,
+    };
+
+    use clap::Parser;
+
+    use super::*;
+    use crate::Args;
+
+    #[test]
+    fn test_sum_url() {
+        let body = "two1nine\nabcdefg\n7pqrstsixteen\n".repeat(1000);
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/input.txt", listener.local_addr().unwrap());
+
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut stream = BufReader::new(stream);
+            let mut line = String::new();
+            while stream.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+
+            let stream = stream.get_mut();
+            write!(stream, "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}", body.len())
+                .unwrap();
+        });
+
+        assert!(is_url(&url) && !is_url("input.txt"));
+
+        let processing = Processing::new(&Args::parse_from(["find_digits", "--file", &url])).unwrap();
+        let summary = sum_url(&url, &processing, &ByteCounter::default());
+        assert_eq!((summary.parsed_lines, summary.incorrect_lines, summary.total_sum), (3000, 1000, 1000 * (29 + 76)));
+
+        server.join().unwrap();
+    }
+}
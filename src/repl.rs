@@ -0,0 +1,109 @@
+use std::{
+    fmt::Write as _,
+    io::{self, BufRead, IsTerminal, Write},
+};
+
+use crate::{config, process::Rules, Args};
+
+const HELP: &str = "Every line is printed with its matched tokens, their byte spans and its value.
+  :reload  read the dictionary and the options again, e.g. after editing the dictionary
+  :help    print this help
+  :quit    leave, as Ctrl-D does
+";
+
+/// Explain the lines typed or pasted on stdin until its end or `:quit`, the prompt is printed on a terminal only
+pub fn run(args: &Args) -> Result<(), String> {
+    let stdin = io::stdin();
+    let prompt = stdin.is_terminal();
+    if prompt {
+        println!("Type or paste lines, :help for the commands");
+    }
+
+    session(Rules::new(args)?, stdin.lock(), io::stdout().lock(), prompt, || {
+        config::reload().and_then(|args| Rules::new(&args))
+    })
+}
+
+fn session(
+    mut rules: Rules,
+    mut input: impl BufRead,
+    mut output: impl Write,
+    prompt: bool,
+    reload: impl Fn() -> Result<Rules, String>,
+) -> Result<(), String> {
+    let mut number = 0;
+    let mut line = String::new();
+
+    loop {
+        let mut reply = String::new();
+        if prompt {
+            write_reply(&mut output, "> ")?;
+        }
+
+        line.clear();
+        match input.read_line(&mut line) {
+            Ok(0) => return Ok(()),
+            Ok(_) => {},
+            Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+                write_reply(&mut output, "The line isn't UTF-8\n")?;
+                continue;
+            },
+            Err(e) => return Err(format!("Fail to read stdin: {e}")),
+        }
+        let text = line.strip_suffix('\n').unwrap_or(&line);
+        let text = text.strip_suffix('\r').unwrap_or(text);
+
+        match text.trim() {
+            ":quit" | ":q" => return Ok(()),
+            ":help" => reply.push_str(HELP),
+            ":reload" => match reload() {
+                Ok(reloaded) => {
+                    rules = reloaded;
+                    reply.push_str("Reloaded the dictionary and the options\n");
+                },
+                Err(e) => {
+                    let _ = writeln!(reply, "Fail to reload the options, the previous ones are kept: {e}");
+                },
+            },
+            _ => {
+                reply = rules.explain(number, text);
+                number += 1;
+            },
+        }
+        write_reply(&mut output, &reply)?;
+    }
+}
+
+fn write_reply(output: &mut impl Write, reply: &str) -> Result<(), String> {
+    output
+        .write_all(reply.as_bytes())
+        .and_then(|()| output.flush())
+        .map_err(|e| format!("Fail to write stdout: {e}"))
+}
+
+#[cfg(test)]
+mod test {
+    use clap::Parser;
+
+    use super::*;
+    use crate::testing::temp_file;
+
+    #[test]
+    fn test_session() {
+        let path = temp_file(".txt", "zwei 2\n");
+        let dictionary = path.to_str().unwrap();
+
+        let input = "two1nine\r\nzwei\n:reload\nzwei\n\n :quit \nnine\n";
+        let mut output = Vec::new();
+        let reload = || Rules::new(&Args::parse_from(["find_digits", "--dictionary", dictionary]));
+        session(Rules::new(&Args::parse_from(["find_digits"])).unwrap(), input.as_bytes(), &mut output, false, reload)
+            .unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "line number 0 \"two1nine\": value 29\n  word \"two\" 0..3 = 2 (first)\n  digit \"1\" 3..4 = 1\n  \
+             word \"nine\" 4..8 = 9 (last)\nline number 1 \"zwei\": no digits\nReloaded the dictionary and the options\n\
+             line number 2 \"zwei\": value 22\n  word \"zwei\" 0..4 = 2 (first, last)\nline number 3 \"\": empty\n"
+        );
+    }
+}
@@ -0,0 +1,429 @@
+use std::{fmt::Write as _, time::Duration};
+
+use clap::{Args, ValueEnum};
+use find_digits::{GroupStats, InputEnds, LineStats, MatchKind, Summary, TokenStats, TopLines};
+use serde::{Serialize, Serializer};
+
+/// Width of the histogram buckets of the line values
+const BUCKET_WIDTH: u32 = 10;
+/// Width of the longest histogram bar
+const BAR_WIDTH: u64 = 50;
+
+/// The reports of `stats` besides the summary, none by default
+#[derive(Args, Debug, Clone, Default)]
+pub struct StatsArgs {
+    /// Report the percentiles of the line values, e.g. `50,90,99`
+    #[arg(long, value_delimiter = ',', value_parser = parse_percentile)]
+    pub percentiles: Vec<f64>,
+
+    /// Print the distribution of the line values in buckets of ten, as text or as JSON
+    #[arg(long, value_enum, num_args = 0..=1, default_missing_value = "text")]
+    pub histogram: Option<ReportFormat>,
+
+    /// Print how often every digit was the first and the last digit of a line, as text or as JSON
+    #[arg(long, value_enum, num_args = 0..=1, default_missing_value = "text")]
+    pub digit_frequency: Option<ReportFormat>,
+
+    /// Print how often every token (`7`, `three`, ...) was matched as the first or the last digit
+    #[arg(long, value_enum, num_args = 0..=1, default_missing_value = "text")]
+    pub token_frequency: Option<ReportFormat>,
+
+    /// Print this amount of the lines with the highest values
+    #[arg(long, default_value_t = 0)]
+    pub top: usize,
+
+    /// Print this amount of the lines with the lowest values
+    #[arg(long, default_value_t = 0)]
+    pub bottom: usize,
+
+    /// Print the totals of every input of a run of several ones (e.g. the files of an archive) as text or as JSON
+    #[arg(long, value_enum, default_value = "text")]
+    pub files: ReportFormat,
+}
+
+fn parse_percentile(value: &str) -> Result<f64, String> {
+    match value.trim().parse() {
+        Ok(percentile) if (0.0..=100.0).contains(&percentile) => Ok(percentile),
+        _ => Err(format!("percentile {value} is not a number 0-100")),
+    }
+}
+
+/// How a report is printed
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReportFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+#[derive(Serialize)]
+struct Histogram {
+    histogram: Vec<Bucket>,
+    count: u64,
+}
+
+#[derive(Serialize)]
+struct Bucket {
+    start: u32,
+    end: u32,
+    count: u64,
+}
+
+#[derive(Serialize)]
+struct DigitFrequency {
+    first_digits: [u64; 10],
+    last_digits: [u64; 10],
+}
+
+#[derive(Serialize)]
+struct TokenFrequency<'a> {
+    numeric: u64,
+    spelled: u64,
+    tokens: Vec<TokenCount<'a>>,
+}
+
+#[derive(Serialize)]
+struct TokenCount<'a> {
+    token: &'a str,
+    kind: MatchKind,
+    count: u64,
+}
+
+#[derive(Serialize)]
+struct Files<'a> {
+    files: &'a [FileTotals],
+}
+
+/// A JSON report on its own line
+fn json(report: &impl Serialize) -> String {
+    crate::response::json(report) + "\n"
+}
+
+/// Distribution of the line values in buckets of ten
+pub fn histogram(stats: &LineStats, format: ReportFormat) -> String {
+    let buckets = stats.buckets(BUCKET_WIDTH);
+
+    match format {
+        ReportFormat::Text => {
+            let largest = buckets.iter().map(|&(_, count)| count).max().unwrap_or_default().max(1);
+            let mut report = String::new();
+
+            for (start, count) in buckets {
+                let bar = "#".repeat(usize::try_from(count * BAR_WIDTH / largest).unwrap_or_default());
+                let _ = writeln!(report, "{start:>2}-{:<2} | {bar} {count}", start + BUCKET_WIDTH - 1);
+            }
+
+            report
+        },
+        ReportFormat::Json => {
+            let buckets: Vec<Bucket> = buckets
+                .into_iter()
+                .map(|(start, count)| Bucket {
+                    start,
+                    end: start + BUCKET_WIDTH - 1,
+                    count,
+                })
+                .collect();
+
+            json(&Histogram {
+                histogram: buckets,
+                count: stats.count(),
+            })
+        },
+    }
+}
+
+/// How often every digit was the first and the last digit of a line
+pub fn digit_frequency(stats: &LineStats, format: ReportFormat) -> String {
+    let (first, last) = (stats.first_digits(), stats.last_digits());
+
+    match format {
+        ReportFormat::Text => {
+            let mut report = "Digit  First  Last\n".to_owned();
+            for digit in 0..10 {
+                let _ = writeln!(report, "{digit:>5}  {:>5}  {:>4}", first[digit], last[digit]);
+            }
+
+            report
+        },
+        ReportFormat::Json => json(&DigitFrequency {
+            first_digits: first,
+            last_digits: last,
+        }),
+    }
+}
+
+/// How often every token was matched as the first or the last digit, the most frequent first
+pub fn token_frequency(tokens: &TokenStats, format: ReportFormat) -> String {
+    let kind = |kind| match kind {
+        MatchKind::Digit => "digit",
+        MatchKind::Word => "word",
+    };
+
+    match format {
+        ReportFormat::Text => {
+            let mut report = format!("Numeric matches: {}, spelled matches: {}\n", tokens.numeric(), tokens.spelled());
+            for (token, token_kind, count) in tokens.tokens() {
+                let _ = writeln!(report, "{token:?} {} matched {count} times", kind(token_kind));
+            }
+
+            report
+        },
+        ReportFormat::Json => {
+            let entries = tokens
+                .tokens()
+                .into_iter()
+                .map(|(token, kind, count)| TokenCount { token, kind, count })
+                .collect();
+
+            json(&TokenFrequency {
+                numeric: tokens.numeric(),
+                spelled: tokens.spelled(),
+                tokens: entries,
+            })
+        },
+    }
+}
+
+/// The kept lines with their values and line numbers, the best first
+pub fn top_lines(lines: &TopLines) -> String {
+    let mut report = String::new();
+    for (value, number, line) in lines.lines() {
+        let _ = writeln!(report, "{value:>2} (line number {number}): {line:?}");
+    }
+
+    report
+}
+
+/// The amount of the lines and the total amount of every key of `--group-by`, the lines without a key last
+pub fn groups(groups: &GroupStats) -> String {
+    let mut report = String::new();
+    let mut unkeyed = None;
+    for (key, lines, sum) in groups.groups() {
+        if key.is_empty() {
+            unkeyed = Some((lines, sum));
+            continue;
+        }
+        let _ = writeln!(report, "{key:?}: lines {lines}, total amount {sum}");
+    }
+    if let Some((lines, sum)) = unkeyed {
+        let _ = writeln!(report, "no key: lines {lines}, total amount {sum}");
+    }
+
+    report
+}
+
+/// The totals of an input of a run of several ones, e.g. an entry of an archive
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct FileTotals {
+    pub file: String,
+    pub parsed_lines: u64,
+    pub incorrect_lines: u64,
+    pub total_sum: u64,
+    #[serde(rename = "elapsed_seconds", serialize_with = "seconds")]
+    pub elapsed: Duration,
+}
+
+fn seconds<S: Serializer>(elapsed: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(elapsed.as_secs_f64())
+}
+
+impl FileTotals {
+    pub fn new(file: &str, summary: &Summary, elapsed: Duration) -> Self {
+        Self {
+            file: file.to_owned(),
+            parsed_lines: summary.parsed_lines,
+            incorrect_lines: summary.incorrect_lines,
+            total_sum: summary.total_sum,
+            elapsed,
+        }
+    }
+}
+
+/// The totals of every input in the order they were processed
+pub fn files(files: &[FileTotals], format: ReportFormat) -> String {
+    match format {
+        ReportFormat::Text => {
+            let mut report = "Files:\n".to_owned();
+            for totals in files {
+                let _ = writeln!(
+                    report,
+                    "{}: parsed lines {}, incorrect lines {}, total amount {}, elapsed {:?}",
+                    totals.file, totals.parsed_lines, totals.incorrect_lines, totals.total_sum, totals.elapsed
+                );
+            }
+
+            report
+        },
+        ReportFormat::Json => json(&Files { files }),
+    }
+}
+
+/// The per-file totals as a JSON array, e.g. for the metadata of a results file
+#[cfg(any(feature = "parquet", test))]
+pub fn files_json(files: &[FileTotals]) -> String {
+    serde_json::to_string(files).unwrap_or_default()
+}
+
+/// The sizes of the output files: the report of `report_bytes` with this line itself, and the rejects file
+pub fn output_size(report_bytes: usize, rejects: Option<u64>) -> String {
+    let mut line = String::new();
+    // the line counts its own bytes, their amount of digits settles within a few rounds
+    loop {
+        let report = report_bytes + line.len();
+        let next = match rejects {
+            Some(rejects) => format!("Output size: report {report} bytes, rejects {rejects} bytes\n"),
+            None => format!("Output size: report {report} bytes\n"),
+        };
+        if next == line {
+            return line;
+        }
+        line = next;
+    }
+}
+
+/// The first and the last digits of the whole input and its value, instead of the total amount of `--scope file`
+pub fn input_ends(ends: &InputEnds) -> String {
+    let (Some((first_number, first)), Some((last_number, last)), Some(value)) = (ends.first, ends.last, ends.value())
+    else {
+        return "\nFile value: none, no digits in the input\n".to_owned();
+    };
+
+    format!(
+        "\nFirst digit: {} (line number {first_number}, bytes {}..{})\nLast digit: {} (line number {last_number}, \
+         bytes {}..{})\nFile value: {value}\n",
+        first.digit, first.start, first.end, last.digit, last.start, last.end
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_groups() {
+        let mut stats = GroupStats::default();
+        for (key, value) in [("b", 29), ("", 7), ("a", 77), ("b", 13)] {
+            stats.add(key, value);
+        }
+
+        assert_eq!(
+            groups(&stats),
+            "\"a\": lines 1, total amount 77\n\"b\": lines 2, total amount 42\nno key: lines 1, total amount 7\n"
+        );
+    }
+
+    #[test]
+    fn test_files() {
+        let summary = Summary {
+            parsed_lines: 4,
+            incorrect_lines: 1,
+            total_sum: 142,
+            ..Summary::default()
+        };
+        let files = [
+            FileTotals::new("in.tar:a.txt", &summary, Duration::from_millis(5)),
+            FileTotals::new("in.tar:\"b\".txt", &Summary::default(), Duration::from_secs(2)),
+        ];
+
+        assert_eq!(
+            super::files(&files, ReportFormat::Text),
+            "Files:\nin.tar:a.txt: parsed lines 4, incorrect lines 1, total amount 142, elapsed 5ms\n\
+             in.tar:\"b\".txt: parsed lines 0, incorrect lines 0, total amount 0, elapsed 2s\n"
+        );
+        assert_eq!(
+            super::files(&files[1..], ReportFormat::Json),
+            "{\"files\":[{\"file\":\"in.tar:\\\"b\\\".txt\",\"parsed_lines\":0,\"incorrect_lines\":0,\"total_sum\":0,\
+             \"elapsed_seconds\":2.0}]}\n"
+        );
+        assert_eq!(
+            files_json(&files[..1]),
+            "[{\"file\":\"in.tar:a.txt\",\"parsed_lines\":4,\"incorrect_lines\":1,\"total_sum\":142,\
+             \"elapsed_seconds\":0.005}]"
+        );
+    }
+
+    #[test]
+    fn test_histogram() {
+        let mut stats = LineStats::default();
+        for value in [29, 83, 13, 24, 42, 14, 76] {
+            stats.add(value);
+        }
+
+        let text = histogram(&stats, ReportFormat::Text);
+        assert_eq!(text.lines().count(), 10);
+        assert!(text.contains(&format!("10-19 | {} 2\n", "#".repeat(50))));
+        assert!(text.contains("40-49 | ######################### 1\n"));
+        assert!(text.contains(" 0-9  |  0\n"));
+
+        let json = histogram(&stats, ReportFormat::Json);
+        assert!(json
+            .starts_with("{\"histogram\":[{\"start\":0,\"end\":9,\"count\":0},{\"start\":10,\"end\":19,\"count\":2}"));
+        assert!(json.ends_with("],\"count\":7}\n"));
+    }
+
+    #[test]
+    fn test_digit_frequency() {
+        let mut stats = LineStats::default();
+        for value in [29, 83, 13, 24, 42, 14, 76] {
+            stats.add(value);
+        }
+
+        let text = digit_frequency(&stats, ReportFormat::Text);
+        assert_eq!(text.lines().count(), 11);
+        assert!(text.contains("\n    1      2     0\n"));
+
+        assert_eq!(
+            digit_frequency(&stats, ReportFormat::Json),
+            "{\"first_digits\":[0,2,2,0,1,0,0,1,1,0],\"last_digits\":[0,0,1,2,2,0,1,0,0,1]}\n"
+        );
+    }
+
+    #[test]
+    fn test_token_frequency() {
+        let mut tokens = TokenStats::default();
+        for line in ["two1nine", "treb7uchet"] {
+            let value = find_digits::extract_line(line).unwrap();
+            tokens.add(line, &value.first);
+            tokens.add(line, &value.last);
+        }
+
+        assert_eq!(
+            token_frequency(&tokens, ReportFormat::Text),
+            "Numeric matches: 2, spelled matches: 2\n\"7\" digit matched 2 times\n\"nine\" word matched 1 times\n\"two\" \
+             word matched 1 times\n"
+        );
+        assert!(token_frequency(&tokens, ReportFormat::Json)
+            .starts_with("{\"numeric\":2,\"spelled\":2,\"tokens\":[{\"token\":\"7\",\"kind\":\"digit\",\"count\":2},"));
+    }
+
+    #[test]
+    fn test_output_size() {
+        let inputs = [(0, None), (1000, Some(24)), (9970, None)];
+        let expected = [
+            "Output size: report 29 bytes\n",
+            "Output size: report 1049 bytes, rejects 24 bytes\n",
+            "Output size: report 10002 bytes\n",
+        ];
+
+        for (pos, (report_bytes, rejects)) in inputs.into_iter().enumerate() {
+            let line = output_size(report_bytes, rejects);
+            assert_eq!(line, expected[pos]);
+            assert_eq!(line.find(" bytes").map(|end| line[20..end].parse()), Some(Ok(report_bytes + line.len())));
+        }
+    }
+
+    #[test]
+    fn test_input_ends() {
+        let mut ends = InputEnds::default();
+        assert_eq!(input_ends(&ends), "\nFile value: none, no digits in the input\n");
+
+        for (number, line) in [(2, "two1nine"), (4, "treb7uchet")] {
+            ends.add(number, &find_digits::extract_line(line).unwrap());
+        }
+        assert_eq!(
+            input_ends(&ends),
+            "\nFirst digit: 2 (line number 2, bytes 0..3)\nLast digit: 7 (line number 4, bytes 4..5)\nFile value: 27\n"
+        );
+    }
+}
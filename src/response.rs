@@ -0,0 +1,93 @@
+//! The JSON bodies of the answers of `serve`, of its WebSocket endpoint and of its JSON-RPC mode
+
+use find_digits::{LineError, Match, Summary};
+use serde::Serialize;
+
+use crate::serve::Stats;
+
+/// The totals of some lines
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Totals {
+    pub sum: u64,
+    pub parsed_lines: u64,
+    pub incorrect_lines: u64,
+}
+
+impl From<&Summary> for Totals {
+    fn from(summary: &Summary) -> Self {
+        Self {
+            sum: summary.total_sum,
+            parsed_lines: summary.parsed_lines,
+            incorrect_lines: summary.incorrect_lines,
+        }
+    }
+}
+
+/// `POST /extract`: the value of every line (`null` for a line without a value) and their totals
+#[derive(Serialize, Debug)]
+pub struct Extracted {
+    pub values: Vec<Option<u32>>,
+    #[serde(flatten)]
+    pub totals: Totals,
+}
+
+/// `GET /stats` and the `stats` method: the totals of all the requests since the start
+#[derive(Serialize, Debug)]
+pub struct StatsBody {
+    pub requests: u64,
+    #[serde(flatten)]
+    pub totals: Totals,
+}
+
+impl From<&Stats> for StatsBody {
+    fn from(stats: &Stats) -> Self {
+        Self {
+            requests: stats.requests,
+            totals: Totals::from(&stats.summary),
+        }
+    }
+}
+
+/// The body of a failed HTTP request
+#[derive(Serialize, Debug)]
+pub struct Failure<'a> {
+    pub error: &'a str,
+}
+
+/// The answer of a line of a WebSocket message
+#[derive(Serialize, Debug)]
+pub struct LineAnswer<'a> {
+    pub line: usize,
+    #[serde(flatten)]
+    pub outcome: Outcome<'a>,
+}
+
+/// `"value": V` of a processed line (`null` without a value), `"error": E` of a line failing the processing
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome<'a> {
+    Value(Option<u32>),
+    Error(&'a str),
+}
+
+/// The totals of a WebSocket connection and of the whole server, pushed periodically
+#[derive(Serialize, Debug)]
+pub struct Snapshot {
+    pub connection: Totals,
+    pub totals: StatsBody,
+}
+
+/// The `extract` method: the value of the line or why it has none, its first and last matches and all of them
+#[derive(Serialize, Debug)]
+pub struct LineReport {
+    pub value: Option<u32>,
+    pub error: Option<LineError>,
+    pub first: Option<Match>,
+    pub last: Option<Match>,
+    pub matches: Vec<Match>,
+}
+
+/// The JSON text of the body, the types above never fail to serialize
+pub fn json(body: &impl Serialize) -> String {
+    serde_json::to_string(body).unwrap_or_default()
+}
@@ -0,0 +1,153 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::PathBuf,
+    str,
+};
+
+use clap::{Args, ValueEnum};
+use find_digits::{extract_line_with, matches_with, Match, ScanOptions};
+
+#[derive(Args, Debug, Clone)]
+pub struct RewriteArgs {
+    /// The input to copy
+    #[arg(short, long)]
+    file: PathBuf,
+    /// Where to write the copy with the numerals
+    #[arg(short, long)]
+    out: PathBuf,
+    /// Which spelled digits are replaced
+    #[arg(long, value_enum, default_value_t)]
+    replace: Selection,
+}
+
+/// The spelled digits of a line to replace by their numerals
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Selection {
+    /// every recognized word
+    #[default]
+    All,
+    /// the word of the first digit only
+    First,
+    /// the word of the last digit only
+    Last,
+    /// the words of the first and the last digits
+    FirstLast,
+}
+
+pub fn run(args: &RewriteArgs, options: &ScanOptions) -> Result<(), String> {
+    let input = File::open(&args.file).map_err(|e| format!("Fail to open file {}: {e:?}", args.file.display()))?;
+    let output = File::create(&args.out).map_err(|e| format!("Fail to create {}: {e:?}", args.out.display()))?;
+    let (mut reader, mut writer) = (BufReader::new(input), BufWriter::new(output));
+
+    let write_error = |e| format!("Fail to write {}: {e:?}", args.out.display());
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        let length = reader
+            .read_until(b'\n', &mut line)
+            .map_err(|e| format!("Fail to read file {}: {e:?}", args.file.display()))?;
+        if length == 0 {
+            break;
+        }
+
+        let mut text = &line[..];
+        if let Some(stripped) = text.strip_suffix(b"\n") {
+            text = stripped.strip_suffix(b"\r").unwrap_or(stripped);
+        }
+
+        // a line of invalid UTF-8 has no digits to recognize, it's copied as is
+        match str::from_utf8(text) {
+            Ok(text) => writer.write_all(rewrite_line(text, options, args.replace).as_bytes()),
+            Err(_) => writer.write_all(text),
+        }
+        .map_err(write_error)?;
+        writer.write_all(&line[text.len()..]).map_err(write_error)?;
+    }
+
+    writer.flush().map_err(write_error)
+}
+
+/// The line with the selected spelled digits replaced by their numerals
+///
+/// The overlapping words like `eightwo` share their letters, they're replaced together by both numerals (`82`)
+/// so the rewritten line keeps its value.
+pub fn rewrite_line(line: &str, options: &ScanOptions, selection: Selection) -> String {
+    let mut words: Vec<Match> = match selection {
+        Selection::All => matches_with(line, options).collect(),
+        _ => match extract_line_with(line, options) {
+            Ok(value) => match selection {
+                Selection::First => vec![value.first],
+                Selection::Last => vec![value.last],
+                _ => vec![value.first, value.last],
+            },
+            Err(_) => Vec::new(),
+        },
+    };
+    words.retain(Match::is_word);
+    words.dedup();
+
+    let mut rewritten = String::with_capacity(line.len());
+    let mut position = 0;
+    for word in words {
+        if word.start >= position {
+            rewritten.push_str(&line[position..word.start]);
+        }
+        rewritten.push(word.digit);
+        position = position.max(word.end);
+    }
+    rewritten.push_str(&line[position..]);
+
+    rewritten
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::*;
+    use crate::testing::temp_dir;
+
+    #[test]
+    fn test_rewrite_line() {
+        let options = ScanOptions::default();
+        let strings = [
+            "two1nine",
+            "eightwothree",
+            "abcone2threexyz",
+            "treb7uchet",
+            "oneighthree",
+            "",
+        ];
+        let expected = [
+            ["219", "21nine", "two19", "219"],
+            ["823", "8wothree", "eightwo3", "8wo3"],
+            ["abc123xyz", "abc12threexyz", "abcone23xyz", "abc123xyz"],
+            ["treb7uchet"; 4],
+            ["183", "1ighthree", "oneigh3", "1igh3"],
+            [""; 4],
+        ];
+        let selections = [Selection::All, Selection::First, Selection::Last, Selection::FirstLast];
+
+        for (pos, line) in strings.into_iter().enumerate() {
+            for (selection, expected) in selections.into_iter().zip(expected[pos]) {
+                assert_eq!(rewrite_line(line, &options, selection), expected, "{line:?} {selection:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_run() {
+        let directory = temp_dir();
+        let (file, out) = (directory.path().join("rewrite.txt"), directory.path().join("rewritten.txt"));
+        fs::write(&file, b"two1nine\r\n\xFFone\nx\n\nsix").unwrap();
+
+        let args = RewriteArgs {
+            file: file.clone(),
+            out: out.clone(),
+            replace: Selection::All,
+        };
+        run(&args, &ScanOptions::default()).unwrap();
+        assert_eq!(fs::read(&out).unwrap(), b"219\r\n\xFFone\nx\n\n6");
+    }
+}
@@ -0,0 +1,241 @@
+use std::{
+    io::{self, BufRead, Write},
+    sync::{Arc, Mutex, PoisonError},
+    time::Instant,
+};
+
+use find_digits::LineError;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{
+    process::{self, Processing},
+    response::{self, LineReport, StatsBody},
+    serve::Stats,
+    tee,
+};
+
+/// The error codes of JSON-RPC 2.0
+const PARSE_ERROR: i32 = -32700;
+const INVALID_REQUEST: i32 = -32600;
+const METHOD_NOT_FOUND: i32 = -32601;
+const INVALID_PARAMS: i32 = -32602;
+/// Of the server errors range, the line fails e.g. by `--on-no-digits error`
+const LINE_FAILED: i32 = -32000;
+
+#[derive(Serialize, Debug)]
+struct RpcResponse<'a> {
+    jsonrpc: &'static str,
+    id: &'a Value,
+    #[serde(flatten)]
+    outcome: RpcOutcome,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "lowercase")]
+enum RpcOutcome {
+    Result(RpcResult),
+    Error { code: i32, message: String },
+}
+
+/// The result of every method, `null` of `shutdown`
+#[derive(Serialize, Debug)]
+#[serde(untagged)]
+enum RpcResult {
+    Line(LineReport),
+    Stats(StatsBody),
+    Shutdown,
+}
+
+/// Answer the JSON-RPC requests of stdin, one per line, by a response line each on stdout until the end of stdin
+/// or `shutdown`, the messages go to stderr
+pub fn run(processing: Processing) -> Result<(), String> {
+    tee::redirect_messages();
+    let processing = Arc::new(processing);
+    process::reload_on_hangup(&processing);
+
+    let (stats, start) = (Mutex::default(), Instant::now());
+    let result = session(io::stdin().lock(), io::stdout().lock(), &processing, &stats);
+
+    let stats: Stats = stats.into_inner().unwrap_or_else(PoisonError::into_inner);
+    if stats.summary.parsed_lines > 0 {
+        processing.record_history("stdio", &stats.summary, start.elapsed());
+    }
+    result.map_err(|e| format!("Fail to serve stdio: {e}"))
+}
+
+fn session(
+    mut input: impl BufRead,
+    mut output: impl Write,
+    processing: &Processing,
+    stats: &Mutex<Stats>,
+) -> io::Result<()> {
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(());
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let (response, shutdown) = respond(&line, processing, stats);
+        if let Some(response) = response {
+            writeln!(output, "{response}")?;
+            output.flush()?;
+        }
+        if shutdown {
+            return Ok(());
+        }
+    }
+}
+
+/// The response of the request, none of a notification, and whether it's `shutdown`
+fn respond(request: &str, processing: &Processing, stats: &Mutex<Stats>) -> (Option<String>, bool) {
+    let request: Value = match serde_json::from_str(request) {
+        Ok(request) => request,
+        Err(e) => return (Some(error_response(&Value::Null, PARSE_ERROR, format!("invalid JSON: {e}"))), false),
+    };
+
+    // the id of an invalid request is unknown, `null` then
+    let (id, method) = match (request.get("id"), request.get("method").and_then(Value::as_str)) {
+        (id, Some(method)) if request.get("jsonrpc").and_then(Value::as_str) == Some("2.0") => (id, method),
+        _ => {
+            let message = "not a JSON-RPC 2.0 request".to_owned();
+            return (Some(error_response(&Value::Null, INVALID_REQUEST, message)), false);
+        },
+    };
+    let params = request.get("params");
+
+    let result = match method {
+        "extract" => match params.and_then(|params| params.get("line")).and_then(Value::as_str) {
+            Some(line) => extract(line, processing, stats)
+                .map(RpcResult::Line)
+                .map_err(|e| (LINE_FAILED, e)),
+            None => Err((INVALID_PARAMS, "the params must be {\"line\": <string>}".to_owned())),
+        },
+        "stats" => Ok(RpcResult::Stats(StatsBody::from(&*stats.lock().unwrap_or_else(PoisonError::into_inner)))),
+        "shutdown" => Ok(RpcResult::Shutdown),
+        _ => Err((METHOD_NOT_FOUND, format!("no method {method}"))),
+    };
+
+    // a notification isn't answered, even on errors
+    let response = id.map(|id| {
+        let outcome = match result {
+            Ok(result) => RpcOutcome::Result(result),
+            Err((code, message)) => RpcOutcome::Error { code, message },
+        };
+        response::json(&RpcResponse {
+            jsonrpc: "2.0",
+            id,
+            outcome,
+        })
+    });
+
+    (response, method == "shutdown")
+}
+
+/// The value of the line, its first and last matches and all of them, the line is counted by `stats`;
+/// `Err` of a line without digits by `--on-no-digits error`
+fn extract(line: &str, processing: &Processing, stats: &Mutex<Stats>) -> Result<LineReport, String> {
+    let (result, matches) = processing.inspect(line);
+    let no_digits = match result {
+        Err(LineError::NoDigits) => processing.no_digits_value("request", 0, Some(line))?,
+        _ => None,
+    };
+
+    let mut stats = stats.lock().unwrap_or_else(PoisonError::into_inner);
+    stats.requests += 1;
+    stats.summary.parsed_lines += 1;
+
+    let report = move |value, error, first, last| LineReport {
+        value,
+        error,
+        first,
+        last,
+        matches,
+    };
+    Ok(match (result, no_digits) {
+        (Ok(value), _) => {
+            stats.summary.add_value(value.value);
+            report(Some(value.value), None, Some(value.first), Some(value.last))
+        },
+        (Err(_), Some(value)) => {
+            stats.summary.add_value(value);
+            report(Some(value), None, None, None)
+        },
+        (Err(e), None) => {
+            stats.summary.incorrect_lines += 1;
+            report(None, Some(e), None, None)
+        },
+    })
+}
+
+fn error_response(id: &Value, code: i32, message: String) -> String {
+    response::json(&RpcResponse {
+        jsonrpc: "2.0",
+        id,
+        outcome: RpcOutcome::Error { code, message },
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use clap::Parser;
+
+    use super::*;
+
+    #[test]
+    fn test_session() {
+        let processing = Processing::new(&crate::Args::parse_from(["find_digits", "serve", "--stdio"])).unwrap();
+        let requests = [
+            r#"{"jsonrpc": "2.0", "id": 1, "method": "extract", "params": {"line": "two1nine"}}"#,
+            r#"{"jsonrpc": "2.0", "id": "b", "method": "extract", "params": {"line": "abc"}}"#,
+            r#"{"jsonrpc": "2.0", "method": "extract", "params": {"line": "7"}}"#,
+            "",
+            r#"{"jsonrpc": "2.0", "id": 2, "method": "extract", "params": ["two1nine"]}"#,
+            r#"{"jsonrpc": "2.0", "id": 3, "method": "stats"}"#,
+            r#"{"jsonrpc": "2.0", "id": 4, "method": "values"}"#,
+            r#"{"id": 5, "method": "stats"}"#,
+            "{not json",
+            r#"{"jsonrpc": "2.0", "id": 6, "method": "shutdown"}"#,
+            r#"{"jsonrpc": "2.0", "id": 7, "method": "stats"}"#,
+        ];
+        let mut output = Vec::new();
+        session(requests.join("\n").as_bytes(), &mut output, &processing, &Mutex::default()).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        let responses: Vec<&str> = output.lines().collect();
+        assert_eq!(responses.len(), 8);
+        assert_eq!(
+            responses[0],
+            "{\"jsonrpc\":\"2.0\",\"id\":1,\"result\":{\"value\":29,\"error\":null,\
+             \"first\":{\"digit\":2,\"start\":0,\"end\":3,\"kind\":\"word\"},\
+             \"last\":{\"digit\":9,\"start\":4,\"end\":8,\"kind\":\"word\"},\
+             \"matches\":[{\"digit\":2,\"start\":0,\"end\":3,\"kind\":\"word\"},\
+             {\"digit\":1,\"start\":3,\"end\":4,\"kind\":\"digit\"},\
+             {\"digit\":9,\"start\":4,\"end\":8,\"kind\":\"word\"}]}}"
+        );
+        // the same error names as of the other JSON outputs
+        assert_eq!(
+            responses[1],
+            "{\"jsonrpc\":\"2.0\",\"id\":\"b\",\"result\":{\"value\":null,\"error\":\"no_digits\",\
+             \"first\":null,\"last\":null,\"matches\":[]}}"
+        );
+
+        let codes: Vec<Option<i64>> = responses[2..]
+            .iter()
+            .map(|response| serde_json::from_str::<Value>(response).unwrap())
+            .map(|response| response.pointer("/error/code").and_then(Value::as_i64))
+            .collect();
+        assert_eq!(codes, [Some(-32602), None, Some(-32601), Some(-32600), Some(-32700), None]);
+        // the notification is counted too
+        assert_eq!(
+            responses[3],
+            "{\"jsonrpc\":\"2.0\",\"id\":3,\"result\":{\"requests\":3,\"sum\":106,\"parsed_lines\":3,\
+             \"incorrect_lines\":1}}"
+        );
+        assert_eq!(responses[7], "{\"jsonrpc\":\"2.0\",\"id\":6,\"result\":null}");
+    }
+}
@@ -0,0 +1,31 @@
+use clap::{Args, ValueEnum};
+
+use crate::highlight::ColorMode;
+
+/// How `scan` prints the lines, they're processed in order by a single thread
+#[derive(Args, Debug, Clone, Default)]
+pub struct ScanArgs {
+    /// What is printed of every line
+    #[arg(long, value_enum, default_value_t)]
+    pub show: Show,
+
+    /// When the highlighted lines are colored
+    #[arg(long, value_enum, default_value_t)]
+    pub color: ColorMode,
+}
+
+/// The output of a scanned line
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Show {
+    /// the line with its first and last matches highlighted
+    #[default]
+    Highlight,
+    /// the line with its matched tokens, their byte offsets and the resulting value
+    Explain,
+    /// the file, line number, byte spans and digits of the first and the last matches as JSON, lines with a value only
+    Spans,
+    /// only the lines which yield a value, like `grep`
+    Matching,
+    /// only the lines which don't yield a value
+    Nonmatching,
+}
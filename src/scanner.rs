@@ -0,0 +1,936 @@
+use std::sync::Arc;
+
+use crate::{byteset::ByteSet, fuzzy, unicode::decimal_value, Dictionary};
+
+/// Combining enclosing keycap, optionally preceded by the emoji presentation selector
+const KEYCAP: &str = "\u{20E3}";
+const KEYCAP_EMOJI: &str = "\u{FE0F}\u{20E3}";
+
+/// How the digit of a [`Match`] was written in the line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum MatchKind {
+    /// a numeric character like `7`
+    Digit,
+    /// a digit spelled out with letters like `seven`
+    Word,
+}
+
+/// A digit found in a line, either as a numeric character or as a spelled word.
+///
+/// `start..end` is the byte span of the matched token inside the line, `digit` is always ASCII `'0'..='9'`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Match {
+    /// a number `0..=9` in the serialized form
+    #[cfg_attr(feature = "serde", serde(with = "digit_number"))]
+    pub digit: char,
+    pub start: usize,
+    pub end: usize,
+    pub kind: MatchKind,
+}
+
+impl Match {
+    /// `true` when the digit was spelled out with letters (e.g. `three`)
+    #[must_use]
+    pub fn is_word(&self) -> bool {
+        self.kind == MatchKind::Word
+    }
+
+    /// The digit as a number `0..=9`
+    #[must_use]
+    pub fn value(&self) -> u8 {
+        u8::try_from(self.digit).map_or(0, |digit| digit - b'0')
+    }
+
+    /// The calibration value of the first and the last digits: `first * 10 + last`
+    #[must_use]
+    pub fn combine(first: &Self, last: &Self) -> u32 {
+        u32::from(first.value()) * 10 + u32::from(last.value())
+    }
+}
+
+/// The serialized form of [`Match::digit`]
+#[cfg(feature = "serde")]
+mod digit_number {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn serialize<S: Serializer>(digit: &char, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u32(digit.to_digit(10).unwrap_or_default())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<char, D::Error> {
+        let value = u32::deserialize(deserializer)?;
+        char::from_digit(value, 10).ok_or_else(|| D::Error::custom(format!("{value} is not a digit")))
+    }
+}
+
+/// Whether the matched words may share letters, e.g. `eightwo`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum Overlap {
+    /// every word counts, `eightwo` is `8` and `2`
+    #[default]
+    Allow,
+    /// the letters of a match aren't matched again, `eightwo` is only `8`
+    Consume,
+}
+
+/// Which characters are recognized as digits
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct ScanOptions {
+    /// any Unicode decimal digit (category `Nd`) counts, not only ASCII `0-9`
+    pub unicode_digits: bool,
+    /// fullwidth forms `０-９` (U+FF10..U+FF19) of CJK documents count, they are included by `unicode_digits` too
+    pub fullwidth_digits: bool,
+    /// keycap emoji like `1\u{FE0F}\u{20E3}` are matched as a whole grapheme, not as a bare digit
+    pub keycap_digits: bool,
+    /// digits adjacent to other digits (parts of multi-digit tokens like `12`) are skipped
+    pub isolated_digits_only: bool,
+    /// spelled digits may contain up to this amount of substitutions, insertions or deletions (`thre`, `sevcn`)
+    pub fuzzy: usize,
+    /// spelled digits are matched only between non-letters, `three` isn't a digit of `threefold`
+    pub word_boundaries: bool,
+    /// spelled digits, [`Dictionary::english`] by default
+    pub dictionary: Option<Arc<Dictionary>>,
+    /// whether the matches may share letters, the line is then tokenized from its beginning in both scans
+    pub overlap: Overlap,
+}
+
+impl ScanOptions {
+    #[must_use]
+    pub fn dictionary(&self) -> &Dictionary {
+        self.dictionary.as_deref().unwrap_or_else(|| Dictionary::english())
+    }
+
+    fn digit(&self, c: char) -> Option<char> {
+        if c.is_ascii_digit() {
+            return Some(c);
+        }
+
+        if self.fullwidth_digits && ('\u{FF10}'..='\u{FF19}').contains(&c) {
+            return char::from_digit(u32::from(c) - 0xFF10, 10);
+        }
+
+        if !self.unicode_digits {
+            return None;
+        }
+
+        decimal_value(c).and_then(|value| char::from_digit(value, 10))
+    }
+
+    /// Bytes a match can begin with, `None` when any char may begin a match (a fuzzy word)
+    fn start_class(&self) -> Option<&ByteSet> {
+        (self.fuzzy == 0).then(|| self.dictionary().starts(self.non_ascii_digits()))
+    }
+
+    /// Bytes a match can end with (the last byte of a digit char), `None` when any char may end a match
+    fn end_class(&self) -> Option<&ByteSet> {
+        (self.fuzzy == 0).then(|| self.dictionary().ends(self.non_ascii_digits()))
+    }
+
+    fn non_ascii_digits(&self) -> bool {
+        self.unicode_digits || self.fullwidth_digits
+    }
+
+    /// Only the ASCII digits and the exact words of the dictionary are matches, wherever they are
+    fn is_plain(&self) -> bool {
+        !(self.non_ascii_digits()
+            || self.keycap_digits
+            || self.isolated_digits_only
+            || self.fuzzy > 0
+            || self.word_boundaries)
+    }
+
+    /// The digit match which begins with the char `c` at the position `start` if it's accepted as a candidate
+    fn digit_match(&self, line: &str, c: char, start: usize) -> Option<Match> {
+        let digit = self.digit(c)?;
+        let end = self.digit_end(line, start + c.len_utf8());
+
+        if self.isolated_digits_only {
+            let before = self.skip_keycap_back(line, start).chars().next_back();
+            let after = line[end..].chars().next();
+
+            if [before, after].into_iter().flatten().any(|c| self.digit(c).is_some()) {
+                return None;
+            }
+        }
+
+        Some(Match {
+            digit,
+            start,
+            end,
+            kind: MatchKind::Digit,
+        })
+    }
+
+    /// The word at `start..end` isn't glued to letters when they're required to be at word boundaries
+    fn delimited(&self, line: &str, start: usize, end: usize) -> bool {
+        if !self.word_boundaries {
+            return true;
+        }
+
+        let before = line[..start].chars().next_back();
+        let after = line[end..].chars().next();
+        ![before, after].into_iter().flatten().any(char::is_alphabetic)
+    }
+
+    /// The end of the digit grapheme: the keycap sequence `1\u{FE0F}\u{20E3}` is a single digit
+    fn digit_end(&self, line: &str, end: usize) -> usize {
+        if !self.keycap_digits {
+            return end;
+        }
+
+        let rest = &line[end..];
+        [KEYCAP_EMOJI, KEYCAP]
+            .into_iter()
+            .find(|suffix| rest.starts_with(suffix))
+            .map_or(end, |suffix| end + suffix.len())
+    }
+
+    /// The line before `start` without the trailing keycap marks, so the previous grapheme is a base char
+    fn skip_keycap_back<'a>(&self, line: &'a str, start: usize) -> &'a str {
+        let before = &line[..start];
+        if !self.keycap_digits {
+            return before;
+        }
+
+        [KEYCAP_EMOJI, KEYCAP]
+            .into_iter()
+            .find_map(|suffix| before.strip_suffix(suffix))
+            .unwrap_or(before)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SearchResult {
+    pub number: Option<char>,
+    pub last_parsed_position: usize,
+}
+
+/// Calculate the calibration value of the line: the first digit times ten plus the last digit
+#[must_use]
+pub fn extract_number(line: &str) -> Option<u32> {
+    extract_number_with(line, &ScanOptions::default())
+}
+
+/// [`extract_number`] with custom scan options
+#[must_use]
+pub fn extract_number_with(line: &str, options: &ScanOptions) -> Option<u32> {
+    let fst = forward_match(line, options)?;
+    let lst = backward_match(line, fst.start, options)?;
+
+    Some(Match::combine(&fst, &lst))
+}
+
+/// The first digit of the line, the reverse scan is not performed
+#[must_use]
+pub fn first_digit(line: &str) -> Option<Match> {
+    forward_match(line, &ScanOptions::default())
+}
+
+/// The last digit of the line, the forward scan is not performed
+#[must_use]
+pub fn last_digit(line: &str) -> Option<Match> {
+    backward_match(line, 0, &ScanOptions::default())
+}
+
+/// Every digit of the line in the order of their beginnings, spelled digits may overlap like `eightwo`
+/// unless [`Overlap::Consume`]
+pub fn matches_with<'a>(line: &'a str, options: &'a ScanOptions) -> impl Iterator<Item = Match> + 'a {
+    all_matches(line, options)
+}
+
+#[must_use]
+pub fn find(line: &str) -> SearchResult {
+    find_with(line, &ScanOptions::default())
+}
+
+#[must_use]
+pub fn find_with(line: &str, options: &ScanOptions) -> SearchResult {
+    match forward_match(line, options) {
+        Some(m) => SearchResult {
+            number: Some(m.digit),
+            last_parsed_position: m.start,
+        },
+        None => SearchResult {
+            number: None,
+            last_parsed_position: line.len(),
+        },
+    }
+}
+
+#[must_use]
+pub fn r_find(line: &str, found_pos: usize) -> SearchResult {
+    r_find_with(line, found_pos, &ScanOptions::default())
+}
+
+#[must_use]
+pub fn r_find_with(line: &str, found_pos: usize, options: &ScanOptions) -> SearchResult {
+    match backward_match(line, found_pos, options) {
+        Some(m) => SearchResult {
+            number: Some(m.digit),
+            last_parsed_position: if m.is_word() { m.start } else { m.end },
+        },
+        None => SearchResult {
+            number: None,
+            last_parsed_position: found_pos.min(line.len()),
+        },
+    }
+}
+
+/// Verify the invariants between the forward and the reverse scans on the line:
+/// the reverse match is never earlier than the forward one, both scans either find a match or not
+/// and they agree when the line contains exactly one digit.
+/// Without the options changing what a match is (other digits than ASCII ones, keycaps, isolated digits,
+/// fuzzy or delimited words) both matches are the ones of a naive scan trying every word at every position too
+///
+/// # Errors
+///
+/// description of the violated invariant
+pub fn cross_check(line: &str, options: &ScanOptions) -> Result<(), String> {
+    let forward = forward_match(line, options);
+    let found_pos = forward.map_or(line.len(), |m| m.start);
+    let reverse = backward_match(line, found_pos, options);
+
+    if options.is_plain() {
+        let naive = naive_matches(line, options);
+        if forward.zip(reverse) != naive {
+            return Err(format!(
+                "forward match {forward:?} and reverse match {reverse:?} differ from naive matches {naive:?}"
+            ));
+        }
+    }
+
+    match (forward, reverse) {
+        (Some(fst), Some(lst)) if lst.start < fst.start => {
+            Err(format!("reverse match {lst:?} is earlier than forward match {fst:?}"))
+        },
+        (Some(fst), Some(lst)) if fst != lst && all_matches(line, options).nth(1).is_none() => {
+            Err(format!("single digit line, but forward match {fst:?} differs from reverse match {lst:?}"))
+        },
+        (Some(fst), None) => Err(format!("forward match {fst:?} without reverse match")),
+        (None, Some(lst)) => Err(format!("reverse match {lst:?} without forward match")),
+        _ => Ok(()),
+    }
+}
+
+/// The first and the last matches of the line by the definition only, independent of the scanners:
+/// the first match begins first, the last one ends last, both are the longest ones at their positions.
+/// With [`Overlap::Consume`] the last match is the last one of the leftmost non-overlapping matches instead
+fn naive_matches(line: &str, options: &ScanOptions) -> Option<(Match, Match)> {
+    let longest_at = |start: usize| {
+        let rest = &line[start..];
+        let words = options.dictionary().words().iter().filter(|(word, _)| rest.starts_with(word.as_str()));
+        let word = words.max_by_key(|(word, _)| word.len()).map(|(word, digit)| Match {
+            digit: *digit,
+            start,
+            end: start + word.len(),
+            kind: MatchKind::Word,
+        });
+        let digit = rest.chars().next().filter(char::is_ascii_digit).map(|digit| Match {
+            digit,
+            start,
+            end: start + 1,
+            kind: MatchKind::Digit,
+        });
+
+        [word, digit].into_iter().flatten().max_by_key(|m| m.end)
+    };
+
+    let mut matches = Vec::new();
+    for start in (0..line.len()).filter(|&start| line.is_char_boundary(start)) {
+        let overlapped = options.overlap == Overlap::Consume && matches.last().is_some_and(|m: &Match| start < m.end);
+        if let Some(m) = longest_at(start).filter(|_| !overlapped) {
+            matches.push(m);
+        }
+    }
+
+    let first = *matches.first()?;
+    let last = match options.overlap {
+        Overlap::Consume => matches.last().copied(),
+        // the leftmost one of the matches ending last is the longest one
+        Overlap::Allow => matches.iter().copied().max_by_key(|m| (m.end, std::cmp::Reverse(m.start))),
+    }?;
+
+    Some((first, last))
+}
+
+// Positions are byte offsets of char boundaries, the words are compared as bytes:
+// a word matched from a char boundary always ends at a char boundary, so slicing can't panic
+pub(crate) fn forward_match(line: &str, options: &ScanOptions) -> Option<Match> {
+    let Some(class) = options.start_class() else {
+        return line.char_indices().find_map(|(pos, c)| match_at(line, pos, c, options));
+    };
+
+    let bytes = line.as_bytes();
+    let mut pos = 0;
+
+    while let Some(offset) = class.find(&bytes[pos..]) {
+        let start = pos + offset;
+        pos = start + 1;
+
+        if !line.is_char_boundary(start) {
+            continue;
+        }
+
+        let c = line[start..].chars().next()?;
+        if let Some(m) = match_at(line, start, c, options) {
+            return Some(m);
+        }
+    }
+
+    None
+}
+
+/// Every match of the line in the order of their beginnings, spelled words may overlap
+fn all_matches<'a>(line: &'a str, options: &'a ScanOptions) -> impl Iterator<Item = Match> + 'a {
+    line.char_indices()
+        .filter_map(move |(pos, c)| match_at(line, pos, c, options))
+        .filter(consumed(options.overlap))
+}
+
+/// Skips the matches beginning inside the previous kept one with [`Overlap::Consume`]
+pub(crate) fn consumed(overlap: Overlap) -> impl FnMut(&Match) -> bool {
+    let mut end = 0;
+    move |m| {
+        if overlap == Overlap::Consume && m.start < end {
+            return false;
+        }
+        end = m.end;
+        true
+    }
+}
+
+/// The match which begins with the char `c` at the position `pos`
+pub(crate) fn match_at(line: &str, pos: usize, c: char, options: &ScanOptions) -> Option<Match> {
+    if let Some(m) = options.digit_match(line, c, pos) {
+        return Some(m);
+    }
+
+    word_at(line, pos, options).filter(|m| options.delimited(line, m.start, m.end))
+}
+
+/// The spelled digit which begins at the position `pos`
+fn word_at(line: &str, pos: usize, options: &ScanOptions) -> Option<Match> {
+    if let Some((digit, length)) = options.dictionary().word_at(line, pos) {
+        return Some(Match {
+            digit,
+            start: pos,
+            end: pos + length,
+            kind: MatchKind::Word,
+        });
+    }
+
+    if options.fuzzy == 0 {
+        return None;
+    }
+
+    fuzzy::forward(line, pos, options.fuzzy, options.dictionary().words()).map(|(digit, length)| Match {
+        digit,
+        start: pos,
+        end: pos + length,
+        kind: MatchKind::Word,
+    })
+}
+
+pub(crate) fn backward_match(line: &str, found_pos: usize, options: &ScanOptions) -> Option<Match> {
+    if options.overlap == Overlap::Consume {
+        // the tokens depend on the previous ones, the line is tokenized from its beginning
+        return all_matches(line, options).last().filter(|m| m.end > found_pos);
+    }
+
+    let Some(class) = options.end_class() else {
+        return line
+            .char_indices()
+            .rev()
+            .take_while(|&(start, c)| start + c.len_utf8() > found_pos)
+            .find_map(|(start, c)| match_before(line, start, c, options));
+    };
+
+    let bytes = line.as_bytes();
+    let mut end = bytes.len();
+
+    while let Some(last) = class.rfind(&bytes[found_pos.min(end)..end]) {
+        let pos = found_pos.min(end) + last + 1;
+        end = pos - 1;
+
+        if !line.is_char_boundary(pos) {
+            continue;
+        }
+
+        let Some((start, c)) = line[..pos].char_indices().next_back() else {
+            continue;
+        };
+        if let Some(m) = match_before(line, start, c, options) {
+            return Some(m);
+        }
+    }
+
+    None
+}
+
+/// The match which ends with the char `c` at the position `start`
+fn match_before(line: &str, start: usize, c: char, options: &ScanOptions) -> Option<Match> {
+    if let Some(m) = options.digit_match(line, c, start) {
+        return Some(m);
+    }
+
+    word_before(line, start + c.len_utf8(), options).filter(|m| options.delimited(line, m.start, m.end))
+}
+
+/// The spelled digit which ends at the position `pos`
+fn word_before(line: &str, pos: usize, options: &ScanOptions) -> Option<Match> {
+    if let Some((digit, length)) = options.dictionary().word_before(line, pos) {
+        return Some(Match {
+            digit,
+            start: pos - length,
+            end: pos,
+            kind: MatchKind::Word,
+        });
+    }
+
+    if options.fuzzy == 0 {
+        return None;
+    }
+
+    fuzzy::backward(line, pos, options.fuzzy, options.dictionary().words()).map(|(digit, length)| Match {
+        digit,
+        start: pos - length,
+        end: pos,
+        kind: MatchKind::Word,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_find() {
+        let strings = [
+            "eightwothree",
+            "abcone2threexyz",
+            "treb7uchet",
+            "7pqrstsixteen",
+            "abcdefg",
+        ];
+        let expected = [
+            SearchResult {
+                number: Some('8'),
+                last_parsed_position: 0,
+            },
+            SearchResult {
+                number: Some('1'),
+                last_parsed_position: 3,
+            },
+            SearchResult {
+                number: Some('7'),
+                last_parsed_position: 4,
+            },
+            SearchResult {
+                number: Some('7'),
+                last_parsed_position: 0,
+            },
+            SearchResult {
+                number: None,
+                last_parsed_position: 7,
+            },
+        ];
+
+        for (pos, line) in strings.into_iter().enumerate() {
+            let res = find(line);
+
+            assert_eq!(res, expected[pos]);
+        }
+    }
+
+    #[test]
+    fn test_r_find() {
+        let strings = [
+            ("eightwothree", 0),
+            ("abcone2threexyz", 3),
+            ("treb7uchet", 4),
+            ("7pqrstsixteen", 0),
+            ("abcdefg", 7),
+            ("abcdefg", 0),
+        ];
+        let expected = [
+            SearchResult {
+                number: Some('3'),
+                last_parsed_position: 7,
+            },
+            SearchResult {
+                number: Some('3'),
+                last_parsed_position: 7,
+            },
+            SearchResult {
+                number: Some('7'),
+                last_parsed_position: 5,
+            },
+            SearchResult {
+                number: Some('6'),
+                last_parsed_position: 6,
+            },
+            SearchResult {
+                number: None,
+                // because 7 it's the smallest number by condition in previous array
+                last_parsed_position: 7,
+            },
+            SearchResult {
+                number: None,
+                last_parsed_position: 0,
+            },
+        ];
+
+        for (pos, (line, found_pos)) in strings.into_iter().enumerate() {
+            let res = r_find(line, found_pos);
+
+            assert_eq!(res, expected[pos]);
+        }
+    }
+
+    #[test]
+    fn test_extract_number() {
+        let strings = [
+            "eightwothree",
+            "abcone2threexyz",
+            "treb7uchet",
+            "7pqrstsixteen",
+            "abcdefg",
+            "x0y5",
+            "0",
+        ];
+        let expected = [Some(83), Some(13), Some(77), Some(76), None, Some(5), Some(0)];
+
+        for (pos, line) in strings.into_iter().enumerate() {
+            let result = extract_number(line);
+            assert_eq!(result, expected[pos]);
+        }
+    }
+
+    #[test]
+    fn test_first_last_digit() {
+        let strings = ["eightwothree", "treb7uchet", "abcdefg"];
+        let expected = [
+            (
+                Some(Match {
+                    digit: '8',
+                    start: 0,
+                    end: 5,
+                    kind: MatchKind::Word,
+                }),
+                Some(Match {
+                    digit: '3',
+                    start: 7,
+                    end: 12,
+                    kind: MatchKind::Word,
+                }),
+            ),
+            (
+                Some(Match {
+                    digit: '7',
+                    start: 4,
+                    end: 5,
+                    kind: MatchKind::Digit,
+                }),
+                Some(Match {
+                    digit: '7',
+                    start: 4,
+                    end: 5,
+                    kind: MatchKind::Digit,
+                }),
+            ),
+            (None, None),
+        ];
+
+        for (pos, line) in strings.into_iter().enumerate() {
+            assert_eq!((first_digit(line), last_digit(line)), expected[pos]);
+        }
+    }
+
+    #[test]
+    fn test_unicode_digits() {
+        let options = ScanOptions {
+            unicode_digits: true,
+            ..ScanOptions::default()
+        };
+        let strings = ["a\u{663}bc", "\u{96B}two", "one\u{FF19}x", "\u{663}"];
+        let expected = [Some(33), Some(52), Some(19), Some(33)];
+
+        for (pos, line) in strings.into_iter().enumerate() {
+            assert_eq!(extract_number_with(line, &options), expected[pos]);
+        }
+
+        assert_eq!(extract_number("a\u{663}bc"), None);
+        assert_eq!(extract_number("\u{96B}two"), Some(22));
+    }
+
+    #[test]
+    fn test_multibyte_text() {
+        let strings = [
+            "caf\u{e9}one\u{1F600}two",
+            "\u{1F600}\u{1F600}",
+            "na\u{ef}ve7r\u{e9}sum\u{e9}",
+            "\u{e9}\u{e9}thr\u{e9}e",
+            "\u{1F600}9\u{1F600}eight\u{1F600}",
+            "\u{440}\u{430}\u{437}\u{434}\u{432}\u{430}5",
+        ];
+        let expected = [Some(12), None, Some(77), None, Some(98), Some(55)];
+
+        for (pos, line) in strings.into_iter().enumerate() {
+            assert_eq!(extract_number(line), expected[pos]);
+        }
+
+        let line = "caf\u{e9}one\u{1F600}two";
+        assert_eq!(first_digit(line).map(|m| &line[m.start..m.end]), Some("one"));
+        assert_eq!(last_digit(line).map(|m| &line[m.start..m.end]), Some("two"));
+        assert_eq!(r_find(line, 6).number, Some('2'));
+        // the position inside the emoji
+        assert_eq!(r_find("\u{1F600}", 2).number, None);
+    }
+
+    #[test]
+    fn test_isolated_digits_only() {
+        let options = ScanOptions {
+            isolated_digits_only: true,
+            ..ScanOptions::default()
+        };
+        let strings = ["ab12cd3", "12two34", "123", "1", "x1y2z"];
+        let expected = [Some(33), Some(22), None, Some(11), Some(12)];
+
+        for (pos, line) in strings.into_iter().enumerate() {
+            assert_eq!(extract_number_with(line, &options), expected[pos]);
+        }
+
+        assert_eq!(extract_number("ab12cd3"), Some(13));
+    }
+
+    #[test]
+    fn test_cross_check() {
+        let strings = [
+            "eightwothree",
+            "abcone2threexyz",
+            "treb7uchet",
+            "abcdefg",
+            "",
+            "\u{1F600}nine\u{1F600}",
+        ];
+
+        for line in strings {
+            assert_eq!(cross_check(line, &ScanOptions::default()), Ok(()));
+        }
+    }
+
+    #[test]
+    fn test_naive_matches() {
+        let consume = ScanOptions {
+            overlap: Overlap::Consume,
+            ..ScanOptions::default()
+        };
+        let dictionary = ScanOptions {
+            dictionary: Some(Arc::new(Dictionary::parse("un 1\nune 8\nné 2\n").unwrap())),
+            ..ScanOptions::default()
+        };
+        let lines = [
+            ("two1nine", &ScanOptions::default()),
+            ("oneight", &ScanOptions::default()),
+            ("oneight", &consume),
+            ("xtwone3four", &consume),
+            ("uné3unet", &dictionary),
+            ("abcdefg", &ScanOptions::default()),
+        ];
+        let expected = [
+            Some([(0, 3, '2'), (4, 8, '9')]),
+            Some([(0, 3, '1'), (2, 7, '8')]),
+            Some([(0, 3, '1'), (0, 3, '1')]),
+            Some([(1, 4, '2'), (7, 11, '4')]),
+            Some([(0, 2, '1'), (5, 8, '8')]),
+            None,
+        ];
+
+        for (pos, (line, options)) in lines.into_iter().enumerate() {
+            let spans = naive_matches(line, options).map(|(first, last)| [first, last].map(|m| (m.start, m.end, m.digit)));
+            assert_eq!(spans, expected[pos], "{line}");
+        }
+    }
+
+    #[test]
+    fn test_fullwidth_digits() {
+        let options = ScanOptions {
+            fullwidth_digits: true,
+            ..ScanOptions::default()
+        };
+        let strings = ["\u{FF11}abc\u{FF19}", "two\u{FF10}", "\u{663}\u{FF15}"];
+        let expected = [Some(19), Some(20), Some(55)];
+
+        for (pos, line) in strings.into_iter().enumerate() {
+            assert_eq!(extract_number_with(line, &options), expected[pos]);
+        }
+
+        assert_eq!(extract_number("\u{FF11}abc\u{FF19}"), None);
+    }
+
+    #[test]
+    fn test_keycap_digits() {
+        let options = ScanOptions {
+            keycap_digits: true,
+            ..ScanOptions::default()
+        };
+        let line = "a1\u{FE0F}\u{20E3}bc9\u{20E3}d";
+
+        assert_eq!(extract_number_with(line, &options), Some(19));
+        assert_eq!(
+            backward_match(line, 0, &options),
+            Some(Match {
+                digit: '9',
+                start: 10,
+                end: 14,
+                kind: MatchKind::Digit,
+            })
+        );
+        assert_eq!(last_digit(line).map(|m| m.end), Some(11));
+
+        let isolated = ScanOptions {
+            keycap_digits: true,
+            isolated_digits_only: true,
+            ..ScanOptions::default()
+        };
+        assert_eq!(extract_number_with("1\u{FE0F}\u{20E3}2\u{FE0F}\u{20E3}x3", &isolated), Some(33));
+    }
+
+    #[test]
+    fn test_fuzzy() {
+        let options = ScanOptions {
+            fuzzy: 1,
+            ..ScanOptions::default()
+        };
+        let strings = [
+            "thre",
+            "xsevcnx",
+            "abfivx9",
+            "twothre",
+            "eightwothree",
+            "zoneight234",
+            "1xyz",
+        ];
+        let expected = [Some(33), Some(77), Some(59), Some(23), Some(83), Some(14), Some(11)];
+
+        for (pos, line) in strings.into_iter().enumerate() {
+            assert_eq!(extract_number_with(line, &options), expected[pos]);
+            assert_eq!(cross_check(line, &options), Ok(()));
+        }
+
+        assert_eq!(extract_number("xsevcnx"), None);
+    }
+
+    #[test]
+    fn test_overlap() {
+        let options = ScanOptions {
+            overlap: Overlap::Consume,
+            ..ScanOptions::default()
+        };
+        let strings = ["eightwo", "eightwothree", "xtwone3four", "oneight", "twoneight", "sevenine1"];
+        let expected = [Some(88), Some(83), Some(24), Some(11), Some(28), Some(71)];
+
+        for (pos, line) in strings.into_iter().enumerate() {
+            assert_eq!(extract_number_with(line, &options), expected[pos], "{line}");
+            assert_eq!(cross_check(line, &options), Ok(()));
+        }
+
+        let digits = |options| matches_with("twoneight", options).map(|m| m.digit).collect::<String>();
+        assert_eq!(digits(&options), "28");
+        assert_eq!(digits(&ScanOptions::default()), "218");
+        assert_eq!(r_find_with("oneight", 3, &options).number, None);
+    }
+
+    #[test]
+    fn test_word_boundaries() {
+        let options = ScanOptions {
+            word_boundaries: true,
+            ..ScanOptions::default()
+        };
+        let strings = [
+            "one threefold two",
+            "(seven) and nine.",
+            "4 someone eightfold",
+            "fivé-three",
+            "eightwo",
+            "thre 3",
+        ];
+        let expected = [Some(12), Some(79), Some(44), Some(33), None, Some(33)];
+
+        for (pos, line) in strings.into_iter().enumerate() {
+            assert_eq!(extract_number_with(line, &options), expected[pos], "{line}");
+            assert_eq!(cross_check(line, &options), Ok(()));
+        }
+
+        let fuzzy = ScanOptions { fuzzy: 1, ..options };
+        assert_eq!(extract_number_with("thre 3 sevn", &fuzzy), Some(37));
+        assert_eq!(extract_number_with("eightwo", &ScanOptions::default()), Some(82));
+    }
+
+    #[test]
+    fn test_arbitrary_input() {
+        // the same checks as the fuzz targets, on random bytes of digits, words and multibyte chars
+        let pieces: [&[u8]; 13] = [
+            b"one",
+            b"un",
+            b"eight",
+            b"thre",
+            b"7",
+            b"x",
+            "é".as_bytes(),
+            "٣".as_bytes(),
+            "３".as_bytes(),
+            "\u{FE0F}\u{20E3}".as_bytes(),
+            b"\xC3",
+            b"\xFF",
+            b"\0",
+        ];
+        let dictionary = Arc::new(Dictionary::parse("un 1\nune 8\nné 2\n").unwrap());
+        let mut generator = crate::Generator::new(5);
+
+        for _ in 0..20_000 {
+            let flags = generator.below(256);
+            let options = ScanOptions {
+                unicode_digits: flags & 1 != 0,
+                fullwidth_digits: flags & 2 != 0,
+                keycap_digits: flags & 4 != 0,
+                isolated_digits_only: flags & 8 != 0,
+                fuzzy: usize::from(flags & 16 != 0),
+                word_boundaries: flags & 64 != 0,
+                dictionary: (flags & 128 != 0).then(|| dictionary.clone()),
+                overlap: if flags & 32 == 0 { Overlap::Allow } else { Overlap::Consume },
+            };
+            let plain = ScanOptions {
+                dictionary: options.dictionary.clone(),
+                overlap: options.overlap,
+                ..ScanOptions::default()
+            };
+            let mut bytes = Vec::new();
+            for _ in 0..generator.below(10) {
+                bytes.extend_from_slice(pieces[generator.below(pieces.len())]);
+            }
+            let line = String::from_utf8_lossy(&bytes);
+            let found_pos = generator.below(line.len() + 3);
+
+            let value = extract_number_with(&line, &options);
+            assert_eq!(value, crate::extract_line_with(&line, &options).ok().map(|value| value.value));
+            assert!(find_with(&line, &options).last_parsed_position <= line.len());
+            assert!(r_find_with(&line, found_pos, &options).last_parsed_position <= line.len());
+            if options.fuzzy == 0 {
+                assert_eq!(cross_check(&line, &options), Ok(()), "{line:?}");
+            }
+            let value = crate::extract_line_with(&line, &plain).ok();
+            assert_eq!(value.map(|value| (value.first, value.last)), naive_matches(&line, &plain), "{line:?}");
+            let dictionary = options.dictionary();
+            assert!(dictionary.word_at(&line, found_pos).is_none() || found_pos < line.len());
+            assert!(dictionary.word_before(&line, found_pos).is_none() || found_pos <= line.len());
+        }
+    }
+}
@@ -0,0 +1,353 @@
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    net::TcpListener,
+    str,
+    sync::{Arc, Mutex, PoisonError},
+    thread,
+    time::Instant,
+};
+
+use clap::Args;
+use find_digits::Summary;
+
+use crate::{
+    process::{self, Processing},
+    response::{self, Extracted, Failure, StatsBody, Totals},
+    rpc, websocket,
+};
+
+/// The largest accepted request body
+const MAX_BODY: usize = 64 << 20;
+
+#[derive(Args, Debug, Clone)]
+pub struct ServeArgs {
+    /// Where to accept the HTTP connections
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    address: String,
+
+    /// Answer newline-delimited JSON-RPC 2.0 requests (`extract`, `stats`, `shutdown`) of stdin on stdout instead,
+    /// e.g. for editor plugins keeping one process
+    #[arg(long)]
+    stdio: bool,
+}
+
+/// The totals of all the requests since the start
+#[derive(Default)]
+pub struct Stats {
+    pub requests: u64,
+    pub summary: Summary,
+}
+
+struct Request {
+    method: String,
+    path: String,
+    body: Vec<u8>,
+    close: bool,
+    /// of a WebSocket handshake
+    websocket_key: Option<String>,
+}
+
+/// Serve `POST /extract` (the lines of the body), `GET /stats` (the totals of all the extracted lines)
+/// and the WebSocket endpoint `/ws` (the lines of the messages)
+pub fn run(args: &ServeArgs, processing: Processing) -> Result<(), String> {
+    if args.stdio {
+        return rpc::run(processing);
+    }
+
+    let listener = TcpListener::bind(&args.address).map_err(|e| format!("Fail to listen on {}: {e}", args.address))?;
+    println!("Serving http://{}", args.address);
+
+    let processing = Arc::new(processing);
+    process::reload_on_hangup(&processing);
+    let stats = Arc::new(Mutex::new(Stats::default()));
+
+    for stream in listener.incoming() {
+        let stream = stream.map_err(|e| format!("Fail to accept connection on {}: {e}", args.address))?;
+        // the small responses must not wait for the acknowledgments of the previous ones
+        let _ = stream.set_nodelay(true);
+        let peer = stream
+            .peer_addr()
+            .map_or_else(|_| args.address.clone(), |peer| peer.to_string());
+        let (processing, stats) = (processing.clone(), stats.clone());
+        thread::spawn(move || {
+            let (connection, start) = (Mutex::new(processing.summary()), Instant::now());
+            if let Err(e) = serve(BufReader::new(&stream), &stream, &processing, &stats, &connection) {
+                println!("Connection failed: {e}");
+            }
+
+            let connection = connection.into_inner().unwrap_or_else(PoisonError::into_inner);
+            if connection.parsed_lines > 0 {
+                processing.record_history(&peer, &connection, start.elapsed());
+            }
+        });
+    }
+
+    Ok(())
+}
+
+/// Answer the requests of one keep-alive connection, the totals of its lines are added to `connection`
+fn serve(
+    mut input: impl BufRead,
+    mut output: impl Write + Send,
+    processing: &Processing,
+    stats: &Mutex<Stats>,
+    connection: &Mutex<Summary>,
+) -> io::Result<()> {
+    loop {
+        let request = match read_request(&mut input) {
+            Ok(Some(request)) => request,
+            Ok(None) => return Ok(()),
+            Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+                return write_response(&mut output, "400 Bad Request", &error(&e.to_string()));
+            },
+            Err(e) => return Err(e),
+        };
+
+        if let ("GET", "/ws", Some(key)) = (request.method.as_str(), request.path.as_str(), &request.websocket_key) {
+            output.write_all(websocket::handshake(key).as_bytes())?;
+            return websocket::session(input, output, processing, stats, connection, websocket::SNAPSHOT_INTERVAL);
+        }
+
+        let (code, body) = match (request.method.as_str(), request.path.as_str()) {
+            ("POST", "/extract") => match extract(&request.body, processing, stats, connection) {
+                Ok(body) => ("200 OK", body),
+                Err(e) => ("422 Unprocessable Entity", error(&e)),
+            },
+            ("GET", "/stats") => {
+                let stats = StatsBody::from(&*stats.lock().unwrap_or_else(PoisonError::into_inner));
+                ("200 OK", response::json(&stats))
+            },
+            ("GET", "/ws") => ("400 Bad Request", error("WebSocket handshake expected")),
+            (_, "/extract" | "/stats" | "/ws") => ("405 Method Not Allowed", error("method not allowed")),
+            _ => ("404 Not Found", error("not found")),
+        };
+
+        write_response(&mut output, code, &body)?;
+        if request.close {
+            return Ok(());
+        }
+    }
+}
+
+/// `None` when the connection is closed between the requests
+fn read_request(input: &mut impl BufRead) -> io::Result<Option<Request>> {
+    let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_owned());
+
+    let mut request_line = String::new();
+    if input.read_line(&mut request_line)? == 0 {
+        return Ok(None);
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target), Some(version)) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(invalid("malformed request line"));
+    };
+    let path = target.split('?').next().unwrap_or_default();
+
+    let mut content_length = 0;
+    let mut close = version == "HTTP/1.0";
+    let mut websocket_key = None;
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+
+        let Some((name, value)) = header.split_once(':') else {
+            return Err(invalid("malformed header"));
+        };
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("content-length") {
+            content_length = value.parse().map_err(|_| invalid("invalid content length"))?;
+        } else if name.eq_ignore_ascii_case("transfer-encoding") {
+            return Err(invalid("chunked bodies aren't supported, send the content length"));
+        } else if name.eq_ignore_ascii_case("connection") {
+            close = value.eq_ignore_ascii_case("close");
+        } else if name.eq_ignore_ascii_case("sec-websocket-key") {
+            websocket_key = Some(value.to_owned());
+        }
+    }
+
+    if content_length > MAX_BODY {
+        return Err(invalid("request body too large"));
+    }
+
+    let mut body = vec![0; content_length];
+    input.read_exact(&mut body)?;
+
+    Ok(Some(Request {
+        method: method.to_owned(),
+        path: path.to_owned(),
+        body,
+        close,
+        websocket_key,
+    }))
+}
+
+/// The values of the lines of the body (`null` for a line without a value) and their totals,
+/// `Err` of a line failing the whole request, e.g. of `--on-no-digits error`
+fn extract(
+    body: &[u8],
+    processing: &Processing,
+    stats: &Mutex<Stats>,
+    connection: &Mutex<Summary>,
+) -> Result<String, String> {
+    let mut summary = processing.summary();
+    let mut cache = processing.line_cache();
+
+    let mut lines: Vec<&[u8]> = body.split(|&byte| byte == b'\n').collect();
+    if lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+
+    let values = lines
+        .into_iter()
+        .enumerate()
+        .map(|(number, line)| {
+            let line = line.strip_suffix(b"\r").unwrap_or(line);
+            summary.parsed_lines += 1;
+
+            processing.process_bytes("request", number, line, &mut summary, &mut cache)
+        })
+        .collect::<Result<Vec<Option<u32>>, String>>()?;
+
+    let mut stats = stats.lock().unwrap_or_else(PoisonError::into_inner);
+    stats.requests += 1;
+    stats.summary.merge(&summary);
+    connection
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .merge(&summary);
+
+    Ok(response::json(&Extracted {
+        values,
+        totals: Totals::from(&summary),
+    }))
+}
+
+fn error(message: &str) -> String {
+    response::json(&Failure { error: message })
+}
+
+fn write_response(output: &mut impl Write, status: &str, body: &str) -> io::Result<()> {
+    // a single write, the stream isn't buffered
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}\n",
+        body.len() + 1
+    );
+    output.write_all(response.as_bytes())
+}
+
+#[cfg(test)]
+mod test {
+    use clap::Parser;
+
+    use super::*;
+
+    #[test]
+    fn test_serve() {
+        let processing = Processing::new(&crate::Args::parse_from(["find_digits", "serve"])).unwrap();
+        let stats = Mutex::new(Stats::default());
+
+        let requests = [
+            "POST /extract HTTP/1.1\r\nContent-Length: 31\r\n\r\ntwo1nine\r\nabcdefg\neightwothree\n",
+            "POST /extract?verbose HTTP/1.1\r\ncontent-length: 5\r\n\r\n4nine",
+            "GET /stats HTTP/1.1\r\n\r\n",
+            "GET /extract HTTP/1.1\r\n\r\n",
+            "GET /metrics HTTP/1.1\r\nConnection: close\r\n\r\n",
+            "GET /stats HTTP/1.1\r\n\r\n",
+        ];
+        let mut output = Vec::new();
+        serve(requests.concat().as_bytes(), &mut output, &processing, &stats, &Mutex::default()).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        let responses: Vec<&str> = output
+            .split("HTTP/1.1 ")
+            .skip(1)
+            .flat_map(|response| {
+                let (code, _) = response.split_once("\r\n").unwrap();
+                let (_, body) = response.split_once("\r\n\r\n").unwrap();
+                assert!(response.contains(&format!("Content-Length: {}\r\n", body.len())));
+                [code, body.trim_end()]
+            })
+            .collect();
+
+        // the connection is closed after the request asking for it
+        assert_eq!(
+            responses,
+            [
+                "200 OK",
+                "{\"values\":[29,null,83],\"sum\":112,\"parsed_lines\":3,\"incorrect_lines\":1}",
+                "200 OK",
+                "{\"values\":[49],\"sum\":49,\"parsed_lines\":1,\"incorrect_lines\":0}",
+                "200 OK",
+                "{\"requests\":2,\"sum\":161,\"parsed_lines\":4,\"incorrect_lines\":1}",
+                "405 Method Not Allowed",
+                "{\"error\":\"method not allowed\"}",
+                "404 Not Found",
+                "{\"error\":\"not found\"}",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_utf8() {
+        let rejects = crate::testing::temp_file(".txt", "");
+        let args = crate::Args::parse_from(["find_digits", "--rejects", rejects.to_str().unwrap(), "serve"]);
+        let processing = Processing::new(&args).unwrap();
+
+        let extracted = extract(b"two1nine\n\xFF7\n", &processing, &Mutex::default(), &Mutex::default()).unwrap();
+        assert_eq!(extracted, "{\"values\":[29,null],\"sum\":29,\"parsed_lines\":2,\"incorrect_lines\":1}");
+        processing.commit_output().unwrap();
+        assert_eq!(std::fs::read_to_string(&rejects).unwrap(), "1\tinvalid_utf8\n");
+    }
+
+    #[test]
+    fn test_bad_request() {
+        let processing = Processing::new(&crate::Args::parse_from(["find_digits", "serve"])).unwrap();
+        let requests = [
+            "garbage\r\n\r\n",
+            "POST /extract HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n",
+            "POST /extract HTTP/1.1\r\nContent-Length: many\r\n\r\n",
+        ];
+
+        for request in requests {
+            let mut output = Vec::new();
+            serve(request.as_bytes(), &mut output, &processing, &Mutex::default(), &Mutex::default()).unwrap();
+            assert!(
+                String::from_utf8(output)
+                    .unwrap()
+                    .starts_with("HTTP/1.1 400 Bad Request\r\n"),
+                "{request}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_no_digits_error() {
+        let args = crate::Args::parse_from(["find_digits", "--on-no-digits", "error", "serve"]);
+        let processing = Processing::new(&args).unwrap();
+        let stats = Mutex::new(Stats::default());
+        let requests = [
+            "POST /extract HTTP/1.1\r\nContent-Length: 13\r\n\r\ntwo1nine\nabc\n",
+            "POST /extract HTTP/1.1\r\nContent-Length: 8\r\n\r\ntwo1nine",
+        ];
+
+        // the failed request isn't counted, the connection goes on
+        let mut output = Vec::new();
+        serve(requests.concat().as_bytes(), &mut output, &processing, &stats, &Mutex::default()).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("HTTP/1.1 422 Unprocessable Entity\r\n"), "{output}");
+        assert!(
+            output.contains("{\"error\":\"file request line number 1 has no digits, line: \\\"abc\\\"\"}"),
+            "{output}"
+        );
+        assert!(output.contains("HTTP/1.1 200 OK\r\n"), "{output}");
+        assert_eq!(stats.lock().unwrap().requests, 1);
+    }
+}
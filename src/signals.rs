@@ -0,0 +1,89 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, OnceLock,
+};
+
+/// Set by `SIGUSR1`, the progress is reported as soon as possible then
+static PROGRESS: OnceLock<Arc<AtomicBool>> = OnceLock::new();
+/// Set by `SIGINT` once `trap_interrupt` is called, the reading stops then
+static INTERRUPT: OnceLock<Arc<AtomicBool>> = OnceLock::new();
+/// Set by `SIGHUP` once `trap_hangup` is called, the configuration is read again then
+static RELOAD: OnceLock<Arc<AtomicBool>> = OnceLock::new();
+
+/// Exit code of an interrupted run, 128 + `SIGINT` as the shells report it
+pub const EXIT_INTERRUPTED: i32 = 130;
+
+/// Start listening for `SIGUSR1`, a failure leaves the default handling of the signal
+pub fn init() {
+    #[cfg(unix)]
+    if let Err(e) = signal_hook::flag::register(signal_hook::consts::SIGUSR1, flag(&PROGRESS)) {
+        println!("Fail to handle SIGUSR1: {e}");
+    }
+}
+
+/// Stop the reading on Ctrl-C and report the partial totals, a second Ctrl-C terminates the process right away
+///
+/// Only the runs ending on their own trap it, the servers are stopped by Ctrl-C as usual.
+pub fn trap_interrupt() {
+    #[cfg(unix)]
+    {
+        use signal_hook::{consts::SIGINT, flag};
+
+        let interrupt = self::flag(&INTERRUPT);
+        // the shutdown is checked before the flag is set by the same signal
+        let result = flag::register_conditional_shutdown(SIGINT, EXIT_INTERRUPTED, interrupt.clone())
+            .and_then(|_| flag::register(SIGINT, interrupt));
+        if let Err(e) = result {
+            println!("Fail to handle SIGINT: {e}");
+        }
+    }
+}
+
+/// Request the reload of the configuration on `SIGHUP` instead of terminating the process
+///
+/// Only the servers and the followed files trap it, the other runs end on their own.
+pub fn trap_hangup() {
+    #[cfg(unix)]
+    if let Err(e) = signal_hook::flag::register(signal_hook::consts::SIGHUP, flag(&RELOAD)) {
+        println!("Fail to handle SIGHUP: {e}");
+    }
+}
+
+/// `true` once per received `SIGUSR1`
+pub fn progress_requested() -> bool {
+    PROGRESS.get().is_some_and(|flag| flag.swap(false, Ordering::Relaxed))
+}
+
+/// `true` once per received `SIGHUP`
+pub fn reload_requested() -> bool {
+    RELOAD.get().is_some_and(|flag| flag.swap(false, Ordering::Relaxed))
+}
+
+/// `true` since the first Ctrl-C
+pub fn interrupted() -> bool {
+    INTERRUPT.get().is_some_and(|flag| flag.load(Ordering::Relaxed))
+}
+
+/// Set on Ctrl-C, e.g. to stop waiting for the input
+pub fn interrupt_flag() -> Arc<AtomicBool> {
+    flag(&INTERRUPT)
+}
+
+fn flag(flag: &OnceLock<Arc<AtomicBool>>) -> Arc<AtomicBool> {
+    Arc::clone(flag.get_or_init(Arc::default))
+}
+
+#[cfg(all(test, unix))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_progress_requested() {
+        init();
+
+        // the default action of the signal terminates the process, the pipelines of the other tests may take the request
+        signal_hook::low_level::raise(signal_hook::consts::SIGUSR1).unwrap();
+        let _ = progress_requested();
+        assert!(!progress_requested());
+    }
+}
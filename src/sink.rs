@@ -0,0 +1,331 @@
+#[cfg(any(feature = "sqlite", feature = "parquet"))]
+use std::sync::{Mutex, PoisonError};
+#[cfg(feature = "sqlite")]
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{path::Path, time::Duration};
+
+#[cfg(any(feature = "sqlite", feature = "parquet"))]
+use find_digits::{matches_with, Match};
+use find_digits::{LineValue, ScanOptions, Summary};
+#[cfg(feature = "sqlite")]
+use rusqlite::{params, Connection};
+
+#[cfg(feature = "parquet")]
+use crate::columnar::LinesWriter;
+#[cfg(feature = "parquet")]
+use crate::report;
+use crate::report::FileTotals;
+
+#[cfg(feature = "sqlite")]
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS runs (
+        id INTEGER PRIMARY KEY,
+        input TEXT NOT NULL,
+        started INTEGER NOT NULL,
+        elapsed_seconds REAL,
+        parsed_lines INTEGER,
+        incorrect_lines INTEGER,
+        excluded_lines INTEGER,
+        total_sum INTEGER
+    );
+    CREATE TABLE IF NOT EXISTS lines (
+        run INTEGER NOT NULL REFERENCES runs (id),
+        file TEXT NOT NULL,
+        line INTEGER NOT NULL,
+        value INTEGER,
+        tokens TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS files (
+        run INTEGER NOT NULL REFERENCES runs (id),
+        file TEXT NOT NULL,
+        elapsed_seconds REAL,
+        parsed_lines INTEGER,
+        incorrect_lines INTEGER,
+        total_sum INTEGER
+    );
+";
+
+/// Receives a row per processed line and the totals of the run,
+/// `sqlite://results.db` or `parquet://results.parquet`
+///
+/// Nothing is visible until [`Sink::finish`]: the rows of a database are inserted in a single transaction,
+/// a Parquet file is renamed once complete, a failed or interrupted run leaves the previous results as they were.
+pub struct Sink {
+    backend: Backend,
+}
+
+/// There's none without the `sqlite` and `parquet` features
+enum Backend {
+    #[cfg(feature = "sqlite")]
+    Sqlite {
+        database: Mutex<Connection>,
+        /// the id of the row of the run
+        run: i64,
+    },
+    /// it's gone once finished
+    #[cfg(feature = "parquet")]
+    Parquet(Mutex<Option<LinesWriter>>),
+}
+
+impl Sink {
+    /// Start a run of the input in the database or the file of the URL
+    pub fn open(url: &str, input: &str) -> Result<Self, String> {
+        let backend = if let Some(path) = url.strip_prefix("sqlite://") {
+            Backend::sqlite(Path::new(path), input)?
+        } else if let Some(path) = url.strip_prefix("parquet://") {
+            Backend::parquet(Path::new(path))?
+        } else {
+            return Err(format!("Unsupported sink {url}, expected sqlite://results.db or parquet://results.parquet"));
+        };
+
+        Ok(Self { backend })
+    }
+
+    /// Add the row of the line, the value is null and the tokens are empty when it has no value
+    ///
+    /// `line` is the line after the confusables replacement.
+    pub fn add_line(
+        &self,
+        file: &str,
+        number: usize,
+        line: &str,
+        value: Option<&LineValue>,
+        options: &ScanOptions,
+    ) -> Result<(), String> {
+        #[cfg(not(any(feature = "sqlite", feature = "parquet")))]
+        let _ = (file, number, line, value, options);
+
+        match self.backend {
+            #[cfg(feature = "sqlite")]
+            Backend::Sqlite { ref database, run } => {
+                let tokens = json_list(&value.map(|value| tokens(line, value, options)).unwrap_or_default());
+                let database = database.lock().unwrap_or_else(PoisonError::into_inner);
+                database
+                    .prepare_cached("INSERT INTO lines (run, file, line, value, tokens) VALUES (?1, ?2, ?3, ?4, ?5)")
+                    .and_then(|mut insert| insert.execute(params![run, file, number, value.map(|v| v.value), tokens]))
+                    .map_err(|e| format!("Fail to insert line number {number} into the results database: {e}"))?;
+                Ok(())
+            },
+            #[cfg(feature = "parquet")]
+            Backend::Parquet(ref writer) => {
+                let tokens = value.map(|value| tokens(line, value, options)).unwrap_or_default();
+                match writer.lock().unwrap_or_else(PoisonError::into_inner).as_mut() {
+                    Some(writer) => writer.add(file, number, value.map(|v| v.value), tokens),
+                    None => Err("Parquet results file is already finished".to_owned()),
+                }
+            },
+        }
+    }
+
+    /// Record the totals of the run and of its inputs, when it has several ones, and make its rows visible
+    pub fn finish(&self, summary: &Summary, files: &[FileTotals], elapsed: Duration) -> Result<(), String> {
+        #[cfg(not(any(feature = "sqlite", feature = "parquet")))]
+        let _ = (summary, files, elapsed);
+
+        match self.backend {
+            #[cfg(feature = "sqlite")]
+            Backend::Sqlite { ref database, run } => {
+                let database = database.lock().unwrap_or_else(PoisonError::into_inner);
+                for totals in files {
+                    database
+                        .prepare_cached(
+                            "INSERT INTO files (run, file, elapsed_seconds, parsed_lines, incorrect_lines, total_sum) \
+                             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                        )
+                        .and_then(|mut insert| {
+                            insert.execute(params![
+                                run,
+                                totals.file,
+                                totals.elapsed.as_secs_f64(),
+                                totals.parsed_lines,
+                                totals.incorrect_lines,
+                                totals.total_sum
+                            ])
+                        })
+                        .map_err(|e| format!("Fail to insert file {} into the results database: {e}", totals.file))?;
+                }
+                database
+                    .execute(
+                        "UPDATE runs SET elapsed_seconds = ?2, parsed_lines = ?3, incorrect_lines = ?4, \
+                         excluded_lines = ?5, total_sum = ?6 WHERE id = ?1",
+                        params![
+                            run,
+                            elapsed.as_secs_f64(),
+                            summary.parsed_lines,
+                            summary.incorrect_lines,
+                            summary.excluded_lines,
+                            summary.total_sum
+                        ],
+                    )
+                    .and_then(|_| database.execute_batch("COMMIT"))
+                    .map_err(|e| format!("Fail to commit the results database: {e}"))
+            },
+            #[cfg(feature = "parquet")]
+            Backend::Parquet(ref writer) => match writer.lock().unwrap_or_else(PoisonError::into_inner).take() {
+                Some(writer) => writer.finish(vec![
+                    ("find_digits.parsed_lines", summary.parsed_lines.to_string()),
+                    ("find_digits.incorrect_lines", summary.incorrect_lines.to_string()),
+                    ("find_digits.excluded_lines", summary.excluded_lines.to_string()),
+                    ("find_digits.total_sum", summary.total_sum.to_string()),
+                    ("find_digits.elapsed_seconds", elapsed.as_secs_f64().to_string()),
+                    ("find_digits.files", report::files_json(files)),
+                ]),
+                None => Err("Parquet results file is already finished".to_owned()),
+            },
+        }
+    }
+}
+
+impl Backend {
+    #[cfg(feature = "sqlite")]
+    fn sqlite(path: &Path, input: &str) -> Result<Self, String> {
+        let error = |e: rusqlite::Error| format!("Fail to write results database {}: {e}", path.display());
+
+        let database = Connection::open(path).map_err(error)?;
+        database.execute_batch(SCHEMA).map_err(error)?;
+        database.execute_batch("BEGIN").map_err(error)?;
+
+        let started = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        database
+            .execute("INSERT INTO runs (input, started) VALUES (?1, ?2)", params![input, started.as_secs()])
+            .map_err(error)?;
+
+        Ok(Self::Sqlite {
+            run: database.last_insert_rowid(),
+            database: Mutex::new(database),
+        })
+    }
+
+    #[cfg(not(feature = "sqlite"))]
+    fn sqlite(path: &Path, input: &str) -> Result<Self, String> {
+        let _ = (path, input);
+        Err("SQLite sink isn't supported by this build, rebuild it with `--features sqlite`".to_owned())
+    }
+
+    #[cfg(feature = "parquet")]
+    fn parquet(path: &Path) -> Result<Self, String> {
+        Ok(Self::Parquet(Mutex::new(Some(LinesWriter::create(path)?))))
+    }
+
+    #[cfg(not(feature = "parquet"))]
+    fn parquet(path: &Path) -> Result<Self, String> {
+        let _ = path;
+        Err("Parquet sink isn't supported by this build, rebuild it with `--features parquet`".to_owned())
+    }
+}
+
+/// The matched tokens of the line in order, e.g. `eight`, `two` and `three`
+#[cfg(any(feature = "sqlite", feature = "parquet"))]
+fn tokens(line: &str, value: &LineValue, options: &ScanOptions) -> Vec<String> {
+    let mut matches: Vec<Match> = matches_with(line, options).collect();
+    // the last match found by the reverse scan isn't necessarily among the forward matches, e.g. of `--fuzzy`
+    if !matches.contains(&value.last) {
+        matches.push(value.last);
+    }
+
+    matches.iter().map(|m| line[m.start..m.end].to_owned()).collect()
+}
+
+/// JSON array of the tokens, e.g. `["eight", "two", "three"]`
+#[cfg(feature = "sqlite")]
+fn json_list(tokens: &[String]) -> String {
+    serde_json::to_string(tokens).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    #[cfg(any(feature = "sqlite", feature = "parquet"))]
+    use find_digits::extract_line;
+
+    use super::*;
+    #[cfg(feature = "sqlite")]
+    use crate::testing::temp_file;
+
+    #[cfg(any(feature = "sqlite", feature = "parquet"))]
+    #[test]
+    fn test_tokens() {
+        let options = ScanOptions::default();
+        let strings = ["eightwothree", "treb7uchet", "4nineeightseven2"];
+        let expected = [
+            &["eight", "two", "three"][..],
+            &["7"],
+            &["4", "nine", "eight", "seven", "2"],
+        ];
+
+        for (pos, line) in strings.into_iter().enumerate() {
+            assert_eq!(tokens(line, &extract_line(line).unwrap(), &options), expected[pos]);
+        }
+    }
+
+    #[test]
+    fn test_open() {
+        assert!(Sink::open("postgres://localhost/results", "in.txt").is_err());
+        #[cfg(not(feature = "sqlite"))]
+        assert!(Sink::open("sqlite://results.db", "in.txt").is_err());
+        #[cfg(not(feature = "parquet"))]
+        assert!(Sink::open("parquet://results.parquet", "in.txt").is_err());
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn test_sqlite() {
+        let path = temp_file(".db", "");
+        let url = format!("sqlite://{}", path.display());
+        let options = ScanOptions::default();
+
+        for _ in 0..2 {
+            let sink = Sink::open(&url, "in.txt").unwrap();
+            for (number, line) in ["two1nine", "abcdefg", "eightwothree"].into_iter().enumerate() {
+                sink.add_line("in.txt", number, line, extract_line(line).ok().as_ref(), &options)
+                    .unwrap();
+            }
+            let summary = Summary {
+                parsed_lines: 3,
+                incorrect_lines: 1,
+                total_sum: 112,
+                ..Summary::default()
+            };
+            let files = [FileTotals::new("in.txt", &summary, Duration::from_millis(4))];
+            sink.finish(&summary, &files, Duration::from_millis(5)).unwrap();
+        }
+        // an unfinished run isn't committed
+        let sink = Sink::open(&url, "in.txt").unwrap();
+        sink.add_line("in.txt", 0, "two1nine", extract_line("two1nine").ok().as_ref(), &options)
+            .unwrap();
+        drop(sink);
+
+        let database = Connection::open(&path).unwrap();
+        let runs: Vec<(i64, i64)> = database
+            .prepare("SELECT id, total_sum FROM runs ORDER BY id")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(runs, [(1, 112), (2, 112)]);
+        let files: Vec<(i64, String, i64)> = database
+            .prepare("SELECT run, file, parsed_lines FROM files ORDER BY run")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(files, [(1, "in.txt".to_owned(), 3), (2, "in.txt".to_owned(), 3)]);
+
+        let lines: Vec<(i64, Option<i64>, String)> = database
+            .prepare("SELECT line, value, tokens FROM lines WHERE run = 2 ORDER BY line")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            lines,
+            [
+                (0, Some(29), "[\"two\",\"1\",\"nine\"]".to_owned()),
+                (1, None, "[]".to_owned()),
+                (2, Some(83), "[\"eight\",\"two\",\"three\"]".to_owned()),
+            ]
+        );
+    }
+}
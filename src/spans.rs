@@ -0,0 +1,54 @@
+use find_digits::LineValue;
+use serde::Serialize;
+
+#[derive(Serialize)]
+struct Span<'a> {
+    file: &'a str,
+    line: usize,
+    #[serde(flatten)]
+    value: &'a LineValue,
+}
+
+/// The first and the last matches of the line as a JSON object on a single line
+///
+/// The byte offsets are within the line, after the confusables replacement.
+pub fn render(file: &str, number: usize, value: &LineValue) -> String {
+    crate::response::json(&Span {
+        file,
+        line: number,
+        value,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use find_digits::extract_line;
+
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let strings = ["two1nine", "treb7uchet"];
+        let expected = [
+            "{\"file\":\"in.txt\",\"line\":0,\"value\":29,\"first\":{\"digit\":2,\"start\":0,\"end\":3,\
+             \"kind\":\"word\"},\"last\":{\"digit\":9,\"start\":4,\"end\":8,\"kind\":\"word\"}}",
+            "{\"file\":\"in.txt\",\"line\":1,\"value\":77,\"first\":{\"digit\":7,\"start\":4,\"end\":5,\
+             \"kind\":\"digit\"},\"last\":{\"digit\":7,\"start\":4,\"end\":5,\"kind\":\"digit\"}}",
+        ];
+
+        for (pos, line) in strings.into_iter().enumerate() {
+            assert_eq!(render("in.txt", pos, &extract_line(line).unwrap()), expected[pos]);
+        }
+    }
+
+    #[test]
+    fn test_schema() {
+        let value = extract_line("xtwone3four").unwrap();
+        let mut rendered: serde_json::Value = serde_json::from_str(&render("in.txt", 0, &value)).unwrap();
+        let object = rendered.as_object_mut().unwrap();
+        object.remove("file");
+        object.remove("line");
+
+        assert_eq!(rendered, serde_json::to_value(value).unwrap());
+    }
+}
@@ -0,0 +1,587 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+    fmt,
+};
+
+use crate::{LineValue, Match, MatchKind};
+
+/// Amount of the possible line values `0..=99`
+const VALUES: usize = 100;
+
+/// Distribution of the line values, the memory is constant: a counter per possible value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineStats {
+    counts: [u64; VALUES],
+}
+
+impl Default for LineStats {
+    fn default() -> Self {
+        Self { counts: [0; VALUES] }
+    }
+}
+
+/// The counts of all the values `0..=99` in order
+#[cfg(feature = "serde")]
+impl serde::Serialize for LineStats {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.counts.as_slice().serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for LineStats {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let counts = Vec::<u64>::deserialize(deserializer)?;
+        let counts = counts.try_into().map_err(|counts: Vec<u64>| {
+            serde::de::Error::custom(format!("{} counts of the values instead of {VALUES}", counts.len()))
+        })?;
+        Ok(Self { counts })
+    }
+}
+
+impl LineStats {
+    /// Count a line value, values above `99` are counted as `99`
+    pub fn add(&mut self, value: u32) {
+        self.add_count(value, 1);
+    }
+
+    /// Count a value seen `count` times, e.g. restored from saved [`LineStats::counts`]
+    pub fn add_count(&mut self, value: u32, count: u64) {
+        let index = usize::try_from(value).map_or(VALUES - 1, |value| value.min(VALUES - 1));
+        self.counts[index] += count;
+    }
+
+    pub fn merge(&mut self, other: &Self) {
+        for (count, other) in self.counts.iter_mut().zip(other.counts) {
+            *count += other;
+        }
+    }
+
+    /// The amount of the counted values
+    #[must_use]
+    pub fn count(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// The values with their counts, the values which weren't seen are skipped
+    pub fn counts(&self) -> impl Iterator<Item = (u32, u64)> + '_ {
+        (0..).zip(self.counts).filter(|&(_, count)| count != 0)
+    }
+
+    #[must_use]
+    pub fn min(&self) -> Option<u32> {
+        self.counts().next().map(|(value, _)| value)
+    }
+
+    #[must_use]
+    pub fn max(&self) -> Option<u32> {
+        self.counts().last().map(|(value, _)| value)
+    }
+
+    #[must_use]
+    pub fn mean(&self) -> Option<f64> {
+        let count = self.count();
+        (count != 0).then(|| {
+            self.counts()
+                .map(|(value, count)| f64::from(value) * count as f64)
+                .sum::<f64>()
+                / count as f64
+        })
+    }
+
+    /// The value below which the `rank` (`0.0..=1.0`) part of the values are, the nearest rank method
+    #[must_use]
+    pub fn quantile(&self, rank: f64) -> Option<u32> {
+        let count = self.count();
+        if count == 0 {
+            return None;
+        }
+
+        // the 1-based position of the value in the sorted values
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let position = ((rank.clamp(0.0, 1.0) * count as f64).ceil() as u64).max(1);
+
+        let mut seen = 0;
+        self.counts().find_map(|(value, count)| {
+            seen += count;
+            (seen >= position).then_some(value)
+        })
+    }
+
+    /// The middle value, the mean of the two middle values for an even amount of values
+    #[must_use]
+    pub fn median(&self) -> Option<f64> {
+        let count = self.count();
+        let lower = self.nth(count.checked_sub(1)? / 2)?;
+        let upper = self.nth(count / 2)?;
+
+        Some(f64::from(lower + upper) / 2.0)
+    }
+
+    /// The population standard deviation
+    #[must_use]
+    pub fn std_dev(&self) -> Option<f64> {
+        let mean = self.mean()?;
+        let squares: f64 = self
+            .counts()
+            .map(|(value, count)| (f64::from(value) - mean).powi(2) * count as f64)
+            .sum();
+
+        Some((squares / self.count() as f64).sqrt())
+    }
+
+    /// The counts of the values in the buckets `start..start + width` which cover `0..=99`, empty buckets included
+    #[must_use]
+    pub fn buckets(&self, width: u32) -> Vec<(u32, u64)> {
+        let width = width.max(1);
+        let mut buckets: Vec<(u32, u64)> = (0..)
+            .step_by(width as usize)
+            .take_while(|&start| start < 100)
+            .map(|start| (start, 0))
+            .collect();
+
+        for (value, count) in self.counts() {
+            buckets[(value / width) as usize].1 += count;
+        }
+
+        buckets
+    }
+
+    /// How often every digit `0..=9` was the first digit of a line, the value is `first * 10 + last`
+    #[must_use]
+    pub fn first_digits(&self) -> [u64; 10] {
+        let mut digits = [0; 10];
+        for (value, count) in self.counts() {
+            digits[(value / 10) as usize % 10] += count;
+        }
+
+        digits
+    }
+
+    /// How often every digit `0..=9` was the last digit of a line
+    #[must_use]
+    pub fn last_digits(&self) -> [u64; 10] {
+        let mut digits = [0; 10];
+        for (value, count) in self.counts() {
+            digits[(value % 10) as usize] += count;
+        }
+
+        digits
+    }
+
+    /// The value at the 0-based position in the sorted values
+    fn nth(&self, position: u64) -> Option<u32> {
+        let mut seen = 0;
+        self.counts().find_map(|(value, count)| {
+            seen += count;
+            (seen > position).then_some(value)
+        })
+    }
+}
+
+impl fmt::Display for LineStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.min(), self.max(), self.mean(), self.median(), self.std_dev()) {
+            (Some(min), Some(max), Some(mean), Some(median), Some(std_dev)) => {
+                write!(f, "min {min}, max {max}, mean {mean:.2}, median {median}, standard deviation {std_dev:.2}")
+            },
+            _ => write!(f, "no values"),
+        }
+    }
+}
+
+/// How often every token was matched as the first or the last digit of a line
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TokenStats {
+    /// numeric characters like `7` or `٣`
+    digits: HashMap<String, u64>,
+    /// spelled digits like `three`, misspelled ones with `--fuzzy`
+    words: HashMap<String, u64>,
+}
+
+impl TokenStats {
+    /// Count the token of the match in the line
+    pub fn add(&mut self, line: &str, m: &Match) {
+        let tokens = match m.kind {
+            MatchKind::Digit => &mut self.digits,
+            MatchKind::Word => &mut self.words,
+        };
+
+        let token = &line[m.start..m.end];
+        match tokens.get_mut(token) {
+            Some(count) => *count += 1,
+            None => {
+                tokens.insert(token.to_owned(), 1);
+            },
+        }
+    }
+
+    pub fn merge(&mut self, other: &Self) {
+        for (tokens, other) in [(&mut self.digits, &other.digits), (&mut self.words, &other.words)] {
+            for (token, count) in other {
+                *tokens.entry(token.clone()).or_default() += count;
+            }
+        }
+    }
+
+    /// The amount of the matches of numeric characters
+    #[must_use]
+    pub fn numeric(&self) -> u64 {
+        self.digits.values().sum()
+    }
+
+    /// The amount of the matches of spelled digits
+    #[must_use]
+    pub fn spelled(&self) -> u64 {
+        self.words.values().sum()
+    }
+
+    /// The tokens with their kinds and counts, the most frequent first
+    #[must_use]
+    pub fn tokens(&self) -> Vec<(&str, MatchKind, u64)> {
+        let mut tokens: Vec<_> = self
+            .digits
+            .iter()
+            .map(|(token, count)| (token.as_str(), MatchKind::Digit, *count))
+            .chain(
+                self.words
+                    .iter()
+                    .map(|(token, count)| (token.as_str(), MatchKind::Word, *count)),
+            )
+            .collect();
+
+        tokens.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| a.0.cmp(b.0)));
+        tokens
+    }
+}
+
+/// The lines with the highest or the lowest values, a bounded heap keeps only `limit` of them
+///
+/// The earlier line wins a tie, so the kept lines don't depend on the processing order.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TopLines {
+    limit: usize,
+    lowest: bool,
+    /// the worst kept line on the top
+    heap: BinaryHeap<Reverse<Ranked>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Ranked {
+    /// the value, or the distance from the largest value for the lowest lines
+    score: u32,
+    number: Reverse<u64>,
+    line: String,
+}
+
+impl TopLines {
+    #[must_use]
+    pub fn highest(limit: usize) -> Self {
+        Self {
+            limit,
+            ..Self::default()
+        }
+    }
+
+    /// The lowest lines, no lines are kept when the limit is zero
+    #[must_use]
+    pub fn lowest(limit: usize) -> Self {
+        Self {
+            limit,
+            lowest: limit != 0,
+            ..Self::default()
+        }
+    }
+
+    /// Keep the line when it's among the `limit` best ones, the line is copied only then
+    pub fn add(&mut self, value: u32, number: u64, line: &str) {
+        if self.limit == 0 {
+            return;
+        }
+
+        let score = self.score(value);
+        if self.heap.len() == self.limit {
+            match self.heap.peek() {
+                Some(Reverse(worst)) if (score, Reverse(number)) > (worst.score, worst.number) => {
+                    self.heap.pop();
+                },
+                _ => return,
+            }
+        }
+
+        self.heap.push(Reverse(Ranked {
+            score,
+            number: Reverse(number),
+            line: line.to_owned(),
+        }));
+    }
+
+    /// Keep the best lines of both, an empty one takes the limit of the other
+    pub fn merge(&mut self, other: &Self) {
+        if self.limit == 0 && other.limit != 0 {
+            self.limit = other.limit;
+            self.lowest = other.lowest;
+        }
+
+        for Reverse(ranked) in &other.heap {
+            self.add(self.value(ranked.score), ranked.number.0, &ranked.line);
+        }
+    }
+
+    /// The values, the line numbers and the lines, the best first
+    #[must_use]
+    pub fn lines(&self) -> Vec<(u32, u64, &str)> {
+        let mut ranked: Vec<&Ranked> = self.heap.iter().map(|Reverse(ranked)| ranked).collect();
+        ranked.sort_by(|a, b| b.cmp(a));
+
+        ranked
+            .into_iter()
+            .map(|ranked| (self.value(ranked.score), ranked.number.0, ranked.line.as_str()))
+            .collect()
+    }
+
+    fn score(&self, value: u32) -> u32 {
+        if self.lowest {
+            u32::MAX - value
+        } else {
+            value
+        }
+    }
+
+    /// The mapping of the values to the scores is its own inverse
+    fn value(&self, score: u32) -> u32 {
+        self.score(score)
+    }
+}
+
+impl PartialEq for TopLines {
+    fn eq(&self, other: &Self) -> bool {
+        (self.limit, self.lowest) == (other.limit, other.lowest) && self.lines() == other.lines()
+    }
+}
+
+impl Eq for TopLines {}
+
+/// The first digit of the first line with digits and the last digit of the last one, with their line numbers
+///
+/// The earliest and the latest lines win whatever the processing order, so the parts of an input merge.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InputEnds {
+    pub first: Option<(u64, Match)>,
+    pub last: Option<(u64, Match)>,
+}
+
+impl InputEnds {
+    pub fn add(&mut self, number: u64, value: &LineValue) {
+        self.merge(&Self {
+            first: Some((number, value.first)),
+            last: Some((number, value.last)),
+        });
+    }
+
+    pub fn merge(&mut self, other: &Self) {
+        if let Some((number, _)) = other.first {
+            if self.first.map_or(true, |(own, _)| number < own) {
+                self.first = other.first;
+            }
+        }
+        if let Some((number, _)) = other.last {
+            if self.last.map_or(true, |(own, _)| number > own) {
+                self.last = other.last;
+            }
+        }
+    }
+
+    /// The value of the whole input: its first digit times ten plus its last digit, `None` without digits
+    #[must_use]
+    pub fn value(&self) -> Option<u32> {
+        match (self.first, self.last) {
+            (Some((_, first)), Some((_, last))) => Some(Match::combine(&first, &last)),
+            _ => None,
+        }
+    }
+}
+
+/// The amounts and the counts of the lines of every key, e.g. of a device ID found in the lines
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GroupStats {
+    /// the amount of the lines with a value and their total amount of every key
+    groups: HashMap<String, (u64, u64)>,
+}
+
+impl GroupStats {
+    pub fn add(&mut self, key: &str, value: u32) {
+        let (lines, sum) = match self.groups.get_mut(key) {
+            Some(group) => group,
+            None => self.groups.entry(key.to_owned()).or_default(),
+        };
+        *lines += 1;
+        *sum = sum.saturating_add(u64::from(value));
+    }
+
+    pub fn merge(&mut self, other: &Self) {
+        for (key, (lines, sum)) in &other.groups {
+            let group = self.groups.entry(key.clone()).or_default();
+            group.0 += lines;
+            group.1 = group.1.saturating_add(*sum);
+        }
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+
+    /// The keys with the amounts of their lines and their total amounts, in the order of the keys
+    #[must_use]
+    pub fn groups(&self) -> Vec<(&str, u64, u64)> {
+        let mut groups: Vec<_> = self
+            .groups
+            .iter()
+            .map(|(key, (lines, sum))| (key.as_str(), *lines, *sum))
+            .collect();
+        groups.sort_unstable();
+        groups
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_stats() {
+        let mut stats = LineStats::default();
+        assert_eq!(stats.to_string(), "no values");
+
+        for value in [29, 83, 13, 24, 42, 14, 76] {
+            stats.add(value);
+        }
+
+        assert_eq!(stats.count(), 7);
+        assert_eq!((stats.min(), stats.max()), (Some(13), Some(83)));
+        assert_eq!(stats.median(), Some(29.0));
+        assert_eq!(stats.to_string(), "min 13, max 83, mean 40.14, median 29, standard deviation 26.54");
+
+        stats.add(31);
+        assert_eq!(stats.median(), Some(30.0));
+        assert_eq!(stats.quantile(0.5), Some(29));
+        assert_eq!(stats.quantile(1.0), Some(83));
+        assert_eq!(stats.quantile(0.0), Some(13));
+    }
+
+    #[test]
+    fn test_digits() {
+        let mut stats = LineStats::default();
+        for value in [29, 83, 13, 24, 42, 14, 76, 5] {
+            stats.add(value);
+        }
+
+        assert_eq!(stats.first_digits(), [1, 2, 2, 0, 1, 0, 0, 1, 1, 0]);
+        assert_eq!(stats.last_digits(), [0, 0, 1, 2, 2, 1, 1, 0, 0, 1]);
+    }
+
+    #[test]
+    fn test_input_ends() {
+        let (mut head, mut tail) = (InputEnds::default(), InputEnds::default());
+        assert_eq!(head.value(), None);
+
+        for (number, line) in [(3, "xtwone3four"), (1, "two1nine"), (2, "treb7uchet")] {
+            head.add(number, &crate::extract_line(line).unwrap());
+        }
+        tail.add(7, &crate::extract_line("abc8").unwrap());
+        tail.merge(&head);
+
+        assert_eq!(tail.value(), Some(28));
+        assert_eq!(tail.first.map(|(number, m)| (number, m.start)), Some((1, 0)));
+        assert_eq!(tail.last.map(|(number, m)| (number, m.start)), Some((7, 3)));
+    }
+
+    #[test]
+    fn test_groups() {
+        let (mut first, mut second) = (GroupStats::default(), GroupStats::default());
+        assert!(first.is_empty());
+
+        for (key, value) in [("b", 29), ("a", 77), ("b", 13)] {
+            first.add(key, value);
+        }
+        second.add("", 83);
+        second.add("a", u32::MAX);
+        first.merge(&second);
+
+        assert_eq!(
+            first.groups(),
+            [("", 1, 83), ("a", 2, 77 + u64::from(u32::MAX)), ("b", 2, 42)]
+        );
+    }
+
+    #[test]
+    fn test_tokens() {
+        let (mut first, mut second) = (TokenStats::default(), TokenStats::default());
+
+        for line in ["two1nine", "eightwothree", "treb7uchet"] {
+            let value = crate::extract_line(line).unwrap();
+            first.add(line, &value.first);
+            second.add(line, &value.last);
+        }
+        first.merge(&second);
+
+        assert_eq!((first.numeric(), first.spelled()), (2, 4));
+        assert_eq!(
+            first.tokens(),
+            [
+                ("7", MatchKind::Digit, 2),
+                ("eight", MatchKind::Word, 1),
+                ("nine", MatchKind::Word, 1),
+                ("three", MatchKind::Word, 1),
+                ("two", MatchKind::Word, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_top_lines() {
+        let lines = [
+            (29, "two1nine"),
+            (83, "eightwothree"),
+            (13, "abcone2threexyz"),
+            (83, "8x3"),
+            (14, "zoneight234"),
+        ];
+        let (mut highest, mut lowest) = (TopLines::highest(2), TopLines::lowest(2));
+        let (mut first, mut second) = (TopLines::highest(2), TopLines::highest(2));
+
+        for (number, (value, line)) in (0..).zip(lines) {
+            highest.add(value, number, line);
+            lowest.add(value, number, line);
+            if number % 2 == 0 { &mut first } else { &mut second }.add(value, number, line);
+        }
+
+        assert_eq!(highest.lines(), [(83, 1, "eightwothree"), (83, 3, "8x3")]);
+        assert_eq!(lowest.lines(), [(13, 2, "abcone2threexyz"), (14, 4, "zoneight234")]);
+
+        let mut merged = TopLines::default();
+        merged.merge(&second);
+        merged.merge(&first);
+        assert_eq!(merged, highest);
+    }
+
+    #[test]
+    fn test_merge() {
+        let (mut first, mut second, mut all) = (LineStats::default(), LineStats::default(), LineStats::default());
+
+        for value in 10..60 {
+            if value % 3 == 0 { &mut first } else { &mut second }.add(value);
+            all.add(value);
+        }
+
+        first.merge(&second);
+        assert_eq!(first, all);
+    }
+}
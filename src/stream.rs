@@ -0,0 +1,206 @@
+//! The first and the last digits of a line read in chunks, so a line of gigabytes is scanned in bounded memory
+
+use std::{
+    mem,
+    str::{self, Utf8Error},
+};
+
+use crate::{scanner::match_at, LineError, LineValue, Match, Overlap, ScanOptions};
+
+/// Bytes kept before the next scanned position: the char before a keycap digit or a word
+const LOOKBEHIND: usize = 16;
+/// Bytes needed after a position besides the longest word: a keycap sequence and the char after it
+const CONTEXT: usize = 16;
+/// Bytes of a UTF-8 char, a fuzzy edit may add one
+const MAX_CHAR: usize = 4;
+
+/// The matches of a line fed in chunks, the same as [`crate::extract_line_with`] of the whole line
+///
+/// Only the bytes which may still be part of a match are kept. With `fuzzy` words the last match is the one ending
+/// last of the forward scan, which may differ from the backward one of [`crate::extract_line_with`].
+#[derive(Debug, Clone)]
+pub struct StreamScanner {
+    options: ScanOptions,
+    /// the kept bytes of the line, the last ones may be an unterminated char
+    window: Vec<u8>,
+    /// the position of `window` in the line
+    base: usize,
+    /// the position in the line of the next char to scan
+    next: usize,
+    /// bytes a match beginning at a position may need after it
+    lookahead: usize,
+    first: Option<Match>,
+    last: Option<Match>,
+    /// the end of the previous match with [`Overlap::Consume`]
+    consumed: usize,
+}
+
+impl StreamScanner {
+    #[must_use]
+    pub fn new(options: ScanOptions) -> Self {
+        let longest = options.dictionary().words().iter().map(|(word, _)| word.len()).max();
+        let lookahead = longest.unwrap_or_default() + options.fuzzy * MAX_CHAR + CONTEXT;
+        Self {
+            options,
+            window: Vec::new(),
+            base: 0,
+            next: 0,
+            lookahead,
+            first: None,
+            last: None,
+            consumed: 0,
+        }
+    }
+
+    /// Scan the next bytes of the line, a char may be split between two chunks
+    ///
+    /// # Errors
+    ///
+    /// the line isn't UTF-8, the scanner shouldn't be fed more then
+    pub fn feed(&mut self, chunk: &[u8]) -> Result<(), Utf8Error> {
+        self.window.extend_from_slice(chunk);
+        self.scan(false)
+    }
+
+    /// Scan the end of the line
+    ///
+    /// # Errors
+    ///
+    /// the line ends with an unterminated char
+    pub fn end(&mut self) -> Result<(), Utf8Error> {
+        self.scan(true)
+    }
+
+    /// The value of the line scanned so far, call [`Self::end`] first
+    ///
+    /// # Errors
+    ///
+    /// the line is empty or has no digits
+    pub fn value(&self) -> Result<LineValue, LineError> {
+        if self.base + self.window.len() == 0 {
+            return Err(LineError::Empty);
+        }
+
+        match (self.first, self.last) {
+            (Some(first), Some(last)) => Ok(LineValue {
+                value: Match::combine(&first, &last),
+                first,
+                last,
+            }),
+            _ => Err(LineError::NoDigits),
+        }
+    }
+
+    /// Scan the positions followed by enough bytes, all of them at the end of the line
+    fn scan(&mut self, end: bool) -> Result<(), Utf8Error> {
+        // taken during the scan, the matches are kept by `self.accept`
+        let mut window = mem::take(&mut self.window);
+        let valid = match str::from_utf8(&window) {
+            Ok(text) => text.len(),
+            Err(e) if e.error_len().is_none() && !end => e.valid_up_to(),
+            Err(e) => {
+                self.window = window;
+                return Err(e);
+            },
+        };
+        let text = str::from_utf8(&window[..valid]).unwrap_or_default();
+        let limit = if end { text.len() } else { text.len().saturating_sub(self.lookahead) };
+
+        let mut pos = self.next - self.base;
+        while pos < limit {
+            let Some(c) = text[pos..].chars().next() else {
+                break;
+            };
+            if let Some(m) = match_at(text, pos, c, &self.options) {
+                self.accept(m);
+            }
+            pos += c.len_utf8();
+        }
+        self.next = self.base + pos;
+
+        let mut drained = pos.saturating_sub(LOOKBEHIND);
+        while !text.is_char_boundary(drained) {
+            drained -= 1;
+        }
+        window.drain(..drained);
+        self.window = window;
+        self.base += drained;
+        Ok(())
+    }
+
+    /// Keep the match found at `m.start - base` as the first or the last one
+    fn accept(&mut self, mut m: Match) {
+        m.start += self.base;
+        m.end += self.base;
+        if self.options.overlap == Overlap::Consume {
+            if m.start < self.consumed {
+                return;
+            }
+            self.consumed = m.end;
+        }
+
+        if self.first.is_none() {
+            self.first = Some(m);
+        }
+        // the longest of the matches ending last, as the backward scan finds
+        if self.last.map_or(true, |last| m.end > last.end) {
+            self.last = Some(m);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_stream_scanner() {
+        let pieces: [&[u8]; 10] = [
+            b"one",
+            b"eight",
+            b"ight",
+            b"7",
+            b"x",
+            "é".as_bytes(),
+            "٣".as_bytes(),
+            "３".as_bytes(),
+            "\u{FE0F}\u{20E3}".as_bytes(),
+            b"-",
+        ];
+        let mut generator = crate::Generator::new(7);
+
+        for _ in 0..5_000 {
+            let flags = generator.below(64);
+            let options = ScanOptions {
+                unicode_digits: flags & 1 != 0,
+                fullwidth_digits: flags & 2 != 0,
+                keycap_digits: flags & 4 != 0,
+                isolated_digits_only: flags & 8 != 0,
+                word_boundaries: flags & 16 != 0,
+                overlap: if flags & 32 == 0 { Overlap::Allow } else { Overlap::Consume },
+                ..ScanOptions::default()
+            };
+            let mut bytes = Vec::new();
+            for _ in 0..generator.below(40) {
+                bytes.extend_from_slice(pieces[generator.below(pieces.len())]);
+            }
+            let line = str::from_utf8(&bytes).unwrap();
+
+            let mut scanner = StreamScanner::new(options.clone());
+            let mut rest = bytes.as_slice();
+            while !rest.is_empty() {
+                let (chunk, next) = rest.split_at(generator.below(rest.len()) + 1);
+                scanner.feed(chunk).unwrap();
+                assert!(scanner.window.len() < scanner.lookahead + LOOKBEHIND + 2 * MAX_CHAR);
+                rest = next;
+            }
+            scanner.end().unwrap();
+            assert_eq!(scanner.value(), crate::extract_line_with(line, &options), "{line:?}");
+        }
+
+        let mut scanner = StreamScanner::new(ScanOptions::default());
+        assert_eq!(scanner.feed(b"two\xC3"), Ok(()));
+        assert!(scanner.end().is_err());
+        assert!(StreamScanner::new(ScanOptions::default()).feed(b"two\xFFnine").is_err());
+    }
+}
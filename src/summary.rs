@@ -0,0 +1,163 @@
+use std::fmt;
+
+use crate::{GroupStats, InputEnds, LineStats, TokenStats, TopLines};
+
+/// Running totals of a processed input
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Summary {
+    pub parsed_lines: u64,
+    pub incorrect_lines: u64,
+    /// the lines without any text, among the incorrect ones unless they're ignored
+    pub empty_lines: u64,
+    /// the lines left out by the line filters, neither summed nor incorrect
+    pub excluded_lines: u64,
+    /// the lines left out by the sampling
+    pub unsampled_lines: u64,
+    pub total_sum: u64,
+    /// the total amount exceeded `u64`, `total_sum` is wrong then
+    pub overflowed: bool,
+    /// the distribution of the values of the correct lines
+    pub stats: LineStats,
+    /// the matched first and last tokens, counted on demand only
+    pub tokens: TokenStats,
+    /// the lines with the highest and the lowest values, kept on demand only
+    pub highest: TopLines,
+    pub lowest: TopLines,
+    /// the first and the last digits of the whole input, kept on demand only
+    pub ends: InputEnds,
+    /// the totals of the keys of the lines, kept on demand only
+    pub groups: GroupStats,
+    pub input_bytes: ByteCount,
+}
+
+impl Summary {
+    /// Add the value of a line to the total amount
+    pub fn add_value(&mut self, value: u32) {
+        self.add_total(u64::from(value));
+        self.stats.add(value);
+    }
+
+    /// Add the totals of another part of the same input, e.g. a chunk processed in parallel
+    pub fn merge(&mut self, other: &Self) {
+        self.parsed_lines += other.parsed_lines;
+        self.incorrect_lines += other.incorrect_lines;
+        self.empty_lines += other.empty_lines;
+        self.excluded_lines += other.excluded_lines;
+        self.unsampled_lines += other.unsampled_lines;
+        self.add_total(other.total_sum);
+        self.overflowed |= other.overflowed;
+        self.stats.merge(&other.stats);
+        self.tokens.merge(&other.tokens);
+        self.highest.merge(&other.highest);
+        self.lowest.merge(&other.lowest);
+        self.ends.merge(&other.ends);
+        self.groups.merge(&other.groups);
+        self.input_bytes.logical += other.input_bytes.logical;
+        self.input_bytes.physical += other.input_bytes.physical;
+    }
+
+    /// The rate of the sampled lines and the total amount extrapolated to all the lines, `None` without sampling
+    #[must_use]
+    pub fn sample_estimate(&self) -> Option<(f64, f64)> {
+        if self.unsampled_lines == 0 {
+            return None;
+        }
+
+        let rate = (self.parsed_lines - self.unsampled_lines) as f64 / self.parsed_lines as f64;
+        let estimate = if rate == 0.0 { 0.0 } else { self.total_sum as f64 / rate };
+        Some((rate, estimate))
+    }
+
+    fn add_total(&mut self, amount: u64) {
+        let (total_sum, overflowed) = self.total_sum.overflowing_add(amount);
+        self.total_sum = total_sum;
+        self.overflowed |= overflowed;
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Parsed lines: {}, Incorrect lines {}, Total amount: {}",
+            self.parsed_lines, self.incorrect_lines, self.total_sum
+        )
+    }
+}
+
+/// Logical (decoded) and physical (stored) sizes of a data stream
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ByteCount {
+    pub logical: u64,
+    pub physical: u64,
+}
+
+impl ByteCount {
+    /// `true` when the stored data differs from the decoded one, e.g. it's compressed
+    #[must_use]
+    pub fn is_encoded(&self) -> bool {
+        self.logical != self.physical
+    }
+
+    /// Logical to physical size ratio
+    #[must_use]
+    pub fn ratio(&self) -> Option<f64> {
+        (self.physical != 0).then(|| self.logical as f64 / self.physical as f64)
+    }
+}
+
+impl fmt::Display for ByteCount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "logical {} bytes, physical {} bytes", self.logical, self.physical)?;
+
+        match self.ratio() {
+            Some(ratio) => write!(f, ", compression ratio {ratio:.2}"),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_overflow() {
+        let mut summary = Summary {
+            total_sum: u64::MAX - 10,
+            ..Summary::default()
+        };
+
+        summary.add_value(10);
+        assert_eq!((summary.total_sum, summary.overflowed), (u64::MAX, false));
+
+        summary.add_value(1);
+        assert!(summary.overflowed);
+
+        let mut merged = Summary::default();
+        merged.merge(&summary);
+        assert!(merged.overflowed);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let mut summary = Summary {
+            highest: TopLines::highest(2),
+            ..Summary::default()
+        };
+        for (number, line) in ["two1nine", "treb7uchet", "4nineeightseven2"].into_iter().enumerate() {
+            let value = crate::extract_line(line).unwrap();
+            summary.parsed_lines += 1;
+            summary.add_value(value.value);
+            summary.tokens.add(line, &value.first);
+            summary.highest.add(value.value, number as u64, line);
+        }
+
+        let text = serde_json::to_string(&summary).unwrap();
+        assert_eq!(serde_json::from_str::<Summary>(&text).unwrap(), summary);
+        assert!(serde_json::from_str::<LineStats>("[1, 2]").is_err());
+    }
+}
@@ -0,0 +1,115 @@
+use std::{
+    io::{self, Read, Write},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex, PoisonError,
+    },
+};
+
+/// Set by `--tee`, stdout carries the copied input then and the messages go to stderr
+static MESSAGES_TO_STDERR: AtomicBool = AtomicBool::new(false);
+/// The messages held while the terminal is taken by the dashboard, `None` when they're printed
+static HELD_MESSAGES: Mutex<Option<HeldMessages>> = Mutex::new(None);
+/// The messages held at most, the following ones are only counted
+const HELD_LIMIT: usize = 1000;
+
+#[derive(Debug, Default)]
+struct HeldMessages {
+    texts: Vec<String>,
+    dropped: u64,
+}
+
+/// Print the messages and the reports to stderr from now on, stdout is left to the copied input
+pub fn redirect_messages() {
+    MESSAGES_TO_STDERR.store(true, Ordering::Relaxed);
+}
+
+pub fn messages_to_stderr() -> bool {
+    MESSAGES_TO_STDERR.load(Ordering::Relaxed)
+}
+
+/// `println!` of a message or a report, to stderr once the input is copied to stdout
+macro_rules! message {
+    ($($arg:tt)*) => {
+        if let Some(text) = $crate::tee::unless_held(format!($($arg)*)) {
+            if $crate::tee::messages_to_stderr() {
+                eprintln!("{text}");
+            } else {
+                println!("{text}");
+            }
+        }
+    };
+}
+
+pub(crate) use message;
+
+/// Hold the messages until [`release_messages`], e.g. while the dashboard is drawn
+#[cfg(feature = "tui")]
+pub fn hold_messages() {
+    *HELD_MESSAGES.lock().unwrap_or_else(PoisonError::into_inner) = Some(HeldMessages::default());
+}
+
+/// Print the held messages, the following ones are printed right away again
+#[cfg(feature = "tui")]
+pub fn release_messages() {
+    let held = HELD_MESSAGES.lock().unwrap_or_else(PoisonError::into_inner).take();
+    let held = held.unwrap_or_default();
+    for text in &held.texts {
+        message!("{text}");
+    }
+    if held.dropped != 0 {
+        message!("{} more messages", held.dropped);
+    }
+}
+
+/// The message back unless it's held
+pub fn unless_held(text: String) -> Option<String> {
+    match HELD_MESSAGES.lock().unwrap_or_else(PoisonError::into_inner).as_mut() {
+        Some(held) if held.texts.len() < HELD_LIMIT => held.texts.push(text),
+        Some(held) => held.dropped += 1,
+        None => return Some(text),
+    }
+    None
+}
+
+/// Reader writing every read byte to the output unchanged, e.g. to stdout for the next command of a pipeline
+#[derive(Debug)]
+pub struct TeeReader<R, W> {
+    inner: R,
+    output: W,
+}
+
+impl<R, W> TeeReader<R, W> {
+    pub fn new(inner: R, output: W) -> Self {
+        Self { inner, output }
+    }
+}
+
+impl<R: Read, W: Write> Read for TeeReader<R, W> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        // e.g. a closed pipe, it isn't an error of the input but the reading can't go on
+        self.output
+            .write_all(&buf[..read])
+            .map_err(|e| io::Error::new(e.kind(), format!("fail to copy the input: {e}")))?;
+
+        Ok(read)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_tee_reader() {
+        let input = &b"two1nine\n\xff\neightwothree"[..];
+        let mut output = Vec::new();
+
+        let mut content = Vec::new();
+        TeeReader::new(input, &mut output).read_to_end(&mut content).unwrap();
+
+        assert_eq!(content, input);
+        assert_eq!(output, input);
+    }
+}
@@ -0,0 +1,23 @@
+//! The temporary files of the tests, deleted when their guards are dropped, a failed test leaves none behind
+
+use std::fs;
+
+use tempfile::{Builder, TempDir, TempPath};
+
+/// A new file `find_digits_*<suffix>` of the content, the suffix is the extension some readers detect the format by
+pub fn temp_file(suffix: &str, content: impl AsRef<[u8]>) -> TempPath {
+    let path = Builder::new()
+        .prefix("find_digits_")
+        .suffix(suffix)
+        .tempfile()
+        .unwrap()
+        .into_temp_path();
+    fs::write(&path, content).unwrap();
+
+    path
+}
+
+/// A new empty directory, deleted with all its files
+pub fn temp_dir() -> TempDir {
+    Builder::new().prefix("find_digits_").tempdir().unwrap()
+}
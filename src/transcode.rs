@@ -0,0 +1,115 @@
+use std::io::{self, Read};
+
+use encoding_rs::{Decoder, Encoding, UTF_8};
+
+/// Bytes read from the input at once
+const CHUNK: usize = 8192;
+
+/// The encoding of the label of `--encoding`, e.g. `latin1`, `utf-16le` or `shift_jis`
+pub fn parse_encoding(label: &str) -> Result<&'static Encoding, String> {
+    Encoding::for_label(label.trim().as_bytes())
+        .ok_or_else(|| format!("unknown encoding {label}, e.g. latin1, windows-1252, utf-16le or shift_jis"))
+}
+
+/// The input decoded to UTF-8 as it's read, the lines are split and checked afterwards.
+/// A byte order mark overrides the encoding, the malformed sequences are replaced by `U+FFFD`
+pub struct Transcoder<R> {
+    inner: R,
+    decoder: Decoder,
+    raw: Box<[u8]>,
+    /// the decoded bytes, they're returned from `position`
+    decoded: Vec<u8>,
+    position: usize,
+    end: bool,
+}
+
+impl<R: Read> Transcoder<R> {
+    pub fn new(inner: R, encoding: &'static Encoding) -> Self {
+        Self {
+            inner,
+            decoder: encoding.new_decoder(),
+            raw: vec![0; CHUNK].into_boxed_slice(),
+            decoded: Vec::new(),
+            position: 0,
+            end: false,
+        }
+    }
+}
+
+impl<R: Read> Read for Transcoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.decoded.len() {
+            if self.end {
+                return Ok(0);
+            }
+
+            let length = self.inner.read(&mut self.raw)?;
+            self.end = length == 0;
+            let capacity = self
+                .decoder
+                .max_utf8_buffer_length(length)
+                .ok_or_else(|| io::Error::other("the decoded input is too long"))?;
+            self.decoded.resize(capacity, 0);
+            // the output fits, all the input is decoded
+            let (_, _, written, _) = self
+                .decoder
+                .decode_to_utf8(&self.raw[..length], &mut self.decoded, self.end);
+            self.decoded.truncate(written);
+            self.position = 0;
+        }
+
+        let length = buf.len().min(self.decoded.len() - self.position);
+        buf[..length].copy_from_slice(&self.decoded[self.position..self.position + length]);
+        self.position += length;
+        Ok(length)
+    }
+}
+
+/// The input decoded from the encoding, UTF-8 is read as it is and its invalid lines are skipped
+pub fn decode<'a>(input: impl Read + Send + 'a, encoding: Option<&'static Encoding>) -> Box<dyn Read + Send + 'a> {
+    match encoding {
+        Some(encoding) if encoding != UTF_8 => Box::new(Transcoder::new(input, encoding)),
+        _ => Box::new(input),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// A byte per read, the multibyte sequences are split between the reads
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let length = self.0.len().min(buf.len()).min(1);
+            buf[..length].copy_from_slice(&self.0[..length]);
+            self.0 = &self.0[length..];
+            Ok(length)
+        }
+    }
+
+    #[test]
+    fn test_transcoder() {
+        let utf16: Vec<u8> = "\u{FEFF}two1nine\nsieben 7 é\n"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect();
+        let inputs: [(&[u8], &str); 4] = [
+            (b"caf\xE9 one\n\xA37\n", "latin1"),
+            (&utf16, "utf-16le"),
+            (b"\x82\xa0 8", "shift_jis"),
+            (b"\xFF1\n", "utf-8"),
+        ];
+        let expected = ["café one\n£7\n", "two1nine\nsieben 7 é\n", "あ 8", "\u{FFFD}1\n"];
+
+        for (pos, (input, label)) in inputs.into_iter().enumerate() {
+            let mut text = String::new();
+            let mut reader = Transcoder::new(Trickle(input), parse_encoding(label).unwrap());
+            reader.read_to_string(&mut text).unwrap();
+            assert_eq!(text, expected[pos], "{label}");
+        }
+
+        assert!(parse_encoding("klingon").is_err());
+    }
+}
@@ -0,0 +1,50 @@
+/// First code points (the zero) of the Unicode `Nd` (decimal number) blocks except ASCII, Unicode 14.0
+///
+/// Every block is exactly ten contiguous code points from zero to nine.
+static DECIMAL_ZEROS: [u32; 65] = [
+    0x0660, 0x06F0, 0x07C0, 0x0966, 0x09E6, 0x0A66, 0x0AE6, 0x0B66, 0x0BE6, 0x0C66, 0x0CE6, 0x0D66, 0x0DE6, 0x0E50,
+    0x0ED0, 0x0F20, 0x1040, 0x1090, 0x17E0, 0x1810, 0x1946, 0x19D0, 0x1A80, 0x1A90, 0x1B50, 0x1BB0, 0x1C40, 0x1C50,
+    0xA620, 0xA8D0, 0xA900, 0xA9D0, 0xA9F0, 0xAA50, 0xABF0, 0xFF10, 0x104A0, 0x10D30, 0x11066, 0x110F0, 0x11136,
+    0x111D0, 0x112F0, 0x11450, 0x114D0, 0x11650, 0x116C0, 0x11730, 0x118E0, 0x11950, 0x11C50, 0x11D50, 0x11DA0,
+    0x16A60, 0x16AC0, 0x16B50, 0x1D7CE, 0x1D7D8, 0x1D7E2, 0x1D7EC, 0x1D7F6, 0x1E140, 0x1E2F0, 0x1E950, 0x1FBF0,
+];
+
+/// Decimal value of any Unicode `Nd` character, e.g. Arabic-Indic `'\u{663}'` is 3
+pub(crate) fn decimal_value(c: char) -> Option<u32> {
+    if let Some(value) = c.to_digit(10) {
+        return Some(value);
+    }
+
+    let code = u32::from(c);
+    let zero = match DECIMAL_ZEROS.binary_search(&code) {
+        Ok(pos) => DECIMAL_ZEROS[pos],
+        Err(0) => return None,
+        Err(pos) => DECIMAL_ZEROS[pos - 1],
+    };
+
+    (code - zero < 10).then_some(code - zero)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_decimal_value() {
+        let chars = [
+            '7',
+            '\u{663}',
+            '\u{96B}',
+            '\u{FF19}',
+            '\u{1D7CE}',
+            'a',
+            '\u{66A}',
+            '\u{B2}',
+        ];
+        let expected = [Some(7), Some(3), Some(5), Some(9), Some(0), None, None, None];
+
+        for (pos, c) in chars.into_iter().enumerate() {
+            assert_eq!(decimal_value(c), expected[pos]);
+        }
+    }
+}
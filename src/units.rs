@@ -0,0 +1,155 @@
+use std::{ops::Range, time::Duration};
+
+/// Parse a human friendly duration like `500ms`, `10s`, `30m`, `2h` or `7d`, a bare number is seconds
+///
+/// # Errors
+///
+/// the number or the unit can't be recognized
+pub fn parse_duration(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let split = value.find(|c: char| !c.is_ascii_digit()).unwrap_or(value.len());
+    let (amount, unit) = value.split_at(split);
+
+    let amount: u64 = amount.parse().map_err(|e| format!("incorrect duration {value}: {e}"))?;
+
+    let seconds = match unit {
+        "ms" => return Ok(Duration::from_millis(amount)),
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => return Err(format!("unknown duration unit `{unit}` in {value}")),
+    };
+
+    amount
+        .checked_mul(seconds)
+        .map(Duration::from_secs)
+        .ok_or_else(|| format!("duration {value} is too long"))
+}
+
+/// Parse a size like `64K`, `1M`, `1MiB` or `2G` in binary units, a bare number is bytes
+///
+/// # Errors
+///
+/// the number or the unit can't be recognized
+pub fn parse_size(value: &str) -> Result<usize, String> {
+    let value = value.trim();
+    let split = value.find(|c: char| !c.is_ascii_digit()).unwrap_or(value.len());
+    let (amount, unit) = value.split_at(split);
+
+    let amount: usize = amount.parse().map_err(|e| format!("incorrect size {value}: {e}"))?;
+
+    let bytes = match unit {
+        "" | "B" => 1,
+        "K" | "k" | "KiB" => 1 << 10,
+        "M" | "MiB" => 1 << 20,
+        "G" | "GiB" => 1 << 30,
+        _ => return Err(format!("unknown size unit `{unit}` in {value}")),
+    };
+
+    amount
+        .checked_mul(bytes)
+        .ok_or_else(|| format!("size {value} is too large"))
+}
+
+/// Parse a range of line numbers like `1000:2000`, the end is exclusive, either bound may be omitted (`:500`, `1000:`)
+///
+/// # Errors
+///
+/// a bound isn't a number, the separator is missing or the end is before the start
+pub fn parse_line_range(value: &str) -> Result<Range<usize>, String> {
+    let Some((start, end)) = value.trim().split_once(':') else {
+        return Err(format!("incorrect line range {value}, expected START:END"));
+    };
+
+    let bound = |bound: &str, default| match bound.trim() {
+        "" => Ok(default),
+        bound => bound.parse().map_err(|e| format!("incorrect line range {value}: {e}")),
+    };
+    let range = bound(start, 0)?..bound(end, usize::MAX)?;
+
+    if range.end < range.start {
+        return Err(format!("line range {value} ends before its start"));
+    }
+
+    Ok(range)
+}
+
+/// Format seconds since the Unix epoch as `YYYY-MM-DD HH:MM:SS` UTC
+#[must_use]
+pub fn format_timestamp(seconds: u64) -> String {
+    let days = seconds / 86_400;
+    let rest = seconds % 86_400;
+
+    // civil from days, http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+
+    format!("{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02}", rest / 3600, rest % 3600 / 60, rest % 60)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        let strings = ["500ms", "10", "10s", "30m", "2h", "7d"];
+        let expected = [
+            Duration::from_millis(500),
+            Duration::from_secs(10),
+            Duration::from_secs(10),
+            Duration::from_secs(30 * 60),
+            Duration::from_secs(2 * 60 * 60),
+            Duration::from_secs(7 * 24 * 60 * 60),
+        ];
+
+        for (pos, value) in strings.into_iter().enumerate() {
+            assert_eq!(parse_duration(value), Ok(expected[pos]));
+        }
+
+        assert!(parse_duration("ten").is_err());
+        assert!(parse_duration("10w").is_err());
+    }
+
+    #[test]
+    fn test_parse_size() {
+        let strings = ["8192", "512B", "64K", "1M", "1MiB", "2G"];
+        let expected = [8192, 512, 64 * 1024, 1024 * 1024, 1024 * 1024, 2 * 1024 * 1024 * 1024];
+
+        for (pos, value) in strings.into_iter().enumerate() {
+            assert_eq!(parse_size(value), Ok(expected[pos]));
+        }
+
+        assert!(parse_size("M").is_err());
+        assert!(parse_size("1T").is_err());
+    }
+
+    #[test]
+    fn test_parse_line_range() {
+        let strings = ["1000:2000", ":500", "1000:", ":", " 5 : 5 "];
+        let expected = [1000..2000, 0..500, 1000..usize::MAX, 0..usize::MAX, 5..5];
+
+        for (pos, value) in strings.into_iter().enumerate() {
+            assert_eq!(parse_line_range(value), Ok(expected[pos].clone()));
+        }
+
+        assert!(parse_line_range("1000").is_err());
+        assert!(parse_line_range("a:b").is_err());
+        assert!(parse_line_range("2000:1000").is_err());
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(0), "1970-01-01 00:00:00");
+        assert_eq!(format_timestamp(951_782_400), "2000-02-29 00:00:00");
+        assert_eq!(format_timestamp(1_791_983_301), "2026-10-14 13:08:21");
+    }
+}
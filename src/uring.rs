@@ -0,0 +1,208 @@
+use std::{
+    fs::File,
+    io::{self, Read},
+    mem,
+    os::unix::fs::FileExt,
+    os::unix::io::AsRawFd,
+};
+
+use io_uring::{opcode, types, IoUring};
+
+/// Sequential reader of a file keeping several block reads in flight on `io_uring`
+pub struct UringReader {
+    ring: IoUring,
+    file: File,
+    slots: Vec<Slot>,
+    /// the slot being consumed, the slots are consumed in the order of their offsets
+    current: usize,
+    /// the consumed bytes of the current slot
+    position: usize,
+    next_offset: u64,
+    /// of a single read
+    block_size: u32,
+    eof: bool,
+}
+
+struct Slot {
+    buffer: Vec<u8>,
+    offset: u64,
+    /// the read is submitted and its completion isn't reaped yet, the kernel may write the buffer
+    in_flight: bool,
+    /// the result of the completed read
+    result: Option<i32>,
+    /// the bytes of the buffer ready to be consumed
+    length: Option<usize>,
+}
+
+impl UringReader {
+    /// Keep `depth` reads of `block_size` bytes in flight
+    ///
+    /// # Errors
+    ///
+    /// `io_uring` is not available, e.g. an old kernel or it's disabled by the sandbox
+    pub fn new(file: File, depth: u32, block_size: u32) -> io::Result<Self> {
+        let mut reader = Self {
+            ring: IoUring::new(depth)?,
+            file,
+            slots: (0..depth)
+                .map(|_| Slot {
+                    buffer: vec![0; block_size as usize],
+                    offset: 0,
+                    in_flight: false,
+                    result: None,
+                    length: None,
+                })
+                .collect(),
+            current: 0,
+            position: 0,
+            next_offset: 0,
+            block_size,
+            eof: false,
+        };
+
+        for slot in 0..reader.slots.len() {
+            reader.submit(slot)?;
+        }
+
+        Ok(reader)
+    }
+
+    fn submit(&mut self, slot: usize) -> io::Result<()> {
+        let fd = types::Fd(self.file.as_raw_fd());
+        let target = &mut self.slots[slot];
+        target.offset = self.next_offset;
+        target.result = None;
+        target.length = None;
+        self.next_offset += u64::from(self.block_size);
+
+        let entry = opcode::Read::new(fd, target.buffer.as_mut_ptr(), self.block_size)
+            .offset(target.offset)
+            .build()
+            .user_data(slot as u64);
+
+        // SAFETY: the buffer isn't touched nor freed until the completion is reaped: `in_flight` is checked
+        // by the reads and by the drop
+        unsafe { self.ring.submission().push(&entry) }.map_err(|_| io::Error::other("submission queue is full"))?;
+        target.in_flight = true;
+        self.ring.submit()?;
+
+        Ok(())
+    }
+
+    /// Wait for at least one completion and store the results
+    fn reap(&mut self) -> io::Result<()> {
+        self.ring.submit_and_wait(1)?;
+
+        for entry in self.ring.completion() {
+            let Some(slot) = usize::try_from(entry.user_data())
+                .ok()
+                .and_then(|slot| self.slots.get_mut(slot))
+            else {
+                continue;
+            };
+            slot.in_flight = false;
+            slot.result = Some(entry.result());
+        }
+
+        Ok(())
+    }
+
+    /// The ready bytes of the slot, a short read is completed synchronously
+    fn complete(&mut self, slot: usize) -> io::Result<usize> {
+        if let Some(length) = self.slots[slot].length {
+            return Ok(length);
+        }
+
+        while self.slots[slot].result.is_none() {
+            self.reap()?;
+        }
+
+        let target = &mut self.slots[slot];
+        let result = target.result.unwrap_or_default();
+        let mut length = usize::try_from(result).map_err(|_| io::Error::from_raw_os_error(-result))?;
+
+        // the rest of the block unless it's the end of the file
+        while length > 0 && length < target.buffer.len() {
+            let read = self
+                .file
+                .read_at(&mut target.buffer[length..], target.offset + length as u64)?;
+            if read == 0 {
+                break;
+            }
+            length += read;
+        }
+
+        target.length = Some(length);
+        Ok(length)
+    }
+}
+
+impl Read for UringReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            if self.eof {
+                return Ok(0);
+            }
+
+            let slot = self.current;
+            let length = match self.complete(slot) {
+                Ok(length) => length,
+                Err(e) => {
+                    self.eof = true;
+                    return Err(e);
+                },
+            };
+
+            if length == 0 {
+                self.eof = true;
+                return Ok(0);
+            }
+
+            if self.position < length {
+                let read = buf.len().min(length - self.position);
+                buf[..read].copy_from_slice(&self.slots[slot].buffer[self.position..self.position + read]);
+                self.position += read;
+                return Ok(read);
+            }
+
+            self.position = 0;
+            self.current = (slot + 1) % self.slots.len();
+            self.submit(slot)?;
+        }
+    }
+}
+
+impl Drop for UringReader {
+    fn drop(&mut self) {
+        while self.slots.iter().any(|slot| slot.in_flight) {
+            if self.reap().is_err() {
+                // the kernel may still write into the buffers, they must outlive it
+                mem::forget(mem::take(&mut self.slots));
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::temp_file;
+
+    #[test]
+    fn test_uring_reader() {
+        let (depth, block_size) = (3, 4096);
+        let content: Vec<u8> = (0..3 * block_size * depth + 17).map(|pos| (pos % 251) as u8).collect();
+        let path = temp_file(".txt", &content);
+
+        match UringReader::new(File::open(&path).unwrap(), depth, block_size) {
+            Ok(mut reader) => {
+                let mut read = Vec::new();
+                reader.read_to_end(&mut read).unwrap();
+                assert!(read == content);
+            },
+            // io_uring is disabled here, nothing to check
+            Err(e) => println!("io_uring is not available: {e:?}"),
+        }
+    }
+}
@@ -0,0 +1,214 @@
+use std::{fs, path::PathBuf};
+
+use clap::Args;
+use find_digits::{extract_line_with, LineError, Match, ScanOptions};
+use serde::Serialize;
+
+/// Version of the vectors document layout
+const FORMAT_VERSION: u32 = 1;
+
+static LINES: [&str; 31] = [
+    // the task examples
+    "1abc2",
+    "pqr3stu8vwx",
+    "a1b2c3d4e5f",
+    "treb7uchet",
+    "two1nine",
+    "eightwothree",
+    "abcone2threexyz",
+    "xtwone3four",
+    "4nineeightseven2",
+    "zoneight234",
+    "7pqrstsixteen",
+    // no value
+    "",
+    "abcdefg",
+    "NOT A DIGIT",
+    "thre fiv seve",
+    "xsevcnx",
+    // overlaps and single digits
+    "oneight",
+    "twone",
+    "eighthree",
+    "sevenine",
+    "5",
+    "nine",
+    // multi-digit tokens
+    "ab12cd3",
+    "123",
+    // multi-byte text
+    "caf\u{e9}one\u{1F600}two",
+    "na\u{ef}ve7r\u{e9}sum\u{e9}",
+    // non-ASCII digits
+    "a\u{663}bc\u{96B}",
+    "\u{FF11}abc\u{FF19}",
+    "x\u{FF15}two",
+    // keycaps
+    "a1\u{FE0F}\u{20E3}bc9\u{20E3}d",
+    "1\u{FE0F}\u{20E3}2\u{FE0F}\u{20E3}x3",
+];
+
+#[derive(Args, Debug, Clone)]
+pub struct VectorsArgs {
+    /// Where to write the vectors, stdout by default
+    #[arg(long)]
+    out: Option<PathBuf>,
+}
+
+fn presets() -> Vec<(&'static str, ScanOptions)> {
+    vec![
+        ("default", ScanOptions::default()),
+        (
+            "unicode_digits",
+            ScanOptions {
+                unicode_digits: true,
+                ..ScanOptions::default()
+            },
+        ),
+        (
+            "fullwidth_digits",
+            ScanOptions {
+                fullwidth_digits: true,
+                ..ScanOptions::default()
+            },
+        ),
+        (
+            "keycap_digits",
+            ScanOptions {
+                keycap_digits: true,
+                ..ScanOptions::default()
+            },
+        ),
+        (
+            "isolated_digits_only",
+            ScanOptions {
+                isolated_digits_only: true,
+                ..ScanOptions::default()
+            },
+        ),
+        (
+            "fuzzy",
+            ScanOptions {
+                fuzzy: 1,
+                ..ScanOptions::default()
+            },
+        ),
+    ]
+}
+
+pub fn run(args: &VectorsArgs) -> Result<(), String> {
+    let document = render();
+
+    if let Some(path) = &args.out {
+        return fs::write(path, document).map_err(|e| format!("Fail to write {}: {e:?}", path.display()));
+    }
+
+    print!("{document}");
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct Document {
+    version: u32,
+    generator: &'static str,
+    presets: Vec<Preset>,
+}
+
+#[derive(Serialize)]
+struct Preset {
+    name: &'static str,
+    options: Options,
+    vectors: Vec<Vector>,
+}
+
+/// The scan options of a preset
+#[derive(Serialize)]
+#[allow(clippy::struct_excessive_bools)]
+struct Options {
+    unicode_digits: bool,
+    fullwidth_digits: bool,
+    keycap_digits: bool,
+    isolated_digits_only: bool,
+    fuzzy: usize,
+}
+
+/// The expected value of a line, the matches of a value or why there is none
+#[derive(Serialize)]
+struct Vector {
+    line: &'static str,
+    value: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    first: Option<Match>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last: Option<Match>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<LineError>,
+}
+
+/// The vectors of every preset as a JSON document
+fn render() -> String {
+    let presets = presets()
+        .into_iter()
+        .map(|(name, options)| Preset {
+            name,
+            options: Options {
+                unicode_digits: options.unicode_digits,
+                fullwidth_digits: options.fullwidth_digits,
+                keycap_digits: options.keycap_digits,
+                isolated_digits_only: options.isolated_digits_only,
+                fuzzy: options.fuzzy,
+            },
+            vectors: LINES.iter().map(|line| vector(line, &options)).collect(),
+        })
+        .collect();
+    let document = Document {
+        version: FORMAT_VERSION,
+        generator: concat!(env!("CARGO_PKG_NAME"), " ", env!("CARGO_PKG_VERSION")),
+        presets,
+    };
+
+    serde_json::to_string_pretty(&document).unwrap_or_default() + "\n"
+}
+
+fn vector(line: &'static str, options: &ScanOptions) -> Vector {
+    let (value, first, last, error) = match extract_line_with(line, options) {
+        Ok(value) => (Some(value.value), Some(value.first), Some(value.last), None),
+        Err(e) => (None, None, None, Some(e)),
+    };
+
+    Vector {
+        line,
+        value,
+        first,
+        last,
+        error,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let document: serde_json::Value = serde_json::from_str(&render()).unwrap();
+        let presets = document["presets"].as_array().unwrap();
+        assert_eq!(presets.len(), self::presets().len());
+        assert!(presets
+            .iter()
+            .all(|preset| preset["vectors"].as_array().unwrap().len() == LINES.len()));
+
+        let vectors = &presets[0]["vectors"];
+        let two1nine = serde_json::json!({
+            "line": "two1nine",
+            "value": 29,
+            "first": {"digit": 2, "start": 0, "end": 3, "kind": "word"},
+            "last": {"digit": 9, "start": 4, "end": 8, "kind": "word"},
+        });
+        assert!(vectors.as_array().unwrap().contains(&two1nine));
+        assert!(vectors
+            .as_array()
+            .unwrap()
+            .contains(&serde_json::json!({"line": "", "value": null, "error": "empty"})));
+    }
+}
@@ -0,0 +1,220 @@
+use std::collections::HashMap;
+
+use clap::ValueEnum;
+use find_digits::{Extractor, LineError, LineValue, Match, MatchKind, Overlap, ScanOptions};
+use regex::Regex;
+
+/// What `--verify` does with the lines the fast extractor and the reference one disagree on
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Verification {
+    /// report every line and go on
+    #[default]
+    Report,
+    /// report every line and exit with an error after the report
+    Fail,
+}
+
+/// The engine extracting the values of the lines
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExtractorKind {
+    /// the scanners of the library
+    #[default]
+    Scanner,
+    /// the regular expressions of the reference extractor, slow
+    Regex,
+}
+
+impl ExtractorKind {
+    pub fn build(self, options: &ScanOptions) -> Result<Box<dyn Extractor>, String> {
+        Ok(match self {
+            Self::Scanner => Box::new(options.clone()),
+            Self::Regex => Box::new(Reference::new(options)?),
+        })
+    }
+}
+
+/// Slow extractor of the calibration values by regular expressions, written from the definition only:
+/// the first digit is the leftmost match, the last digit is the match ending last, the longest one at a position.
+/// With [`Overlap::Consume`] the last digit is the last one of the leftmost non-overlapping matches instead
+#[derive(Debug)]
+pub struct Reference {
+    /// the leftmost match, the longer words are tried first
+    first: Regex,
+    /// the match ending at the end of the searched text
+    last: Regex,
+    /// the digits of the words
+    digits: HashMap<String, char>,
+    overlap: Overlap,
+}
+
+/// The byte span and the digit of a match
+type Token = (usize, usize, char);
+
+impl Reference {
+    /// Only the ASCII digits and the words of the dictionary are supported
+    pub fn new(options: &ScanOptions) -> Result<Self, String> {
+        if options.unicode_digits
+            || options.fullwidth_digits
+            || options.keycap_digits
+            || options.isolated_digits_only
+            || options.fuzzy > 0
+            || options.word_boundaries
+        {
+            return Err(
+                "The reference extractor supports the ASCII digits and the words of the dictionary only".to_owned()
+            );
+        }
+
+        let mut words: Vec<&(String, char)> = options.dictionary().words().iter().collect();
+        words.sort_by_key(|(word, _)| std::cmp::Reverse(word.len()));
+        let alternatives: Vec<String> = words
+            .iter()
+            .map(|(word, _)| regex::escape(word))
+            .chain(["[0-9]".to_owned()])
+            .collect();
+        let pattern = format!("(?:{})", alternatives.join("|"));
+        let error = |e: regex::Error| format!("Fail to build the reference extractor: {e}");
+
+        Ok(Self {
+            first: Regex::new(&pattern).map_err(error)?,
+            last: Regex::new(&format!("{pattern}$")).map_err(error)?,
+            digits: words.into_iter().cloned().collect(),
+            overlap: options.overlap,
+        })
+    }
+
+    /// The first and the last matches of the line
+    fn matches(&self, line: &str) -> Option<(Token, Token)> {
+        let first = self.first.find(line)?;
+        let last = if self.overlap == Overlap::Consume {
+            let last = self.first.find_iter(line).last()?;
+            self.token(last.as_str(), last.start())
+        } else {
+            // the last match doesn't begin before the first one, it ends at the latest position
+            let last = (first.start()..=line.len())
+                .rev()
+                .filter(|&end| line.is_char_boundary(end))
+                .find_map(|end| self.last.find(&line[first.start()..end]))?;
+            self.token(last.as_str(), first.start() + last.start())
+        };
+
+        Some((self.token(first.as_str(), first.start()), last))
+    }
+
+    fn token(&self, text: &str, start: usize) -> Token {
+        let digit = self.digits.get(text).copied().or_else(|| text.chars().next());
+        (start, start + text.len(), digit.unwrap_or_default())
+    }
+
+    /// Compare the result of the fast extractor with the reference one, the description of the difference otherwise
+    pub fn check(&self, line: &str, result: &Result<LineValue, LineError>) -> Result<(), String> {
+        let fast = result
+            .as_ref()
+            .ok()
+            .map(|value| [value.first, value.last].map(|m| (m.start, m.end, m.digit)));
+        let reference = self.matches(line).map(|(first, last)| [first, last]);
+
+        if fast == reference {
+            Ok(())
+        } else {
+            Err(format!("fast matches {fast:?} differ from reference matches {reference:?}"))
+        }
+    }
+}
+
+impl Extractor for Reference {
+    fn extract(&self, line: &str) -> Result<LineValue, LineError> {
+        if line.is_empty() {
+            return Err(LineError::Empty);
+        }
+
+        let (first, last) = self.matches(line).ok_or(LineError::NoDigits)?;
+        let [first, last] = [first, last].map(|(start, end, digit)| Match {
+            digit,
+            start,
+            end,
+            kind: if line[start..].starts_with(|c: char| c.is_ascii_digit()) {
+                MatchKind::Digit
+            } else {
+                MatchKind::Word
+            },
+        });
+
+        Ok(LineValue {
+            value: Match::combine(&first, &last),
+            first,
+            last,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use find_digits::{extract_line_with, Dictionary, Generator};
+
+    use super::*;
+
+    #[test]
+    fn test_extract() {
+        let reference = Reference::new(&ScanOptions::default()).unwrap();
+        let strings = [
+            "two1nine",
+            "eightwothree",
+            "xtwone3four",
+            "treb7uchet",
+            "oneight",
+            "abcdefg",
+            "",
+        ];
+        let expected = [
+            Some(((0, 3, '2'), (4, 8, '9'))),
+            Some(((0, 5, '8'), (7, 12, '3'))),
+            Some(((1, 4, '2'), (7, 11, '4'))),
+            Some(((4, 5, '7'), (4, 5, '7'))),
+            Some(((0, 3, '1'), (2, 7, '8'))),
+            None,
+            None,
+        ];
+
+        for (pos, line) in strings.into_iter().enumerate() {
+            assert_eq!(reference.matches(line), expected[pos], "{line}");
+        }
+    }
+
+    #[test]
+    fn test_check() {
+        let dictionary = Dictionary::parse("un 1\nune 8\nné 2\n").unwrap();
+        let presets = [
+            ScanOptions::default(),
+            ScanOptions {
+                dictionary: Some(Arc::new(dictionary)),
+                ..ScanOptions::default()
+            },
+            ScanOptions {
+                overlap: Overlap::Consume,
+                ..ScanOptions::default()
+            },
+        ];
+        let mut generator = Generator::new(11);
+
+        for options in presets {
+            let reference = Reference::new(&options).unwrap();
+            for line in (0..2000)
+                .map(|_| generator.next_line())
+                .chain(["uné3unet".to_owned(), String::new()])
+            {
+                let result = extract_line_with(&line, &options);
+                assert_eq!(reference.check(&line, &result), Ok(()), "{line}");
+                assert_eq!(reference.extract(&line), result, "{line}");
+            }
+        }
+
+        assert!(Reference::new(&ScanOptions {
+            fuzzy: 1,
+            ..ScanOptions::default()
+        })
+        .is_err());
+    }
+}
@@ -0,0 +1,178 @@
+//! The JavaScript bindings, the module is built by
+//! `cargo rustc --release --lib --target wasm32-unknown-unknown --no-default-features --features wasm
+//! --crate-type cdylib` and its glue code by `wasm-bindgen --target web`
+//!
+//! The offsets are of the UTF-16 code units of the strings, as JavaScript indexes them, not of their UTF-8 bytes.
+
+use std::mem;
+
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::{Match, Summary};
+
+/// A digit of a line, `line.slice(start, end)` is its text
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Digit {
+    pub digit: u8,
+    pub start: usize,
+    pub end: usize,
+    /// spelled with letters
+    #[wasm_bindgen(js_name = isWord)]
+    pub is_word: bool,
+}
+
+impl Digit {
+    fn new(line: &str, m: &Match) -> Self {
+        let units = |position: usize| line[..position].encode_utf16().count();
+        Self {
+            digit: m.value(),
+            start: units(m.start),
+            end: units(m.end),
+            is_word: m.is_word(),
+        }
+    }
+}
+
+/// The value of a line with its first and last digits
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineValue {
+    pub value: u32,
+    pub first: Digit,
+    pub last: Digit,
+}
+
+/// The calibration value of the line, `undefined` without digits
+#[wasm_bindgen(js_name = extractNumber)]
+#[must_use]
+pub fn extract_number(line: &str) -> Option<u32> {
+    crate::extract_number(line)
+}
+
+/// The value of the line with its first and last digits, `undefined` without digits
+#[wasm_bindgen(js_name = extractLine)]
+#[must_use]
+pub fn extract_line(line: &str) -> Option<LineValue> {
+    crate::extract_line(line).ok().map(|value| LineValue {
+        value: value.value,
+        first: Digit::new(line, &value.first),
+        last: Digit::new(line, &value.last),
+    })
+}
+
+/// The totals of a text pushed in chunks, e.g. the decoded chunks of `File.stream()`
+///
+/// A line may be split between the chunks, the last line is summed by `finish`.
+/// The totals are `BigInt`s, the total amount may exceed `Number.MAX_SAFE_INTEGER`.
+#[wasm_bindgen]
+#[derive(Debug, Default)]
+pub struct StreamingSum {
+    summary: Summary,
+    /// the beginning of the line continued by the next chunk
+    pending: String,
+}
+
+#[wasm_bindgen]
+impl StreamingSum {
+    #[wasm_bindgen(constructor)]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sum the complete lines of the chunk
+    pub fn push(&mut self, chunk: &str) {
+        let mut rest = chunk;
+        while let Some(end) = rest.find('\n') {
+            self.pending.push_str(&rest[..end]);
+            let line = mem::take(&mut self.pending);
+            self.add_line(&line);
+            rest = &rest[end + 1..];
+        }
+        self.pending.push_str(rest);
+    }
+
+    /// Sum the last line when the text doesn't end with a line break
+    pub fn finish(&mut self) {
+        if !self.pending.is_empty() {
+            let line = mem::take(&mut self.pending);
+            self.add_line(&line);
+        }
+    }
+
+    #[wasm_bindgen(getter, js_name = parsedLines)]
+    #[must_use]
+    pub fn parsed_lines(&self) -> u64 {
+        self.summary.parsed_lines
+    }
+
+    #[wasm_bindgen(getter, js_name = incorrectLines)]
+    #[must_use]
+    pub fn incorrect_lines(&self) -> u64 {
+        self.summary.incorrect_lines
+    }
+
+    #[wasm_bindgen(getter, js_name = totalSum)]
+    #[must_use]
+    pub fn total_sum(&self) -> u64 {
+        self.summary.total_sum
+    }
+
+    fn add_line(&mut self, line: &str) {
+        let line = crate::trim_line(line, self.summary.parsed_lines == 0);
+        self.summary.parsed_lines += 1;
+        match crate::extract_number(line) {
+            Some(value) => self.summary.add_value(value),
+            None => self.summary.incorrect_lines += 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_extract_line() {
+        let value = extract_line("caf\u{e9}\u{1F600}two1nine").unwrap();
+        assert_eq!(value.value, 29);
+        assert_eq!(
+            (value.first, value.last),
+            (
+                Digit {
+                    digit: 2,
+                    start: 6,
+                    end: 9,
+                    is_word: true
+                },
+                Digit {
+                    digit: 9,
+                    start: 10,
+                    end: 14,
+                    is_word: true
+                }
+            )
+        );
+        assert_eq!(extract_line("abcdefg"), None);
+    }
+
+    #[test]
+    fn test_streaming_sum() {
+        let text = "two1nine\r\nabcdefg\nxtwone3four\n\ntreb7uchet";
+
+        for chunk_size in [1, 3, 7, text.len()] {
+            let mut sum = StreamingSum::new();
+            for chunk in text.as_bytes().chunks(chunk_size) {
+                sum.push(std::str::from_utf8(chunk).unwrap());
+            }
+            sum.finish();
+
+            assert_eq!(
+                (sum.parsed_lines(), sum.incorrect_lines(), sum.total_sum()),
+                (5, 2, 29 + 24 + 77),
+                "{chunk_size}"
+            );
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use std::{
+    path::Path,
+    sync::mpsc::{self, Receiver, RecvTimeoutError},
+    time::Duration,
+};
+
+use notify::{Event, RecursiveMode, Watcher};
+
+use crate::signals;
+
+/// The events of a single save (truncate, write, rename, ...) come in a burst, it's awaited as a whole
+const DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// Process the file and process it again on every change, until the watching fails or Ctrl-C
+pub fn run(file: &str, mut process: impl FnMut()) -> Result<(), String> {
+    let path = Path::new(file);
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender).map_err(|e| format!("Fail to watch file {file}: {e}"))?;
+
+    // editors often save a new file in place of the old one, so the directory is watched
+    let directory = match path.parent() {
+        Some(directory) if !directory.as_os_str().is_empty() => directory,
+        _ => Path::new("."),
+    };
+    watcher
+        .watch(directory, RecursiveMode::NonRecursive)
+        .map_err(|e| format!("Fail to watch file {file}: {e}"))?;
+
+    loop {
+        if path.exists() {
+            process();
+        } else {
+            println!("File {file} doesn't exist");
+        }
+
+        println!("Watching file {file} for changes");
+        if !wait_change(&receiver, path)? {
+            return Ok(());
+        }
+    }
+}
+
+/// `false` on Ctrl-C
+fn wait_change(receiver: &Receiver<notify::Result<Event>>, path: &Path) -> Result<bool, String> {
+    loop {
+        let event = match receiver.recv_timeout(DEBOUNCE) {
+            Ok(event) => event.map_err(|e| format!("Fail to watch file {}: {e}", path.display()))?,
+            Err(RecvTimeoutError::Timeout) if signals::interrupted() => return Ok(false),
+            Err(RecvTimeoutError::Timeout) => continue,
+            Err(RecvTimeoutError::Disconnected) => return Err("File watcher stopped".to_owned()),
+        };
+
+        if is_change(&event, path) {
+            break;
+        }
+    }
+
+    while receiver.recv_timeout(DEBOUNCE).is_ok() {}
+    Ok(true)
+}
+
+/// Any event of the file but reading it, e.g. by the processing itself
+fn is_change(event: &Event, path: &Path) -> bool {
+    !event.kind.is_access()
+        && event
+            .paths
+            .iter()
+            .any(|changed| changed.file_name() == path.file_name())
+}
+
+#[cfg(test)]
+mod test {
+    use std::{fs, thread};
+
+    use notify::event::{AccessKind, CreateKind, EventKind, ModifyKind};
+
+    use super::*;
+    use crate::testing::temp_dir;
+
+    #[test]
+    fn test_is_change() {
+        let path = Path::new("inputs/day1.txt");
+        let events = [
+            (EventKind::Modify(ModifyKind::Any), "/work/inputs/day1.txt"),
+            (EventKind::Create(CreateKind::File), "inputs/day1.txt"),
+            (EventKind::Access(AccessKind::Any), "inputs/day1.txt"),
+            (EventKind::Modify(ModifyKind::Any), "inputs/day2.txt"),
+        ];
+        let expected = [true, true, false, false];
+
+        for (pos, (kind, changed)) in events.into_iter().enumerate() {
+            let event = Event::new(kind).add_path(changed.into());
+            assert_eq!(is_change(&event, path), expected[pos], "{changed}");
+        }
+    }
+
+    #[test]
+    fn test_wait_change() {
+        let directory = temp_dir();
+        let path = directory.path().join("input.txt");
+
+        let (sender, receiver) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(sender).unwrap();
+        watcher.watch(directory.path(), RecursiveMode::NonRecursive).unwrap();
+
+        thread::scope(|scope| {
+            scope.spawn(|| {
+                thread::sleep(DEBOUNCE);
+                fs::write(&path, "two1nine\n").unwrap();
+            });
+
+            assert_eq!(wait_change(&receiver, &path), Ok(true));
+        });
+    }
+}
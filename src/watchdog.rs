@@ -0,0 +1,122 @@
+use std::{
+    fmt,
+    sync::mpsc::{self, RecvTimeoutError},
+    thread,
+    time::{Duration, Instant},
+};
+
+use find_digits::counting::ByteCounter;
+
+/// How often the progress of the guarded job is checked
+const CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Limits {
+    /// the whole job must complete within this time
+    pub timeout: Option<Duration>,
+    /// the job must read something at least once in this period
+    pub stall_timeout: Option<Duration>,
+}
+
+impl Limits {
+    pub fn is_unlimited(&self) -> bool {
+        self.timeout.is_none() && self.stall_timeout.is_none()
+    }
+}
+
+/// Why the guarded job was abandoned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Abort {
+    Timeout { limit: Duration, offset: u64 },
+    Stalled { idle: Duration, offset: u64 },
+    Panicked,
+}
+
+impl fmt::Display for Abort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Timeout { limit, offset } => write!(f, "timed out after {limit:?} at byte offset {offset}"),
+            Self::Stalled { idle, offset } => write!(f, "no progress for {idle:?} at byte offset {offset}"),
+            Self::Panicked => write!(f, "processing panicked"),
+        }
+    }
+}
+
+/// Run the job on its own thread while the calling thread watches the progress counter
+///
+/// A job blocked in a read (e.g. a hung NFS mount) can't be interrupted,
+/// so on abort it's left behind and the caller is expected to skip the input or exit.
+pub fn guard<T, F>(limits: Limits, progress: &ByteCounter, job: F) -> Result<T, Abort>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        // the receiver is gone when the job was abandoned
+        let _ = sender.send(job());
+    });
+
+    let start_timestamp = Instant::now();
+    let mut progress_timestamp = start_timestamp;
+    let mut offset = progress.get();
+
+    loop {
+        match receiver.recv_timeout(CHECK_INTERVAL) {
+            Ok(result) => return Ok(result),
+            Err(RecvTimeoutError::Disconnected) => return Err(Abort::Panicked),
+            Err(RecvTimeoutError::Timeout) => {},
+        }
+
+        let current = progress.get();
+        if current != offset {
+            offset = current;
+            progress_timestamp = Instant::now();
+        }
+
+        if let Some(limit) = limits.timeout {
+            if start_timestamp.elapsed() > limit {
+                return Err(Abort::Timeout { limit, offset });
+            }
+        }
+
+        if let Some(stall_timeout) = limits.stall_timeout {
+            let idle = progress_timestamp.elapsed();
+            if idle > stall_timeout {
+                return Err(Abort::Stalled { idle, offset });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_guard() {
+        let progress = ByteCounter::default();
+
+        assert_eq!(guard(Limits::default(), &progress, || 42), Ok(42));
+
+        let stalled = guard(
+            Limits {
+                timeout: None,
+                stall_timeout: Some(Duration::from_millis(200)),
+            },
+            &progress,
+            || thread::sleep(Duration::from_secs(5)),
+        );
+        assert!(matches!(stalled, Err(Abort::Stalled { offset: 0, .. })));
+
+        let timed_out = guard(
+            Limits {
+                timeout: Some(Duration::from_millis(200)),
+                stall_timeout: None,
+            },
+            &progress,
+            || thread::sleep(Duration::from_secs(5)),
+        );
+        assert!(matches!(timed_out, Err(Abort::Timeout { .. })));
+    }
+}
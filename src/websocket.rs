@@ -0,0 +1,345 @@
+use std::{
+    io::{self, BufRead, Write},
+    str,
+    sync::{mpsc, Mutex, PoisonError},
+    thread,
+    time::Duration,
+};
+
+use find_digits::Summary;
+use sha1::{Digest, Sha1};
+
+use crate::{
+    process::Processing,
+    response::{self, LineAnswer, Outcome, Snapshot, StatsBody, Totals},
+    serve::Stats,
+};
+
+/// How often the aggregate snapshots are pushed to the clients
+pub const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The largest accepted message
+const MAX_MESSAGE: usize = 16 << 20;
+
+/// Appended to the key of the client to prove the handshake is understood, by RFC 6455
+const GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+const OPCODE_CONTINUATION: u8 = 0x0;
+const OPCODE_TEXT: u8 = 0x1;
+const OPCODE_BINARY: u8 = 0x2;
+const OPCODE_CLOSE: u8 = 0x8;
+const OPCODE_PING: u8 = 0x9;
+const OPCODE_PONG: u8 = 0xA;
+
+/// The response switching the connection to the WebSocket protocol
+pub fn handshake(key: &str) -> String {
+    format!(
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+        accept_key(key)
+    )
+}
+
+/// Process the lines of the received messages, every line is answered by `{"line": N, "value": V}`
+/// (`{"line": N, "error": E}` of a line failing, e.g. of `--on-no-digits error`)
+/// and the totals of the connection (added to `connection`) and of the server are pushed every `interval`
+pub fn session(
+    mut input: impl BufRead,
+    output: impl Write + Send,
+    processing: &Processing,
+    stats: &Mutex<Stats>,
+    connection: &Mutex<Summary>,
+    interval: Duration,
+) -> io::Result<()> {
+    let output = Mutex::new(output);
+    let send = |opcode, payload: &[u8]| {
+        write_frame(&mut *output.lock().unwrap_or_else(PoisonError::into_inner), opcode, payload)
+    };
+    let snapshot = || {
+        let connection = connection.lock().unwrap_or_else(PoisonError::into_inner);
+        let stats = stats.lock().unwrap_or_else(PoisonError::into_inner);
+        response::json(&Snapshot {
+            connection: Totals::from(&*connection),
+            totals: StatsBody::from(&*stats),
+        })
+    };
+
+    let (stop, stopped) = mpsc::channel::<()>();
+    thread::scope(|scope| {
+        scope.spawn(move || {
+            // the sender is dropped when the session ends
+            while stopped.recv_timeout(interval) == Err(mpsc::RecvTimeoutError::Timeout) {
+                if send(OPCODE_TEXT, snapshot().as_bytes()).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let result = receive(&mut input, processing, stats, connection, &send);
+        drop(stop);
+
+        let close = result?;
+        send(OPCODE_TEXT, snapshot().as_bytes())?;
+        match close {
+            Some(code) => send(OPCODE_CLOSE, &code),
+            None => Ok(()),
+        }
+    })
+}
+
+/// Answer the messages until the client closes the connection, the status of its close frame is returned
+fn receive(
+    input: &mut impl BufRead,
+    processing: &Processing,
+    stats: &Mutex<Stats>,
+    connection: &Mutex<Summary>,
+    send: &impl Fn(u8, &[u8]) -> io::Result<()>,
+) -> io::Result<Option<Vec<u8>>> {
+    let mut cache = processing.line_cache();
+    let mut message = Vec::new();
+    let mut number = 0;
+
+    loop {
+        let Some((fin, opcode, payload)) = read_frame(input)? else {
+            return Ok(None);
+        };
+
+        match opcode {
+            OPCODE_PING => send(OPCODE_PONG, &payload)?,
+            OPCODE_PONG => {},
+            // the status code of the client is echoed
+            OPCODE_CLOSE => return Ok(Some(payload.get(..2).unwrap_or_default().to_vec())),
+            OPCODE_TEXT | OPCODE_BINARY | OPCODE_CONTINUATION => {
+                if message.len() + payload.len() > MAX_MESSAGE {
+                    return Err(invalid("message too large"));
+                }
+                message.extend_from_slice(&payload);
+                if !fin {
+                    continue;
+                }
+
+                // the lines of a message are the ones of a body of `POST /extract`, the empty ones too
+                let mut lines: Vec<&[u8]> = message.split(|&byte| byte == b'\n').collect();
+                if lines.last().is_some_and(|line| line.is_empty()) {
+                    lines.pop();
+                }
+
+                let mut summary = processing.summary();
+                for line in lines {
+                    let line = line.strip_suffix(b"\r").unwrap_or(line);
+                    summary.parsed_lines += 1;
+
+                    let value = processing.process_bytes("websocket", number, line, &mut summary, &mut cache);
+                    let outcome = match &value {
+                        Ok(value) => Outcome::Value(*value),
+                        Err(e) => Outcome::Error(e),
+                    };
+                    let answer = response::json(&LineAnswer { line: number, outcome });
+                    send(OPCODE_TEXT, answer.as_bytes())?;
+                    number += 1;
+                }
+                message.clear();
+
+                connection
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .merge(&summary);
+                let mut stats = stats.lock().unwrap_or_else(PoisonError::into_inner);
+                stats.requests += 1;
+                stats.summary.merge(&summary);
+            },
+            _ => return Err(invalid("unknown opcode")),
+        }
+    }
+}
+
+/// The final flag, the opcode and the unmasked payload, `None` when the connection is closed between the frames
+fn read_frame(input: &mut impl BufRead) -> io::Result<Option<(bool, u8, Vec<u8>)>> {
+    let mut header = [0; 2];
+    if input.fill_buf()?.is_empty() {
+        return Ok(None);
+    }
+    input.read_exact(&mut header)?;
+
+    let (fin, opcode, masked) = (header[0] & 0x80 != 0, header[0] & 0x0F, header[1] & 0x80 != 0);
+    if !masked {
+        return Err(invalid("the frames of a client must be masked"));
+    }
+
+    let length = match header[1] & 0x7F {
+        126 => {
+            let mut length = [0; 2];
+            input.read_exact(&mut length)?;
+            u64::from(u16::from_be_bytes(length))
+        },
+        127 => {
+            let mut length = [0; 8];
+            input.read_exact(&mut length)?;
+            u64::from_be_bytes(length)
+        },
+        length => u64::from(length),
+    };
+    let length = usize::try_from(length)
+        .ok()
+        .filter(|&length| length <= MAX_MESSAGE)
+        .ok_or_else(|| invalid("message too large"))?;
+
+    let mut mask = [0; 4];
+    input.read_exact(&mut mask)?;
+    let mut payload = vec![0; length];
+    input.read_exact(&mut payload)?;
+    for (pos, byte) in payload.iter_mut().enumerate() {
+        *byte ^= mask[pos % 4];
+    }
+
+    Ok(Some((fin, opcode, payload)))
+}
+
+/// A final unmasked frame, as the server sends them
+fn write_frame(output: &mut impl Write, opcode: u8, payload: &[u8]) -> io::Result<()> {
+    let mut frame = vec![0x80 | opcode];
+    match (u8::try_from(payload.len()), u16::try_from(payload.len())) {
+        (Ok(length @ 0..=125), _) => frame.push(length),
+        (_, Ok(length)) => {
+            frame.push(126);
+            frame.extend_from_slice(&length.to_be_bytes());
+        },
+        _ => {
+            frame.push(127);
+            frame.extend_from_slice(&(payload.len() as u64).to_be_bytes());
+        },
+    }
+    frame.extend_from_slice(payload);
+
+    // a single write, the stream isn't buffered
+    output.write_all(&frame)
+}
+
+fn accept_key(key: &str) -> String {
+    base64(&Sha1::digest(format!("{}{GUID}", key.trim())))
+}
+
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let triple = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+
+        for (pos, shift) in [18, 12, 6, 0].into_iter().enumerate() {
+            if pos <= chunk.len() {
+                encoded.push(ALPHABET[(triple >> shift & 0x3F) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+
+    encoded
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_owned())
+}
+
+#[cfg(test)]
+mod test {
+    use clap::Parser;
+
+    use super::*;
+
+    /// A frame of a client: the frame of the server masked by an arbitrary key
+    fn client_frame(fin: bool, opcode: u8, payload: &[u8]) -> Vec<u8> {
+        let mask = [0x37, 0xFA, 0x21, 0x3D];
+        let mut frame = Vec::new();
+        write_frame(&mut frame, opcode, payload).unwrap();
+        if !fin {
+            frame[0] &= 0x7F;
+        }
+        frame[1] |= 0x80;
+
+        let header = frame.len() - payload.len();
+        frame.splice(header..header, mask);
+        for (pos, byte) in frame[header + 4..].iter_mut().enumerate() {
+            *byte ^= mask[pos % 4];
+        }
+        frame
+    }
+
+    /// The opcodes and the payloads of the frames of the server
+    fn server_frames(mut data: &[u8]) -> Vec<(u8, String)> {
+        let mut frames = Vec::new();
+        while !data.is_empty() {
+            let (opcode, mut length, mut header) = (data[0] & 0x0F, usize::from(data[1]), 2);
+            if length == 126 {
+                length = usize::from(u16::from_be_bytes([data[2], data[3]]));
+                header = 4;
+            }
+            frames.push((opcode, String::from_utf8_lossy(&data[header..header + length]).into_owned()));
+            data = &data[header + length..];
+        }
+        frames
+    }
+
+    #[test]
+    fn test_accept_key() {
+        // the example of RFC 6455
+        assert_eq!(accept_key("dGhlIHNhbXBsZSBub25jZQ=="), "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+
+        let strings: [&[u8]; 5] = [b"", b"f", b"fo", b"foo", b"foobar"];
+        let expected = ["", "Zg==", "Zm8=", "Zm9v", "Zm9vYmFy"];
+        for (pos, data) in strings.into_iter().enumerate() {
+            assert_eq!(base64(data), expected[pos]);
+        }
+    }
+
+    #[test]
+    fn test_session() {
+        let processing = Processing::new(&crate::Args::parse_from(["find_digits", "serve"])).unwrap();
+        let stats = Mutex::new(Stats::default());
+
+        let long_line = "x".repeat(200) + "7";
+        let input = [
+            client_frame(true, OPCODE_TEXT, b"two1nine\n\nabcdefg\n"),
+            client_frame(true, OPCODE_PING, b"ping"),
+            client_frame(false, OPCODE_TEXT, b"eigh"),
+            client_frame(true, OPCODE_CONTINUATION, b"twothree"),
+            client_frame(true, OPCODE_TEXT, long_line.as_bytes()),
+            client_frame(true, OPCODE_CLOSE, &1000u16.to_be_bytes()),
+        ]
+        .concat();
+        let mut output = Vec::new();
+        session(&input[..], &mut output, &processing, &stats, &Mutex::default(), Duration::from_secs(3600)).unwrap();
+
+        let expected = [
+            (OPCODE_TEXT, "{\"line\":0,\"value\":29}".to_owned()),
+            (OPCODE_TEXT, "{\"line\":1,\"value\":null}".to_owned()),
+            (OPCODE_TEXT, "{\"line\":2,\"value\":null}".to_owned()),
+            (OPCODE_PONG, "ping".to_owned()),
+            (OPCODE_TEXT, "{\"line\":3,\"value\":83}".to_owned()),
+            (OPCODE_TEXT, "{\"line\":4,\"value\":77}".to_owned()),
+            (
+                OPCODE_TEXT,
+                "{\"connection\":{\"sum\":189,\"parsed_lines\":5,\"incorrect_lines\":2},\"totals\":{\"requests\":3,\
+                 \"sum\":189,\"parsed_lines\":5,\"incorrect_lines\":2}}"
+                    .to_owned(),
+            ),
+            (OPCODE_CLOSE, String::from_utf8_lossy(&1000u16.to_be_bytes()).into_owned()),
+        ];
+        assert_eq!(server_frames(&output), expected);
+    }
+
+    #[test]
+    fn test_unmasked_frame() {
+        let processing = Processing::new(&crate::Args::parse_from(["find_digits", "serve"])).unwrap();
+        let input = [0x81, 0x01, b'7'];
+
+        let (stats, connection) = (Mutex::default(), Mutex::default());
+        let result = session(&input[..], Vec::new(), &processing, &stats, &connection, Duration::from_secs(3600));
+        assert_eq!(result.map_err(|e| e.kind()), Err(io::ErrorKind::InvalidData));
+    }
+}